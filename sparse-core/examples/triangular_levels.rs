@@ -0,0 +1,63 @@
+//! Timing of a level-scheduled triangular solve against forward substitution.
+//!
+//! Run with `cargo run --release --example triangular_levels`.
+
+use sparse_core::local::sparse::csr_mat::CsrMatrix;
+use sparse_core::local::sparse::triangular::{Diagonal, Triangle};
+use std::time::Instant;
+
+/// A lower triangular matrix of dimension `n` whose rows depend on eight rows of the
+/// previous block of `block` rows, so that every level holds `block` rows.
+fn lower_triangular(n: usize, block: usize) -> CsrMatrix<f64> {
+    let mut indptr = vec![0];
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    for row in 0..n {
+        if row >= block {
+            for offset in (0..8).rev() {
+                if let Some(col) = (row - block).checked_sub(31 * offset) {
+                    indices.push(col);
+                    data.push(-0.1);
+                }
+            }
+        }
+        indices.push(row);
+        data.push(2.0);
+        indptr.push(indices.len());
+    }
+    CsrMatrix::try_new((n, n), indices, indptr, data).unwrap()
+}
+
+fn main() {
+    let n = 2_000_000;
+    let mat = lower_triangular(n, 200_000);
+    let levels = mat.level_sets(Triangle::Lower).unwrap();
+    let b: Vec<f64> = (0..n).map(|index| (index % 13) as f64).collect();
+
+    let mut expected = b.clone();
+    let start = Instant::now();
+    mat.solve_triangular(Triangle::Lower, Diagonal::NonUnit, &mut expected)
+        .unwrap();
+    let sequential = start.elapsed();
+    println!("Forward substitution: {:?}", sequential);
+
+    let available = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut thread_counts = vec![1, 2, 4, available];
+    thread_counts.sort_unstable();
+    thread_counts.dedup();
+    for threads in thread_counts {
+        let mut x = b.clone();
+        let start = Instant::now();
+        mat.solve_triangular_levels(&levels, Diagonal::NonUnit, &mut x, threads)
+            .unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(x, expected);
+        println!(
+            "{} levels on {} threads: {:?}, speedup {:.2}",
+            levels.number_of_levels(),
+            threads,
+            elapsed,
+            sequential.as_secs_f64() / elapsed.as_secs_f64()
+        );
+    }
+}
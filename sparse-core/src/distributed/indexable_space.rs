@@ -5,9 +5,11 @@ use std::marker::PhantomData;
 
 use super::index_layout::DistributedIndexLayout;
 use super::indexable_vector::DistributedIndexableVector;
-use sparse_traits::types::{IndexType, Scalar};
-use sparse_traits::linalg::Inner;
-use sparse_traits::{Element, IndexLayout, IndexableSpace, InnerProductSpace};
+use sparse_traits::types::{IndexType, Scalar, SparseLinAlgResult};
+use sparse_traits::linalg::{Fill, Inner, MultSumInto, ScalarMult};
+use sparse_traits::{
+    Element, ElementView, ElementViewMut, IndexLayout, IndexableSpace, InnerProductSpace,
+};
 
 pub struct DistributedIndexableVectorSpace<'comm, T: Scalar + Equivalence, C: Communicator> {
     index_layout: &'comm DistributedIndexLayout<'comm, C>,
@@ -30,7 +32,7 @@ pub struct DistributedIndexableVectorSpaceElement<
     C: Communicator,
 > {
     space: &'space DistributedIndexableVectorSpace<'comm, T, C>,
-    data: super::indexable_vector::DistributedIndexableVector<'comm, T, C>,
+    data: DistributedIndexableVector<'comm, T, C>,
 }
 
 impl<'space, 'comm, T: Scalar + Equivalence, C: Communicator> Element
@@ -39,18 +41,16 @@ where
     T::Real: Equivalence,
 {
     type Space = DistributedIndexableVectorSpace<'comm, T, C>;
-    type View<'b> = &'b super::indexable_vector::DistributedIndexableVector<'comm, T, C> where Self: 'b;
-    type ViewMut<'b> = &'b mut super::indexable_vector::DistributedIndexableVector<'comm, T, C> where Self: 'b;
 
     fn space(&self) -> &Self::Space {
         self.space
     }
 
-    fn view<'b>(&'b self) -> Self::View<'b> {
+    fn view<'b>(&'b self) -> &'b DistributedIndexableVector<'comm, T, C> {
         &self.data
     }
 
-    fn view_mut<'b>(&'b mut self) -> Self::ViewMut<'b> {
+    fn view_mut<'b>(&'b mut self) -> &'b mut DistributedIndexableVector<'comm, T, C> {
         &mut self.data
     }
 }
@@ -62,6 +62,8 @@ where
 {
     type F = T;
     type E<'space> = DistributedIndexableVectorSpaceElement<'space, 'comm, T, C> where Self: 'space;
+    type View<'b> = &'b DistributedIndexableVector<'comm, T, C> where Self: 'b;
+    type ViewMut<'b> = &'b mut DistributedIndexableVector<'comm, T, C> where Self: 'b;
 
     fn create_element<'space>(&'space self) -> Self::E<'space> {
         DistributedIndexableVectorSpaceElement {
@@ -69,6 +71,56 @@ where
             data: DistributedIndexableVector::<'comm, T, C>::new(&self.index_layout),
         }
     }
+
+    fn is_compatible(&self, other: &Self) -> bool {
+        self.dimension() == other.dimension()
+    }
+
+    fn reborrow_view<'b, 'a: 'b>(x: &'b ElementView<'a, Self>) -> ElementView<'b, Self>
+    where
+        Self: 'a,
+    {
+        x
+    }
+
+    fn reborrow_view_mut<'b, 'a: 'b>(
+        y: &'b mut ElementViewMut<'a, Self>,
+    ) -> ElementViewMut<'b, Self>
+    where
+        Self: 'a,
+    {
+        y
+    }
+
+    fn axpy_inplace<'b>(
+        &self,
+        alpha: Self::F,
+        x: &ElementView<'b, Self>,
+        y: &mut ElementViewMut<'b, Self>,
+    ) -> SparseLinAlgResult<()>
+    where
+        Self: 'b,
+    {
+        y.mult_sum_into(x, alpha)
+    }
+
+    fn fill_inplace<'b>(
+        &self,
+        x: &ElementView<'b, Self>,
+        y: &mut ElementViewMut<'b, Self>,
+    ) -> SparseLinAlgResult<()>
+    where
+        Self: 'b,
+    {
+        y.fill(x)
+    }
+
+    fn scale_inplace<'b>(&self, alpha: Self::F, y: &mut ElementViewMut<'b, Self>)
+    where
+        Self: 'b,
+    {
+        y.scalar_mult(alpha)
+    }
 }
 
 impl<'a, T: Scalar + Equivalence, C: Communicator> IndexableSpace
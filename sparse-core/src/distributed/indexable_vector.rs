@@ -28,6 +28,10 @@ impl<'a, T: Scalar + Equivalence, C: Communicator> DistributedIndexableVector<'a
         &self.local
     }
 
+    fn local_mut(&mut self) -> &mut LocalIndexableVector<T> {
+        &mut self.local
+    }
+
     pub fn fill_from_root(
         &mut self,
         other: &Option<LocalIndexableVector<T>>,
@@ -191,3 +195,21 @@ where
         global_result
     }
 }
+
+impl<T: Scalar + Equivalence, C: Communicator> Fill for DistributedIndexableVector<'_, T, C> {
+    fn fill(&mut self, other: &Self) -> SparseLinAlgResult<()> {
+        self.local_mut().fill(other.local())
+    }
+}
+
+impl<T: Scalar + Equivalence, C: Communicator> ScalarMult for DistributedIndexableVector<'_, T, C> {
+    fn scalar_mult(&mut self, scalar: Self::T) {
+        self.local_mut().scalar_mult(scalar)
+    }
+}
+
+impl<T: Scalar + Equivalence, C: Communicator> MultSumInto for DistributedIndexableVector<'_, T, C> {
+    fn mult_sum_into(&mut self, other: &Self, scalar: Self::T) -> SparseLinAlgResult<()> {
+        self.local_mut().mult_sum_into(other.local(), scalar)
+    }
+}
@@ -1,5 +1,7 @@
 pub mod distributed;
 pub mod local;
+pub mod preconditioners;
+pub mod solvers;
 pub mod tools;
 
 #[cfg(test)]
@@ -1,5 +1,6 @@
 pub mod index_layout;
 pub mod indexable_space;
 pub mod indexable_vector;
+pub mod matrix_market;
 pub mod sparse;
 
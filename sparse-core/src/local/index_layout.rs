@@ -1,5 +1,6 @@
 use sparse_traits::{IndexLayout, IndexType, SparseLinAlgError, SparseLinAlgResult};
 
+#[derive(Debug, Clone)]
 pub struct LocalIndexLayout {
     size: IndexType,
 }
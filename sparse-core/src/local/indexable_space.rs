@@ -4,10 +4,14 @@ use std::marker::PhantomData;
 
 use super::index_layout::LocalIndexLayout;
 use super::indexable_vector::LocalIndexableVector;
-use sparse_traits::linalg::{Inner, Norm2};
-use sparse_traits::types::{IndexType, Scalar};
-use sparse_traits::{Element, IndexLayout, IndexableSpace, InnerProductSpace, NormedSpace};
+use sparse_traits::linalg::{Fill, Inner, MultSumInto, Norm2, ScalarMult};
+use sparse_traits::types::{IndexType, Scalar, SparseLinAlgResult};
+use sparse_traits::{
+    Element, ElementView, ElementViewMut, IndexLayout, IndexableSpace, InnerProductSpace,
+    NormedSpace,
+};
 
+#[derive(Debug, Clone)]
 pub struct LocalIndexableVectorSpace<T: Scalar> {
     index_layout: LocalIndexLayout,
     _phantom: PhantomData<T>,
@@ -24,23 +28,21 @@ impl<T: Scalar> LocalIndexableVectorSpace<T> {
 
 pub struct LocalIndexableVectorSpaceElement<'a, T: Scalar> {
     space: &'a LocalIndexableVectorSpace<T>,
-    data: super::indexable_vector::LocalIndexableVector<T>,
+    data: LocalIndexableVector<T>,
 }
 
 impl<'a, T: Scalar> Element for LocalIndexableVectorSpaceElement<'a, T> {
     type Space = LocalIndexableVectorSpace<T>;
-    type View<'b> = &'b super::indexable_vector::LocalIndexableVector<T> where Self: 'b;
-    type ViewMut<'b> = &'b mut super::indexable_vector::LocalIndexableVector<T> where Self: 'b;
 
     fn space(&self) -> &Self::Space {
         self.space
     }
 
-    fn view<'b>(&'b self) -> Self::View<'b> {
+    fn view(&self) -> &LocalIndexableVector<T> {
         &self.data
     }
 
-    fn view_mut<'b>(&'b mut self) -> Self::ViewMut<'b> {
+    fn view_mut(&mut self) -> &mut LocalIndexableVector<T> {
         &mut self.data
     }
 }
@@ -48,6 +50,8 @@ impl<'a, T: Scalar> Element for LocalIndexableVectorSpaceElement<'a, T> {
 impl<T: Scalar> sparse_traits::LinearSpace for LocalIndexableVectorSpace<T> {
     type F = T;
     type E<'a> = LocalIndexableVectorSpaceElement<'a, T> where Self: 'a;
+    type View<'a> = &'a LocalIndexableVector<T> where Self: 'a;
+    type ViewMut<'a> = &'a mut LocalIndexableVector<T> where Self: 'a;
 
     fn create_element<'a>(&'a self) -> Self::E<'a> {
         LocalIndexableVectorSpaceElement {
@@ -55,6 +59,56 @@ impl<T: Scalar> sparse_traits::LinearSpace for LocalIndexableVectorSpace<T> {
             data: LocalIndexableVector::new(self.index_layout().number_of_global_indices()),
         }
     }
+
+    fn is_compatible(&self, other: &Self) -> bool {
+        self.dimension() == other.dimension()
+    }
+
+    fn reborrow_view<'b, 'a: 'b>(x: &'b ElementView<'a, Self>) -> ElementView<'b, Self>
+    where
+        Self: 'a,
+    {
+        x
+    }
+
+    fn reborrow_view_mut<'b, 'a: 'b>(
+        y: &'b mut ElementViewMut<'a, Self>,
+    ) -> ElementViewMut<'b, Self>
+    where
+        Self: 'a,
+    {
+        y
+    }
+
+    fn axpy_inplace<'a>(
+        &self,
+        alpha: Self::F,
+        x: &ElementView<'a, Self>,
+        y: &mut ElementViewMut<'a, Self>,
+    ) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        y.mult_sum_into(x, alpha)
+    }
+
+    fn fill_inplace<'a>(
+        &self,
+        x: &ElementView<'a, Self>,
+        y: &mut ElementViewMut<'a, Self>,
+    ) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        y.fill(x)
+    }
+
+    fn scale_inplace<'a>(&self, alpha: Self::F, y: &mut ElementViewMut<'a, Self>)
+    where
+        Self: 'a,
+    {
+        y.scalar_mult(alpha)
+    }
 }
 
 impl<T: Scalar> IndexableSpace for LocalIndexableVectorSpace<T> {
@@ -8,6 +8,7 @@ use sparse_traits::{IndexLayout, IndexType};
 
 use super::index_layout::LocalIndexLayout;
 
+#[derive(Debug, Clone)]
 pub struct LocalIndexableVector<T: Scalar> {
     data: Vec<T>,
     index_layout: LocalIndexLayout,
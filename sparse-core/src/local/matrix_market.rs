@@ -0,0 +1,694 @@
+//! Reading and writing of sparse matrices and vectors in Matrix Market format.
+//!
+//! The reader supports the `coordinate` and `array` formats with `real`, `complex`,
+//! `integer` and `pattern` fields and `general`, `symmetric`, `skew-symmetric` and
+//! `hermitian` symmetry. Symmetric matrices are expanded on reading so that the
+//! result always stores the full matrix. The writer produces `general` files with a
+//! `real` or `complex` field depending on the scalar type.
+//!
+//! Explicit zeros in `coordinate` files are kept as stored entries, so that reading
+//! and writing preserves the sparsity pattern. They can be removed with
+//! [CsrMatrix::eliminate_zeros]. Zeros in `array` files are not stored.
+//!
+//! See <https://math.nist.gov/MatrixMarket/formats.html> for the file format.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+use sparse_traits::types::{IndexType, Scalar};
+use sparse_traits::types::{SparseLinAlgError, SparseLinAlgResult};
+
+use super::indexable_vector::LocalIndexableVector;
+use super::sparse::coo_mat::CooMatrix;
+use super::sparse::csr_mat::CsrMatrix;
+
+/// The largest number of entries reserved before reading the entries of a file.
+const MAX_RESERVED_ENTRIES: usize = 1 << 20;
+
+/// Storage format of a Matrix Market file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixMarketFormat {
+    /// Sparse storage as a list of `(row, col, value)` entries.
+    Coordinate,
+    /// Dense storage in column-major order.
+    Array,
+}
+
+/// Type of the entries of a Matrix Market file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixMarketField {
+    Real,
+    Complex,
+    Integer,
+    /// Only the sparsity pattern is stored. All entries are read as one.
+    Pattern,
+}
+
+/// Symmetry structure of a Matrix Market file.
+///
+/// For all variants except `General` only the lower triangle is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixMarketSymmetry {
+    General,
+    Symmetric,
+    SkewSymmetric,
+    Hermitian,
+}
+
+/// The information in the header line of a Matrix Market file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatrixMarketHeader {
+    pub format: MatrixMarketFormat,
+    pub field: MatrixMarketField,
+    pub symmetry: MatrixMarketSymmetry,
+}
+
+fn parse_error<R>(line: usize, message: impl Into<String>) -> SparseLinAlgResult<R> {
+    Err(SparseLinAlgError::MatrixMarketError {
+        line,
+        message: message.into(),
+    })
+}
+
+impl MatrixMarketHeader {
+    fn parse(line: &str) -> SparseLinAlgResult<Self> {
+        let tokens: Vec<String> = line
+            .split_whitespace()
+            .map(|token| token.to_lowercase())
+            .collect();
+
+        if tokens.len() != 5 || tokens[0] != "%%matrixmarket" {
+            return parse_error(
+                1,
+                "Expected header `%%MatrixMarket matrix <format> <field> <symmetry>`",
+            );
+        }
+        if tokens[1] != "matrix" {
+            return parse_error(1, format!("Unsupported object `{}`", tokens[1]));
+        }
+
+        let format = match tokens[2].as_str() {
+            "coordinate" => MatrixMarketFormat::Coordinate,
+            "array" => MatrixMarketFormat::Array,
+            other => return parse_error(1, format!("Unknown format `{other}`")),
+        };
+        let field = match tokens[3].as_str() {
+            "real" | "double" => MatrixMarketField::Real,
+            "complex" => MatrixMarketField::Complex,
+            "integer" => MatrixMarketField::Integer,
+            "pattern" => MatrixMarketField::Pattern,
+            other => return parse_error(1, format!("Unknown field `{other}`")),
+        };
+        let symmetry = match tokens[4].as_str() {
+            "general" => MatrixMarketSymmetry::General,
+            "symmetric" => MatrixMarketSymmetry::Symmetric,
+            "skew-symmetric" => MatrixMarketSymmetry::SkewSymmetric,
+            "hermitian" => MatrixMarketSymmetry::Hermitian,
+            other => return parse_error(1, format!("Unknown symmetry `{other}`")),
+        };
+
+        if format == MatrixMarketFormat::Array && field == MatrixMarketField::Pattern {
+            return parse_error(1, "The `pattern` field requires the `coordinate` format");
+        }
+
+        Ok(Self {
+            format,
+            field,
+            symmetry,
+        })
+    }
+}
+
+// Returns the imaginary unit if `T` is a complex type.
+fn imaginary_unit<T: Scalar>() -> Option<T> {
+    let one = <T::Real as num::One>::one();
+    let unit = (-T::one()).sqrt();
+    // The sign of the result depends on the sign of the zero imaginary part of `-1`.
+    if unit.im() == one {
+        Some(unit)
+    } else if unit.im() == -one {
+        Some(-unit)
+    } else {
+        None
+    }
+}
+
+// Iterator over the lines of a file that skips comments and empty lines.
+struct DataLines<R: BufRead> {
+    lines: std::io::Lines<R>,
+    line_number: usize,
+}
+
+impl<R: BufRead> DataLines<R> {
+    fn next_line(&mut self) -> SparseLinAlgResult<Option<String>> {
+        for line in self.lines.by_ref() {
+            self.line_number += 1;
+            let line = line?;
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with('%') {
+                return Ok(Some(trimmed.to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    fn expect_line(&mut self, what: &str) -> SparseLinAlgResult<String> {
+        match self.next_line()? {
+            Some(line) => Ok(line),
+            None => parse_error(
+                self.line_number + 1,
+                format!("Unexpected end of file, expected {what}"),
+            ),
+        }
+    }
+
+    fn parse_tokens<V: std::str::FromStr>(
+        &self,
+        tokens: &[&str],
+        what: &str,
+    ) -> SparseLinAlgResult<Vec<V>> {
+        tokens
+            .iter()
+            .map(|token| {
+                token.parse::<V>().or_else(|_| {
+                    parse_error(
+                        self.line_number,
+                        format!("Cannot parse `{token}` in {what}"),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    // Split a line into exactly `count` tokens.
+    fn split<'a>(
+        &self,
+        line: &'a str,
+        count: usize,
+        what: &str,
+    ) -> SparseLinAlgResult<Vec<&'a str>> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() != count {
+            return parse_error(
+                self.line_number,
+                format!("Expected {count} values for {what}, found {}", tokens.len()),
+            );
+        }
+        Ok(tokens)
+    }
+}
+
+struct EntryParser<T: Scalar> {
+    field: MatrixMarketField,
+    imaginary_unit: Option<T>,
+}
+
+impl<T: Scalar> EntryParser<T> {
+    fn new(field: MatrixMarketField) -> SparseLinAlgResult<Self> {
+        let imaginary_unit = imaginary_unit::<T>();
+        if field == MatrixMarketField::Complex && imaginary_unit.is_none() {
+            return parse_error(1, "Cannot read a `complex` file into a real scalar type");
+        }
+        Ok(Self {
+            field,
+            imaginary_unit,
+        })
+    }
+
+    fn number_of_tokens(&self) -> usize {
+        match self.field {
+            MatrixMarketField::Pattern => 0,
+            MatrixMarketField::Complex => 2,
+            _ => 1,
+        }
+    }
+
+    fn value(&self, tokens: &[f64]) -> T {
+        match self.field {
+            MatrixMarketField::Pattern => T::one(),
+            MatrixMarketField::Complex => {
+                T::from_real(T::real(tokens[0]))
+                    + self.imaginary_unit.unwrap() * T::from_real(T::real(tokens[1]))
+            }
+            _ => T::from_real(T::real(tokens[0])),
+        }
+    }
+}
+
+// Add the entry `(row, col)` and its mirror image required by the symmetry.
+//
+// Explicitly stored zeros are kept, so that the sparsity pattern of a file survives
+// reading and writing.
+fn push_entry<T: Scalar>(
+    coo: &mut CooMatrix<T>,
+    symmetry: MatrixMarketSymmetry,
+    row: IndexType,
+    col: IndexType,
+    value: T,
+    line_number: usize,
+) -> SparseLinAlgResult<()> {
+    let located = |err: SparseLinAlgError| SparseLinAlgError::MatrixMarketError {
+        line: line_number,
+        message: err.to_string(),
+    };
+
+    coo.push(row, col, value).map_err(located)?;
+    if row == col {
+        return Ok(());
+    }
+    let mirrored = match symmetry {
+        MatrixMarketSymmetry::General => return Ok(()),
+        MatrixMarketSymmetry::Symmetric => value,
+        MatrixMarketSymmetry::SkewSymmetric => -value,
+        MatrixMarketSymmetry::Hermitian => value.conj(),
+    };
+    coo.push(col, row, mirrored).map_err(located)
+}
+
+/// Read the header and entries of a Matrix Market file into a [CooMatrix].
+fn read_coo<T: Scalar, R: Read>(
+    reader: R,
+) -> SparseLinAlgResult<(MatrixMarketHeader, CooMatrix<T>)> {
+    let mut lines = DataLines {
+        lines: BufReader::new(reader).lines(),
+        line_number: 0,
+    };
+
+    let header = match lines.lines.next() {
+        Some(line) => MatrixMarketHeader::parse(&line?)?,
+        None => return parse_error(1, "The file is empty"),
+    };
+    lines.line_number = 1;
+
+    let parser = EntryParser::<T>::new(header.field)?;
+    let ntokens = parser.number_of_tokens();
+
+    let coo = match header.format {
+        MatrixMarketFormat::Coordinate => {
+            let size_line = lines.expect_line("the size line")?;
+            let tokens = lines.split(&size_line, 3, "the size line")?;
+            let size: Vec<IndexType> = lines.parse_tokens(&tokens, "the size line")?;
+            let (nrows, ncols, nnz) = (size[0], size[1], size[2]);
+            check_square(&header, nrows, ncols, lines.line_number)?;
+
+            // The number of entries comes from the file, so only a bounded amount of
+            // memory is reserved up front. A wrong count fails on the missing lines.
+            let capacity = nnz
+                .min(nrows.saturating_mul(ncols))
+                .min(MAX_RESERVED_ENTRIES);
+            let mut coo = CooMatrix::with_capacity((nrows, ncols), capacity);
+            for _ in 0..nnz {
+                let line = lines.expect_line("a matrix entry")?;
+                let tokens = lines.split(&line, 2 + ntokens, "a matrix entry")?;
+                let position: Vec<IndexType> =
+                    lines.parse_tokens(&tokens[..2], "a matrix entry")?;
+                let values: Vec<f64> = lines.parse_tokens(&tokens[2..], "a matrix entry")?;
+
+                if position[0] == 0 || position[1] == 0 {
+                    return parse_error(lines.line_number, "Indices are one-based");
+                }
+                let (row, col) = (position[0] - 1, position[1] - 1);
+                if header.symmetry != MatrixMarketSymmetry::General && row < col {
+                    return parse_error(
+                        lines.line_number,
+                        "Only the lower triangle may be stored for symmetric matrices",
+                    );
+                }
+                if header.symmetry == MatrixMarketSymmetry::SkewSymmetric && row == col {
+                    return parse_error(
+                        lines.line_number,
+                        "The zero diagonal of skew-symmetric matrices may not be stored",
+                    );
+                }
+                push_entry(
+                    &mut coo,
+                    header.symmetry,
+                    row,
+                    col,
+                    parser.value(&values),
+                    lines.line_number,
+                )?;
+            }
+            coo
+        }
+        MatrixMarketFormat::Array => {
+            let size_line = lines.expect_line("the size line")?;
+            let tokens = lines.split(&size_line, 2, "the size line")?;
+            let size: Vec<IndexType> = lines.parse_tokens(&tokens, "the size line")?;
+            let (nrows, ncols) = (size[0], size[1]);
+            check_square(&header, nrows, ncols, lines.line_number)?;
+
+            let mut coo = CooMatrix::new((nrows, ncols));
+            for col in 0..ncols {
+                // Symmetric variants store the lower triangle and skew-symmetric
+                // matrices additionally omit the zero diagonal.
+                let first_row = match header.symmetry {
+                    MatrixMarketSymmetry::General => 0,
+                    MatrixMarketSymmetry::SkewSymmetric => col + 1,
+                    _ => col,
+                };
+                for row in first_row..nrows {
+                    let line = lines.expect_line("a matrix entry")?;
+                    let tokens = lines.split(&line, ntokens, "a matrix entry")?;
+                    let values: Vec<f64> = lines.parse_tokens(&tokens, "a matrix entry")?;
+                    let value = parser.value(&values);
+                    // Dense storage has no notion of stored entries, so zeros are dropped.
+                    if value.is_zero() {
+                        continue;
+                    }
+                    push_entry(
+                        &mut coo,
+                        header.symmetry,
+                        row,
+                        col,
+                        value,
+                        lines.line_number,
+                    )?;
+                }
+            }
+            coo
+        }
+    };
+
+    if let Some(line) = lines.next_line()? {
+        return parse_error(lines.line_number, format!("Unexpected data `{line}`"));
+    }
+
+    Ok((header, coo))
+}
+
+fn check_square(
+    header: &MatrixMarketHeader,
+    nrows: IndexType,
+    ncols: IndexType,
+    line_number: usize,
+) -> SparseLinAlgResult<()> {
+    if header.symmetry != MatrixMarketSymmetry::General && nrows != ncols {
+        return parse_error(
+            line_number,
+            format!("A matrix with symmetry must be square, found {nrows} x {ncols}"),
+        );
+    }
+    Ok(())
+}
+
+/// Read a sparse matrix in Matrix Market format.
+pub fn read_matrix<T: Scalar, R: Read>(reader: R) -> SparseLinAlgResult<CsrMatrix<T>> {
+    let (_, coo) = read_coo::<T, R>(reader)?;
+    Ok(coo.to_csr())
+}
+
+/// Read a vector in Matrix Market format.
+///
+/// The file must describe a matrix with a single column.
+pub fn read_vector<T: Scalar, R: Read>(reader: R) -> SparseLinAlgResult<LocalIndexableVector<T>> {
+    let (_, coo) = read_coo::<T, R>(reader)?;
+    let (nrows, ncols) = coo.shape();
+    if ncols != 1 {
+        return Err(SparseLinAlgError::SingleDimensionError {
+            expected: 1,
+            actual: ncols,
+        });
+    }
+
+    let mut vec = LocalIndexableVector::<T>::new(nrows);
+    let mut view = vec.view_mut().unwrap();
+    for (&row, &value) in coo.rows().iter().zip(coo.data()) {
+        *view.get_mut(row).unwrap() += value;
+    }
+    Ok(vec)
+}
+
+fn field_name<T: Scalar>() -> &'static str {
+    if imaginary_unit::<T>().is_some() {
+        "complex"
+    } else {
+        "real"
+    }
+}
+
+fn write_value<T: Scalar, W: Write>(writer: &mut W, value: T) -> SparseLinAlgResult<()> {
+    if imaginary_unit::<T>().is_some() {
+        write!(writer, "{} {}", value.re(), value.im())?;
+    } else {
+        write!(writer, "{}", value.re())?;
+    }
+    Ok(())
+}
+
+/// Write a sparse matrix in `coordinate` Matrix Market format.
+pub fn write_matrix<T: Scalar, W: Write>(
+    mat: &CsrMatrix<T>,
+    mut writer: W,
+) -> SparseLinAlgResult<()> {
+    let (nrows, ncols) = mat.shape();
+    writeln!(
+        writer,
+        "%%MatrixMarket matrix coordinate {} general",
+        field_name::<T>()
+    )?;
+    writeln!(writer, "{} {} {}", nrows, ncols, mat.nnz())?;
+    for row in 0..nrows {
+        for index in mat.indptr()[row]..mat.indptr()[1 + row] {
+            write!(writer, "{} {} ", 1 + row, 1 + mat.indices()[index])?;
+            write_value(&mut writer, mat.data()[index])?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a vector in `array` Matrix Market format.
+pub fn write_vector<T: Scalar, W: Write>(
+    vec: &LocalIndexableVector<T>,
+    mut writer: W,
+) -> SparseLinAlgResult<()> {
+    let view = vec.view().unwrap();
+    writeln!(
+        writer,
+        "%%MatrixMarket matrix array {} general",
+        field_name::<T>()
+    )?;
+    writeln!(writer, "{} 1", view.len())?;
+    for &value in view.iter() {
+        write_value(&mut writer, value)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read a sparse matrix from a Matrix Market file.
+pub fn read_matrix_file<T: Scalar, P: AsRef<Path>>(path: P) -> SparseLinAlgResult<CsrMatrix<T>> {
+    read_matrix(File::open(path)?)
+}
+
+/// Read a vector from a Matrix Market file.
+pub fn read_vector_file<T: Scalar, P: AsRef<Path>>(
+    path: P,
+) -> SparseLinAlgResult<LocalIndexableVector<T>> {
+    read_vector(File::open(path)?)
+}
+
+/// Write a sparse matrix to a Matrix Market file.
+pub fn write_matrix_file<T: Scalar, P: AsRef<Path>>(
+    mat: &CsrMatrix<T>,
+    path: P,
+) -> SparseLinAlgResult<()> {
+    write_matrix(mat, BufWriter::new(File::create(path)?))
+}
+
+/// Write a vector to a Matrix Market file.
+pub fn write_vector_file<T: Scalar, P: AsRef<Path>>(
+    vec: &LocalIndexableVector<T>,
+    path: P,
+) -> SparseLinAlgResult<()> {
+    write_vector(vec, BufWriter::new(File::create(path)?))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use cauchy::c64;
+
+    #[test]
+    fn test_read_coordinate_general() {
+        let input = "%%MatrixMarket matrix coordinate real general
+% A comment
+
+2 3 3
+1 1 1.5
+2 3 -2e1
+1 2 3
+";
+        let csr = read_matrix::<f64, _>(input.as_bytes()).unwrap();
+
+        assert_eq!(csr.shape(), (2, 3));
+        assert_eq!(csr.indptr(), &[0, 2, 3]);
+        assert_eq!(csr.indices(), &[0, 1, 2]);
+        assert_eq!(csr.data(), &[1.5, 3.0, -20.0]);
+    }
+
+    #[test]
+    fn test_read_explicit_zeros() {
+        let input = "%%MatrixMarket matrix coordinate real symmetric
+2 2 2
+1 1 0
+2 1 0.0
+";
+        let csr = read_matrix::<f64, _>(input.as_bytes()).unwrap();
+        assert_eq!(csr.indptr(), &[0, 2, 3]);
+        assert_eq!(csr.indices(), &[0, 1, 0]);
+        assert_eq!(csr.data(), &[0.0, 0.0, 0.0]);
+
+        let mut buffer = Vec::<u8>::new();
+        write_matrix(&csr, &mut buffer).unwrap();
+        let result = read_matrix::<f64, _>(buffer.as_slice()).unwrap();
+        assert_eq!(result.indptr(), csr.indptr());
+        assert_eq!(result.indices(), csr.indices());
+    }
+
+    #[test]
+    fn test_read_symmetry() {
+        let input = "%%MatrixMarket matrix coordinate integer symmetric
+2 2 2
+1 1 4
+2 1 -1
+";
+        let csr = read_matrix::<f64, _>(input.as_bytes()).unwrap();
+        assert_eq!(csr.indices(), &[0, 1, 0]);
+        assert_eq!(csr.data(), &[4.0, -1.0, -1.0]);
+
+        let input = "%%MatrixMarket matrix coordinate pattern skew-symmetric
+2 2 1
+2 1
+";
+        let csr = read_matrix::<f64, _>(input.as_bytes()).unwrap();
+        assert_eq!(csr.indices(), &[1, 0]);
+        assert_eq!(csr.data(), &[-1.0, 1.0]);
+
+        let input = "%%MatrixMarket matrix coordinate complex hermitian
+2 2 2
+1 1 2 0
+2 1 1 3
+";
+        let csr = read_matrix::<c64, _>(input.as_bytes()).unwrap();
+        assert_eq!(csr.indices(), &[0, 1, 0]);
+        assert_eq!(
+            csr.data(),
+            &[c64::new(2.0, 0.0), c64::new(1.0, -3.0), c64::new(1.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn test_read_array() {
+        // The matrix [[1, 0], [2, 3]] in column-major order.
+        let input = "%%MatrixMarket matrix array real general
+2 2
+1
+2
+0
+3
+";
+        let csr = read_matrix::<f64, _>(input.as_bytes()).unwrap();
+        assert_eq!(csr.indptr(), &[0, 1, 3]);
+        assert_eq!(csr.data(), &[1.0, 2.0, 3.0]);
+
+        // The matrix [[1, 2], [2, 3]] with only the lower triangle stored.
+        let input = "%%MatrixMarket matrix array real symmetric
+2 2
+1
+2
+3
+";
+        let csr = read_matrix::<f64, _>(input.as_bytes()).unwrap();
+        assert_eq!(csr.indices(), &[0, 1, 0, 1]);
+        assert_eq!(csr.data(), &[1.0, 2.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let parse = |input: &str| read_matrix::<f64, _>(input.as_bytes());
+
+        assert!(matches!(
+            parse("%%MatrixMarket matrix coordinate real unknown\n1 1 0\n"),
+            Err(SparseLinAlgError::MatrixMarketError { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse("%%MatrixMarket matrix coordinate complex general\n1 1 0\n"),
+            Err(SparseLinAlgError::MatrixMarketError { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse("%%MatrixMarket matrix coordinate real general\n2 2 1\n1 x 1.0\n"),
+            Err(SparseLinAlgError::MatrixMarketError { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse("%%MatrixMarket matrix coordinate real general\n2 2 1\n3 1 1.0\n"),
+            Err(SparseLinAlgError::MatrixMarketError { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse("%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1.0\n"),
+            Err(SparseLinAlgError::MatrixMarketError { line: 4, .. })
+        ));
+        assert!(matches!(
+            parse("%%MatrixMarket matrix coordinate real symmetric\n2 2 1\n1 2 1.0\n"),
+            Err(SparseLinAlgError::MatrixMarketError { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse(
+                "%%MatrixMarket matrix coordinate real skew-symmetric\n2 2 2\n2 1 1.0\n2 2 1.0\n"
+            ),
+            Err(SparseLinAlgError::MatrixMarketError { line: 4, .. })
+        ));
+        // A huge number of entries in the header must not exhaust the memory.
+        assert!(matches!(
+            parse("%%MatrixMarket matrix coordinate real general\n1000000000 1000000000 1000000000000000000\n"),
+            Err(SparseLinAlgError::MatrixMarketError { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_matrix_roundtrip() {
+        let data = vec![
+            c64::new(1.0, -0.5),
+            c64::new(0.25, 3.0),
+            c64::new(-2.0, 0.0),
+        ];
+        let csr = CsrMatrix::from_aij((2, 3), &[0, 1, 1], &[2, 0, 1], &data).unwrap();
+
+        let mut buffer = Vec::<u8>::new();
+        write_matrix(&csr, &mut buffer).unwrap();
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert!(text.starts_with("%%MatrixMarket matrix coordinate complex general\n2 3 3\n"));
+
+        let result = read_matrix::<c64, _>(buffer.as_slice()).unwrap();
+        assert_eq!(result.shape(), csr.shape());
+        assert_eq!(result.indptr(), csr.indptr());
+        assert_eq!(result.indices(), csr.indices());
+        assert_eq!(result.data(), csr.data());
+    }
+
+    #[test]
+    fn test_vector_roundtrip() {
+        let mut vec = LocalIndexableVector::<f64>::new(3);
+        vec.view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&[1.0, 0.0, -0.125]);
+
+        let mut buffer = Vec::<u8>::new();
+        write_vector(&vec, &mut buffer).unwrap();
+
+        let result = read_vector::<f64, _>(buffer.as_slice()).unwrap();
+        assert_eq!(result.view().unwrap().data(), &[1.0, 0.0, -0.125]);
+
+        let input = "%%MatrixMarket matrix coordinate real general\n3 1 1\n2 1 5\n";
+        let result = read_vector::<f64, _>(input.as_bytes()).unwrap();
+        assert_eq!(result.view().unwrap().data(), &[0.0, 5.0, 0.0]);
+    }
+}
@@ -1,7 +1,81 @@
 //! Single node sparse matrix implementations.
 
+pub mod coo_mat;
+pub mod csc_mat;
 pub mod csr_mat;
+pub mod spgemm;
+pub mod triangular;
 
+use sparse_traits::types::{IndexType, Scalar, SparseLinAlgError, SparseLinAlgResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SparseMatType {
     Csr,
+    Csc,
+}
+
+/// Swap the roles of the major and minor axis of a compressed sparse format.
+///
+/// Given the `indptr`, `indices` and `data` arrays of a matrix compressed along
+/// `n_major` slices with minor indices in `0..n_minor`, this returns the arrays of
+/// the same matrix compressed along the minor axis. Applied to a CSR matrix this
+/// gives its CSC representation (and vice versa). The minor indices of the result
+/// are sorted within each slice. The cost is O(nnz + n_major + n_minor).
+pub(crate) fn transpose_compressed<T: Scalar>(
+    n_major: IndexType,
+    n_minor: IndexType,
+    indptr: &[IndexType],
+    indices: &[IndexType],
+    data: &[T],
+) -> (Vec<IndexType>, Vec<IndexType>, Vec<T>) {
+    let nnz = indptr[n_major];
+
+    // Count the entries in each minor slice and turn the counts into offsets.
+    let mut new_indptr = vec![0 as IndexType; 1 + n_minor];
+    for &index in &indices[..nnz] {
+        new_indptr[1 + index] += 1;
+    }
+    for index in 0..n_minor {
+        new_indptr[1 + index] += new_indptr[index];
+    }
+
+    let mut new_indices = vec![0 as IndexType; nnz];
+    let mut new_data = vec![T::zero(); nnz];
+    let mut next = new_indptr.clone();
+
+    for major in 0..n_major {
+        for index in indptr[major]..indptr[1 + major] {
+            let minor = indices[index];
+            let dest = next[minor];
+            new_indices[dest] = major;
+            new_data[dest] = data[index];
+            next[minor] += 1;
+        }
+    }
+
+    (new_indptr, new_indices, new_data)
+}
+
+/// Check the lengths of the input `x` and output `y` of a matrix application.
+///
+/// For `A x` the expected lengths are the number of columns and rows of `A`; for
+/// `A^T x` and `A^H x` they are swapped.
+pub(crate) fn check_apply_dimensions(
+    expected: (IndexType, IndexType),
+    x_len: IndexType,
+    y_len: IndexType,
+) -> SparseLinAlgResult<()> {
+    if x_len != expected.0 {
+        return Err(SparseLinAlgError::SingleDimensionError {
+            expected: expected.0,
+            actual: x_len,
+        });
+    }
+    if y_len != expected.1 {
+        return Err(SparseLinAlgError::SingleDimensionError {
+            expected: expected.1,
+            actual: y_len,
+        });
+    }
+    Ok(())
 }
@@ -0,0 +1,226 @@
+//! Definition of COO matrices.
+//!
+//! A [CooMatrix] collects `(row, col, value)` triplets one at a time, as they are
+//! produced by finite element assembly, and is then converted into a compressed
+//! format for computations.
+
+use crate::local::sparse::csc_mat::CscMatrix;
+use crate::local::sparse::csr_mat::CsrMatrix;
+use sparse_traits::types::{IndexType, Scalar};
+use sparse_traits::types::{SparseLinAlgError, SparseLinAlgResult};
+
+#[derive(Debug, Clone)]
+pub struct CooMatrix<T: Scalar> {
+    shape: (IndexType, IndexType),
+    rows: Vec<IndexType>,
+    cols: Vec<IndexType>,
+    data: Vec<T>,
+}
+
+impl<T: Scalar> CooMatrix<T> {
+    /// Create an empty matrix with the given shape.
+    pub fn new(shape: (IndexType, IndexType)) -> Self {
+        Self::with_capacity(shape, 0)
+    }
+
+    /// Create an empty matrix with room for `capacity` triplets.
+    pub fn with_capacity(shape: (IndexType, IndexType), capacity: IndexType) -> Self {
+        Self {
+            shape,
+            rows: Vec::with_capacity(capacity),
+            cols: Vec::with_capacity(capacity),
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Create a matrix from a list of triplets.
+    ///
+    /// Returns an error if the three slices have different lengths or if an
+    /// index is out of bounds.
+    pub fn from_aij(
+        shape: (IndexType, IndexType),
+        rows: &[IndexType],
+        cols: &[IndexType],
+        data: &[T],
+    ) -> SparseLinAlgResult<Self> {
+        for len in [rows.len(), cols.len()] {
+            if len != data.len() {
+                return Err(SparseLinAlgError::SingleDimensionError {
+                    expected: data.len(),
+                    actual: len,
+                });
+            }
+        }
+
+        let mut coo = Self::with_capacity(shape, data.len());
+        for ((&row, &col), &value) in rows.iter().zip(cols).zip(data) {
+            coo.push(row, col, value)?;
+        }
+        Ok(coo)
+    }
+
+    /// Add the triplet `(row, col, value)`.
+    ///
+    /// Duplicate entries are allowed and are summed up on conversion.
+    pub fn push(&mut self, row: IndexType, col: IndexType, value: T) -> SparseLinAlgResult<()> {
+        if row >= self.shape.0 {
+            return Err(SparseLinAlgError::IndexOutOfBounds {
+                index: row,
+                bound: self.shape.0,
+            });
+        }
+        if col >= self.shape.1 {
+            return Err(SparseLinAlgError::IndexOutOfBounds {
+                index: col,
+                bound: self.shape.1,
+            });
+        }
+
+        self.rows.push(row);
+        self.cols.push(col);
+        self.data.push(value);
+        Ok(())
+    }
+
+    pub fn shape(&self) -> (IndexType, IndexType) {
+        self.shape
+    }
+
+    /// Number of stored triplets, including duplicates.
+    pub fn nnz(&self) -> IndexType {
+        self.data.len()
+    }
+
+    pub fn rows(&self) -> &[IndexType] {
+        &self.rows
+    }
+
+    pub fn cols(&self) -> &[IndexType] {
+        &self.cols
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Convert into a CSR matrix.
+    ///
+    /// Duplicate entries are summed and the column indices within each row are
+    /// sorted. Entries that sum to zero are kept as explicit zeros.
+    pub fn to_csr(&self) -> CsrMatrix<T> {
+        let nrows = self.shape.0;
+        let nnz = self.nnz();
+
+        // Bucket the triplets by row.
+        let mut row_start = vec![0 as IndexType; 1 + nrows];
+        for &row in &self.rows {
+            row_start[1 + row] += 1;
+        }
+        for row in 0..nrows {
+            row_start[1 + row] += row_start[row];
+        }
+
+        let mut entries = vec![(0 as IndexType, T::zero()); nnz];
+        let mut next = row_start.clone();
+        for ((&row, &col), &value) in self.rows.iter().zip(&self.cols).zip(&self.data) {
+            entries[next[row]] = (col, value);
+            next[row] += 1;
+        }
+
+        // Sort each row by column and merge duplicates.
+        let mut indptr = Vec::<IndexType>::with_capacity(1 + nrows);
+        let mut indices = Vec::<IndexType>::with_capacity(nnz);
+        let mut data = Vec::<T>::with_capacity(nnz);
+
+        indptr.push(0);
+        for row in 0..nrows {
+            let row_entries = &mut entries[row_start[row]..row_start[1 + row]];
+            row_entries.sort_unstable_by_key(|&(col, _)| col);
+
+            let first = indices.len();
+            for &(col, value) in row_entries.iter() {
+                if indices.len() > first && *indices.last().unwrap() == col {
+                    *data.last_mut().unwrap() += value;
+                } else {
+                    indices.push(col);
+                    data.push(value);
+                }
+            }
+            indptr.push(indices.len());
+        }
+
+        CsrMatrix::new(self.shape, indices, indptr, data)
+    }
+
+    /// Convert into a CSC matrix.
+    ///
+    /// Duplicate entries are summed and the row indices within each column are
+    /// sorted. Entries that sum to zero are kept as explicit zeros.
+    pub fn to_csc(&self) -> CscMatrix<T> {
+        CscMatrix::from_csr(&self.to_csr())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_coo_to_csr() {
+        // Assemble the matrix [[1, 0, 5], [0, 0, 0], [3, 4, 0]] with
+        // unsorted columns and a duplicate entry at (0, 2).
+        let mut coo = CooMatrix::new((3, 3));
+        coo.push(2, 1, 4.0).unwrap();
+        coo.push(0, 2, 2.0).unwrap();
+        coo.push(2, 0, 3.0).unwrap();
+        coo.push(0, 0, 1.0).unwrap();
+        coo.push(0, 2, 3.0).unwrap();
+
+        assert_eq!(coo.nnz(), 5);
+
+        let csr = coo.to_csr();
+
+        assert_eq!(csr.indptr(), &[0, 2, 2, 4]);
+        assert_eq!(csr.indices(), &[0, 2, 0, 1]);
+        assert_eq!(csr.data(), &[1.0, 5.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_coo_to_csc() {
+        let rows = vec![0, 2, 0, 2, 0];
+        let cols = vec![2, 1, 0, 0, 2];
+        let data = vec![2.0, 4.0, 1.0, 3.0, 3.0];
+
+        let csc = CooMatrix::from_aij((3, 3), &rows, &cols, &data)
+            .unwrap()
+            .to_csc();
+
+        assert_eq!(csc.indptr(), &[0, 2, 3, 4]);
+        assert_eq!(csc.indices(), &[0, 2, 2, 0]);
+        assert_eq!(csc.data(), &[1.0, 3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_coo_index_out_of_bounds() {
+        let mut coo = CooMatrix::<f64>::new((2, 3));
+
+        assert!(matches!(
+            coo.push(2, 0, 1.0),
+            Err(SparseLinAlgError::IndexOutOfBounds { index: 2, bound: 2 })
+        ));
+        assert!(matches!(
+            coo.push(0, 3, 1.0),
+            Err(SparseLinAlgError::IndexOutOfBounds { index: 3, bound: 3 })
+        ));
+        assert_eq!(coo.nnz(), 0);
+
+        assert!(matches!(
+            CooMatrix::from_aij((2, 2), &[0, 1], &[0], &[1.0, 2.0]),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 2,
+                actual: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,361 @@
+//! Definition of CSC matrices.
+
+use crate::local::indexable_space::LocalIndexableVectorSpace;
+use crate::local::sparse::csr_mat::CsrMatrix;
+use crate::local::sparse::{check_apply_dimensions, transpose_compressed, SparseMatType};
+use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+use sparse_traits::types::SparseLinAlgResult;
+use sparse_traits::{
+    AsApply, AsApplyAdjoint, AsApplyTranspose, ElementView, ElementViewMut, OperatorBase,
+};
+
+use sparse_traits::types::{IndexType, Scalar};
+
+#[derive(Debug)]
+pub struct CscMatrix<T: Scalar> {
+    mat_type: SparseMatType,
+    shape: (IndexType, IndexType),
+    domain: LocalIndexableVectorSpace<T>,
+    range: LocalIndexableVectorSpace<T>,
+    indices: Vec<IndexType>,
+    indptr: Vec<IndexType>,
+    data: Vec<T>,
+}
+
+impl<T: Scalar> CscMatrix<T> {
+    pub fn new(
+        shape: (IndexType, IndexType),
+        indices: Vec<IndexType>,
+        indptr: Vec<IndexType>,
+        data: Vec<T>,
+    ) -> Self {
+        Self {
+            mat_type: SparseMatType::Csc,
+            shape,
+            domain: LocalIndexableVectorSpace::new(shape.1),
+            range: LocalIndexableVectorSpace::new(shape.0),
+            indices,
+            indptr,
+            data,
+        }
+    }
+
+    pub fn mat_type(&self) -> &SparseMatType {
+        &self.mat_type
+    }
+
+    pub fn shape(&self) -> (IndexType, IndexType) {
+        self.shape
+    }
+
+    /// Row indices of the nonzero entries, stored column by column.
+    pub fn indices(&self) -> &[IndexType] {
+        &self.indices
+    }
+
+    /// Offsets into `indices` and `data` at which each column starts.
+    pub fn indptr(&self) -> &[IndexType] {
+        &self.indptr
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Compute `y = alpha * A * x + beta * y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `x` and `y` do not match the shape of the matrix.
+    pub fn matmul(&self, alpha: T, x: &[T], beta: T, y: &mut [T]) {
+        assert_eq!(x.len(), self.shape.1);
+        assert_eq!(y.len(), self.shape.0);
+
+        for out in y.iter_mut() {
+            *out *= beta;
+        }
+
+        for (col, &x_col) in x.iter().enumerate() {
+            let scaled = alpha * x_col;
+            for index in self.indptr[col]..self.indptr[1 + col] {
+                y[self.indices[index]] += self.data[index] * scaled;
+            }
+        }
+    }
+
+    /// Compute `y = alpha * A^T * x + beta * y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `x` and `y` do not match the shape of the matrix.
+    pub fn matmul_transpose(&self, alpha: T, x: &[T], beta: T, y: &mut [T]) {
+        self.matmul_transpose_impl(alpha, x, beta, y, |value| value);
+    }
+
+    /// Compute `y = alpha * A^H * x + beta * y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `x` and `y` do not match the shape of the matrix.
+    pub fn matmul_adjoint(&self, alpha: T, x: &[T], beta: T, y: &mut [T]) {
+        self.matmul_transpose_impl(alpha, x, beta, y, |value| value.conj());
+    }
+
+    fn matmul_transpose_impl<F: Fn(T) -> T>(&self, alpha: T, x: &[T], beta: T, y: &mut [T], op: F) {
+        assert_eq!(x.len(), self.shape.0);
+        assert_eq!(y.len(), self.shape.1);
+
+        for (col, out) in y.iter_mut().enumerate() {
+            let mut acc = T::zero();
+            for index in self.indptr[col]..self.indptr[1 + col] {
+                acc += op(self.data[index]) * x[self.indices[index]];
+            }
+            *out = beta * *out + alpha * acc;
+        }
+    }
+
+    pub fn from_aij(
+        shape: (IndexType, IndexType),
+        rows: &[IndexType],
+        cols: &[IndexType],
+        data: &[T],
+    ) -> SparseLinAlgResult<Self> {
+        let csr = CsrMatrix::from_aij(shape, rows, cols, data)?;
+        Ok(Self::from_csr(&csr))
+    }
+
+    /// Convert a CSR matrix into CSC format in O(nnz) operations.
+    pub fn from_csr(csr: &CsrMatrix<T>) -> Self {
+        let shape = csr.shape();
+        let (indptr, indices, data) =
+            transpose_compressed(shape.0, shape.1, csr.indptr(), csr.indices(), csr.data());
+        Self::new(shape, indices, indptr, data)
+    }
+
+    /// Convert the matrix into CSR format in O(nnz) operations.
+    pub fn to_csr(&self) -> CsrMatrix<T> {
+        let (indptr, indices, data) = transpose_compressed(
+            self.shape.1,
+            self.shape.0,
+            &self.indptr,
+            &self.indices,
+            &self.data,
+        );
+        CsrMatrix::new(self.shape, indices, indptr, data)
+    }
+}
+
+impl<T: Scalar> OperatorBase for CscMatrix<T> {
+    type Domain = LocalIndexableVectorSpace<T>;
+    type Range = LocalIndexableVectorSpace<T>;
+
+    /// The space of vectors the matrix acts on. Its dimension is the number of columns.
+    fn domain(&self) -> &Self::Domain {
+        &self.domain
+    }
+
+    /// The space of vectors the matrix maps into. Its dimension is the number of rows.
+    fn range(&self) -> &Self::Range {
+        &self.range
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_transpose(
+        &self,
+    ) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<T: Scalar> AsApply for CscMatrix<T> {
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        let x_view = x.view().unwrap();
+        let mut y_view = y.view_mut().unwrap();
+
+        check_apply_dimensions((self.shape.1, self.shape.0), x_view.len(), y_view.len())?;
+
+        self.matmul(T::one(), x_view.data(), T::zero(), y_view.data_mut());
+        Ok(())
+    }
+}
+
+impl<T: Scalar> AsApplyTranspose for CscMatrix<T> {
+    fn apply_transpose(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let x_view = x.view().unwrap();
+        let mut y_view = y.view_mut().unwrap();
+
+        check_apply_dimensions((self.shape.0, self.shape.1), x_view.len(), y_view.len())?;
+
+        self.matmul_transpose(T::one(), x_view.data(), T::zero(), y_view.data_mut());
+        Ok(())
+    }
+}
+
+impl<T: Scalar> AsApplyAdjoint for CscMatrix<T> {
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let x_view = x.view().unwrap();
+        let mut y_view = y.view_mut().unwrap();
+
+        check_apply_dimensions((self.shape.0, self.shape.1), x_view.len(), y_view.len())?;
+
+        self.matmul_adjoint(T::one(), x_view.data(), T::zero(), y_view.data_mut());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_csc_from_aij() {
+        // Test the matrix [[1, 2, 0], [0, 3, 4]]
+        let rows = vec![1, 0, 1, 0];
+        let cols = vec![2, 0, 1, 1];
+        let data = vec![4.0, 1.0, 3.0, 2.0];
+
+        let csc = CscMatrix::from_aij((2, 3), &rows, &cols, &data).unwrap();
+
+        assert_eq!(*csc.mat_type(), SparseMatType::Csc);
+        assert_eq!(csc.indptr(), &[0, 1, 3, 4]);
+        assert_eq!(csc.indices(), &[0, 0, 1, 1]);
+        assert_eq!(csc.data(), &[1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_csc_matmul() {
+        // Test the matrix [[1, 2, 0], [0, 3, 4]]
+        let rows = vec![0, 0, 1, 1];
+        let cols = vec![0, 1, 1, 2];
+        let data = vec![1.0, 2.0, 3.0, 4.0];
+
+        let csc = CscMatrix::from_aij((2, 3), &rows, &cols, &data).unwrap();
+
+        // Execute 2 * [1, 2] + 3 * A * x with x = [1, 2, 3].
+        // Expected result is [17, 58].
+        let x = vec![1.0, 2.0, 3.0];
+        let mut res = vec![1.0, 2.0];
+
+        csc.matmul(3.0, &x, 2.0, &mut res);
+
+        assert_eq!(res, vec![17.0, 58.0]);
+
+        // Execute 2 * [1, 2, 3] + A^T * x with x = [1, 2].
+        // Expected result is [3, 12, 14].
+        let x = vec![1.0, 2.0];
+        let mut res = vec![1.0, 2.0, 3.0];
+
+        csc.matmul_transpose(1.0, &x, 2.0, &mut res);
+
+        assert_eq!(res, vec![3.0, 12.0, 14.0]);
+    }
+
+    #[test]
+    fn test_csr_csc_roundtrip() {
+        // Test the matrix [[1, 0, 2], [0, 0, 3], [4, 5, 0]]
+        let rows = vec![0, 0, 1, 2, 2];
+        let cols = vec![0, 2, 2, 0, 1];
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+
+        let csr = CsrMatrix::from_aij((3, 3), &rows, &cols, &data).unwrap();
+        let csc = CscMatrix::from_csr(&csr);
+
+        assert_eq!(csc.indptr(), &[0, 2, 3, 5]);
+        assert_eq!(csc.indices(), &[0, 2, 2, 0, 1]);
+        assert_eq!(csc.data(), &[1.0, 4.0, 5.0, 2.0, 3.0]);
+
+        let back = csc.to_csr();
+
+        assert_eq!(back.shape(), csr.shape());
+        assert_eq!(back.indptr(), csr.indptr());
+        assert_eq!(back.indices(), csr.indices());
+        assert_eq!(back.data(), csr.data());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_csc_matmul_wrong_length() {
+        let csc = CscMatrix::from_aij((2, 3), &[0, 1], &[0, 2], &[1.0, 2.0]).unwrap();
+        let mut res = vec![0.0; 2];
+        csc.matmul(1.0, &[1.0, 1.0], 0.0, &mut res);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_csc_matmul_transpose_wrong_length() {
+        let csc = CscMatrix::from_aij((2, 3), &[0, 1], &[0, 2], &[1.0, 2.0]).unwrap();
+        let mut res = vec![0.0; 2];
+        csc.matmul_transpose(1.0, &[1.0, 1.0], 0.0, &mut res);
+    }
+
+    #[test]
+    fn test_csc_apply_transpose_and_adjoint() -> SparseLinAlgResult<()> {
+        use cauchy::c64;
+        use sparse_traits::types::SparseLinAlgError;
+        use sparse_traits::{Element, LinearSpace};
+
+        // Test the matrix [[1, i, 0], [0, 2, 1 - i]]
+        let rows = vec![0, 0, 1, 1];
+        let cols = vec![0, 1, 1, 2];
+        let data = vec![
+            c64::new(1.0, 0.0),
+            c64::new(0.0, 1.0),
+            c64::new(2.0, 0.0),
+            c64::new(1.0, -1.0),
+        ];
+        let csc = CscMatrix::from_aij((2, 3), &rows, &cols, &data)?;
+        let op = &csc as &dyn OperatorBase<Domain = _, Range = _>;
+        assert!(op.has_apply_transpose());
+        assert!(op.has_apply_adjoint());
+
+        let range = LocalIndexableVectorSpace::<c64>::new(2);
+        let domain = LocalIndexableVectorSpace::<c64>::new(3);
+        let mut x = range.create_element();
+        let mut y = domain.create_element();
+        x.view_mut()
+            .view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&[c64::new(1.0, 0.0), c64::new(0.0, 1.0)]);
+
+        op.apply_transpose(x.view(), y.view_mut())?;
+        let expected = [c64::new(1.0, 0.0), c64::new(0.0, 3.0), c64::new(1.0, 1.0)];
+        assert_eq!(y.view().view().unwrap().data(), &expected);
+
+        op.apply_adjoint(x.view(), y.view_mut())?;
+        let expected = [c64::new(1.0, 0.0), c64::new(0.0, 1.0), c64::new(-1.0, 1.0)];
+        assert_eq!(y.view().view().unwrap().data(), &expected);
+
+        // The domain and range of the transpose are swapped.
+        assert!(matches!(
+            csc.apply_transpose(y.view(), x.view_mut()),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 2,
+                actual: 3
+            })
+        ));
+        Ok(())
+    }
+}
@@ -1,19 +1,37 @@
 //! Definition of CSR matrices.
 
-use crate::local::sparse::SparseMatType;
-use sparse_traits::types::SparseLinAlgResult;
+use crate::local::index_layout::LocalIndexLayout;
+use crate::local::indexable_space::LocalIndexableVectorSpace;
+use crate::local::sparse::{check_apply_dimensions, transpose_compressed, SparseMatType};
+use num::{Float, NumCast, Zero};
+use sparse_traits::linalg::matrix_traits::{
+    Matrix, MultSumInto, Norm1, Norm2, NormFrob, NormInfty, ScalarMult, Trace,
+};
+use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+use sparse_traits::types::{SparseLinAlgError, SparseLinAlgResult};
+use sparse_traits::{
+    AsApply, AsApplyAdjoint, AsApplyTranspose, ElementView, ElementViewMut, IndexableSpace,
+    OperatorBase,
+};
 
 use sparse_traits::types::{IndexType, Scalar};
 
+#[derive(Debug)]
 pub struct CsrMatrix<T: Scalar> {
     mat_type: SparseMatType,
     shape: (IndexType, IndexType),
+    domain: LocalIndexableVectorSpace<T>,
+    range: LocalIndexableVectorSpace<T>,
     indices: Vec<IndexType>,
     indptr: Vec<IndexType>,
     data: Vec<T>,
 }
 
 impl<T: Scalar> CsrMatrix<T> {
+    /// Create a new CSR matrix from its raw arrays without any checks.
+    ///
+    /// Operations on a matrix whose arrays do not describe a valid matrix panic or give
+    /// wrong results. Use [CsrMatrix::try_new] for data from untrusted sources.
     pub fn new(
         shape: (IndexType, IndexType),
         indices: Vec<IndexType>,
@@ -23,12 +41,64 @@ impl<T: Scalar> CsrMatrix<T> {
         Self {
             mat_type: SparseMatType::Csr,
             shape,
+            domain: LocalIndexableVectorSpace::new(shape.1),
+            range: LocalIndexableVectorSpace::new(shape.0),
             indices,
             indptr,
             data,
         }
     }
 
+    /// Create a new CSR matrix after checking that the arrays describe a valid matrix.
+    ///
+    /// `indptr` must have `1 + shape.0` nondecreasing entries starting at zero and ending
+    /// at the number of nonzeros, `indices` and `data` must have the same length and all
+    /// column indices must be smaller than `shape.1`.
+    pub fn try_new(
+        shape: (IndexType, IndexType),
+        indices: Vec<IndexType>,
+        indptr: Vec<IndexType>,
+        data: Vec<T>,
+    ) -> SparseLinAlgResult<Self> {
+        if indptr.len() != 1 + shape.0 {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: 1 + shape.0,
+                actual: indptr.len(),
+            });
+        }
+        if indices.len() != data.len() {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: data.len(),
+                actual: indices.len(),
+            });
+        }
+        if indptr[0] != 0 {
+            return Err(SparseLinAlgError::InvalidStructure(format!(
+                "`indptr` must start with 0, found {}",
+                indptr[0]
+            )));
+        }
+        if let Some(row) = indptr.windows(2).position(|w| w[0] > w[1]) {
+            return Err(SparseLinAlgError::InvalidStructure(format!(
+                "`indptr` decreases at row {row}"
+            )));
+        }
+        if indptr[shape.0] != data.len() {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: data.len(),
+                actual: indptr[shape.0],
+            });
+        }
+        if let Some(&col) = indices.iter().find(|&&col| col >= shape.1) {
+            return Err(SparseLinAlgError::IndexOutOfBounds {
+                index: col,
+                bound: shape.1,
+            });
+        }
+
+        Ok(Self::new(shape, indices, indptr, data))
+    }
+
     pub fn mat_type(&self) -> &SparseMatType {
         &self.mat_type
     }
@@ -49,23 +119,82 @@ impl<T: Scalar> CsrMatrix<T> {
         &self.data
     }
 
+    /// Compute `y = alpha * A * x + beta * y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `x` and `y` do not match the shape of the matrix.
     pub fn matmul(&self, alpha: T, x: &[T], beta: T, y: &mut [T]) {
+        assert_eq!(x.len(), self.shape.1);
+        assert_eq!(y.len(), self.shape.0);
+
         for (row, out) in y.iter_mut().enumerate() {
-            *out = beta * *out
-                + alpha * {
-                    let c1 = self.indptr()[row];
-                    let c2 = self.indptr()[1 + row];
-                    let mut acc = T::zero();
-
-                    for index in c1..c2 {
-                        unsafe {
-                            let col = *self.indices().get_unchecked(index);
-                            acc += *self.data().get_unchecked(index) * *x.get_unchecked(col);
-                        }
-                    }
-                    acc
-                }
+            let range = self.indptr[row]..self.indptr[1 + row];
+            let acc = self.indices[range.clone()]
+                .iter()
+                .zip(&self.data[range])
+                .fold(T::zero(), |acc, (&col, &value)| acc + value * x[col]);
+            *out = beta * *out + alpha * acc;
+        }
+    }
+
+    /// Compute `y = alpha * A^T * x + beta * y` without forming the transpose.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `x` and `y` do not match the shape of the matrix.
+    pub fn matmul_transpose(&self, alpha: T, x: &[T], beta: T, y: &mut [T]) {
+        self.matmul_transpose_impl(alpha, x, beta, y, |value| value);
+    }
+
+    /// Compute `y = alpha * A^H * x + beta * y` without forming the conjugate transpose.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `x` and `y` do not match the shape of the matrix.
+    pub fn matmul_adjoint(&self, alpha: T, x: &[T], beta: T, y: &mut [T]) {
+        self.matmul_transpose_impl(alpha, x, beta, y, |value| value.conj());
+    }
+
+    fn matmul_transpose_impl<F: Fn(T) -> T>(&self, alpha: T, x: &[T], beta: T, y: &mut [T], op: F) {
+        assert_eq!(x.len(), self.shape.0);
+        assert_eq!(y.len(), self.shape.1);
+
+        for out in y.iter_mut() {
+            *out *= beta;
+        }
+
+        for (row, &x_row) in x.iter().enumerate() {
+            let scaled = alpha * x_row;
+            for index in self.indptr[row]..self.indptr[1 + row] {
+                y[self.indices[index]] += op(self.data[index]) * scaled;
+            }
+        }
+    }
+
+    /// Return the transpose `A^T` as a new CSR matrix.
+    ///
+    /// The column indices of the result are sorted within each row.
+    pub fn transpose(&self) -> Self {
+        let (indptr, indices, data) = transpose_compressed(
+            self.shape.0,
+            self.shape.1,
+            &self.indptr,
+            &self.indices,
+            &self.data,
+        );
+        Self::new((self.shape.1, self.shape.0), indices, indptr, data)
+    }
+
+    /// Return the conjugate transpose `A^H` as a new CSR matrix.
+    ///
+    /// The column indices of the result are sorted within each row.
+    pub fn conj_transpose(&self) -> Self {
+        let mut result = self.transpose();
+        for value in result.data.iter_mut() {
+            *value = value.conj();
         }
+        result
     }
 
     pub fn from_aij(
@@ -98,7 +227,463 @@ impl<T: Scalar> CsrMatrix<T> {
             new_data.push(data[sorted[index]]);
         }
 
-        Ok(Self::new(shape, indices, indptr, new_data))
+        Self::try_new(shape, indices, indptr, new_data)
+    }
+
+    /// Compute the linear combination `alpha * a + beta * b`.
+    ///
+    /// The sparsity pattern of the result is the union of the patterns of `a` and `b`.
+    /// Its column indices are sorted and free of duplicates.
+    pub fn add(alpha: T, a: &Self, beta: T, b: &Self) -> SparseLinAlgResult<Self> {
+        check_same_shape(a, b)?;
+
+        let (nrows, ncols) = a.shape;
+        let mut indptr = Vec::<IndexType>::with_capacity(1 + nrows);
+        let mut indices = Vec::<IndexType>::with_capacity(a.nnz().max(b.nnz()));
+        let mut data = Vec::<T>::with_capacity(a.nnz().max(b.nnz()));
+
+        // `position[col]` is the index into `data` for `col` in the current row.
+        // `marker[col]` records the row for which `position[col]` is valid.
+        let mut position = vec![0 as IndexType; ncols];
+        let mut marker = vec![IndexType::MAX; ncols];
+        let mut entries = Vec::<(IndexType, T)>::new();
+
+        indptr.push(0);
+        for row in 0..nrows {
+            let row_start = indices.len();
+            for (mat, scalar) in [(a, alpha), (b, beta)] {
+                for index in mat.indptr[row]..mat.indptr[1 + row] {
+                    let col = mat.indices[index];
+                    let value = scalar * mat.data[index];
+                    if marker[col] == row {
+                        data[position[col]] += value;
+                    } else {
+                        marker[col] = row;
+                        position[col] = indices.len();
+                        indices.push(col);
+                        data.push(value);
+                    }
+                }
+            }
+
+            entries.clear();
+            entries.extend(
+                indices[row_start..]
+                    .iter()
+                    .copied()
+                    .zip(data[row_start..].iter().copied()),
+            );
+            entries.sort_unstable_by_key(|&(col, _)| col);
+            for (offset, &(col, value)) in entries.iter().enumerate() {
+                indices[row_start + offset] = col;
+                data[row_start + offset] = value;
+            }
+            indptr.push(indices.len());
+        }
+
+        Ok(Self::new(a.shape, indices, indptr, data))
+    }
+
+    /// Estimate the 2-norm (largest singular value) with a power iteration on `A^H A`.
+    ///
+    /// The iteration stops after `max_iter` steps or when the relative change of the
+    /// estimate is below `tol`. The estimate is a lower bound of the 2-norm.
+    pub fn estimate_norm_2(&self, max_iter: usize, tol: T::Real) -> T::Real {
+        let zero = <T::Real as Zero>::zero();
+        if self.shape.0 == 0 || self.shape.1 == 0 {
+            return zero;
+        }
+
+        // A non-constant starting vector is less likely to be orthogonal
+        // to the dominant singular vector than a vector of ones.
+        let mut x: Vec<T> = (0..self.shape.1)
+            .map(|index| T::from_real(<T::Real as NumCast>::from(1 + index).unwrap()))
+            .collect();
+        let mut y = vec![T::zero(); self.shape.0];
+        let mut estimate = zero;
+
+        for _ in 0..max_iter {
+            let x_norm = vector_norm(&x);
+            if x_norm == zero {
+                break;
+            }
+            for elem in x.iter_mut() {
+                *elem /= T::from_real(x_norm);
+            }
+
+            self.matmul(T::one(), &x, T::zero(), &mut y);
+            let new_estimate = vector_norm(&y);
+            self.matmul_adjoint(T::one(), &y, T::zero(), &mut x);
+
+            let converged = Float::abs(new_estimate - estimate) <= tol * new_estimate;
+            estimate = new_estimate;
+            if converged {
+                break;
+            }
+        }
+        estimate
+    }
+
+    /// Number of stored entries, including explicit zeros and duplicates.
+    pub fn nnz(&self) -> IndexType {
+        self.data.len()
+    }
+
+    /// Return true if the column indices in each row are strictly increasing.
+    ///
+    /// This means that they are sorted and that there are no duplicate entries.
+    pub fn has_canonical_format(&self) -> bool {
+        (0..self.shape.0).all(|row| {
+            self.indices[self.indptr[row]..self.indptr[1 + row]]
+                .windows(2)
+                .all(|w| w[0] < w[1])
+        })
+    }
+
+    /// Sort the column indices within each row.
+    pub fn sort_indices(&mut self) {
+        let mut entries = Vec::<(IndexType, T)>::new();
+        for row in 0..self.shape.0 {
+            let range = self.indptr[row]..self.indptr[1 + row];
+            if self.indices[range.clone()].windows(2).all(|w| w[0] <= w[1]) {
+                continue;
+            }
+
+            entries.clear();
+            entries.extend(
+                self.indices[range.clone()]
+                    .iter()
+                    .copied()
+                    .zip(self.data[range.clone()].iter().copied()),
+            );
+            entries.sort_by_key(|&(col, _)| col);
+
+            for (index, (col, value)) in range.zip(entries.iter()) {
+                self.indices[index] = *col;
+                self.data[index] = *value;
+            }
+        }
+    }
+
+    /// Sum up duplicate entries so that each column appears at most once per row.
+    ///
+    /// The column indices are sorted first.
+    pub fn sum_duplicates(&mut self) {
+        self.sort_indices();
+        self.compress(|indices, data, col, value| {
+            if indices.last() == Some(&col) {
+                *data.last_mut().unwrap() += value;
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Remove explicitly stored zero entries.
+    pub fn eliminate_zeros(&mut self) {
+        self.compress(|_, _, _, value| value != T::zero());
+    }
+
+    // Rebuild the arrays in place, keeping an entry if `keep` returns true.
+    //
+    // `keep` is passed the entries of the current row that have been kept so far.
+    fn compress<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&[IndexType], &mut [T], IndexType, T) -> bool,
+    {
+        let mut count: IndexType = 0;
+        for row in 0..self.shape.0 {
+            let row_start = count;
+            for index in self.indptr[row]..self.indptr[1 + row] {
+                let col = self.indices[index];
+                let value = self.data[index];
+                if keep(
+                    &self.indices[row_start..count],
+                    &mut self.data[row_start..count],
+                    col,
+                    value,
+                ) {
+                    self.indices[count] = col;
+                    self.data[count] = value;
+                    count += 1;
+                }
+            }
+            self.indptr[row] = row_start;
+        }
+        self.indptr[self.shape.0] = count;
+        self.indices.truncate(count);
+        self.data.truncate(count);
+    }
+}
+
+/// Immutable view onto the arrays of a CSR matrix.
+pub struct CsrMatrixView<'a, T: Scalar> {
+    shape: (IndexType, IndexType),
+    indices: &'a [IndexType],
+    indptr: &'a [IndexType],
+    data: &'a [T],
+}
+
+/// Mutable view onto a CSR matrix. The values can be changed but not the sparsity pattern.
+pub struct CsrMatrixViewMut<'a, T: Scalar> {
+    shape: (IndexType, IndexType),
+    indices: &'a [IndexType],
+    indptr: &'a [IndexType],
+    data: &'a mut [T],
+}
+
+macro_rules! implement_csr_view {
+    ($ViewType:ident) => {
+        impl<T: Scalar> $ViewType<'_, T> {
+            pub fn shape(&self) -> (IndexType, IndexType) {
+                self.shape
+            }
+
+            pub fn indices(&self) -> &[IndexType] {
+                self.indices
+            }
+
+            pub fn indptr(&self) -> &[IndexType] {
+                self.indptr
+            }
+
+            pub fn data(&self) -> &[T] {
+                self.data
+            }
+        }
+    };
+}
+implement_csr_view!(CsrMatrixView);
+implement_csr_view!(CsrMatrixViewMut);
+
+impl<T: Scalar> CsrMatrixViewMut<'_, T> {
+    pub fn data_mut(&mut self) -> &mut [T] {
+        self.data
+    }
+}
+
+impl<T: Scalar> Matrix for CsrMatrix<T> {
+    type T = T;
+    type Ind = LocalIndexLayout;
+    type View<'a>
+        = CsrMatrixView<'a, T>
+    where
+        Self: 'a;
+    type ViewMut<'a>
+        = CsrMatrixViewMut<'a, T>
+    where
+        Self: 'a;
+
+    fn view<'a>(&'a self) -> Option<Self::View<'a>> {
+        Some(CsrMatrixView {
+            shape: self.shape,
+            indices: &self.indices,
+            indptr: &self.indptr,
+            data: &self.data,
+        })
+    }
+
+    fn view_mut<'a>(&'a mut self) -> Option<Self::ViewMut<'a>> {
+        Some(CsrMatrixViewMut {
+            shape: self.shape,
+            indices: &self.indices,
+            indptr: &self.indptr,
+            data: &mut self.data,
+        })
+    }
+
+    fn column_layout(&self) -> &Self::Ind {
+        self.domain.index_layout()
+    }
+
+    fn row_layout(&self) -> &Self::Ind {
+        self.range.index_layout()
+    }
+}
+
+impl<T: Scalar> ScalarMult for CsrMatrix<T> {
+    fn scalar_mult(&mut self, scalar: Self::T) {
+        for elem in self.data.iter_mut() {
+            *elem *= scalar;
+        }
+    }
+}
+
+impl<T: Scalar> MultSumInto for CsrMatrix<T> {
+    fn mult_sum_into(&mut self, other: &Self, scalar: Self::T) -> SparseLinAlgResult<()> {
+        check_same_shape(self, other)?;
+        if self.indptr == other.indptr && self.indices == other.indices {
+            // Same sparsity pattern. We can work directly on the values.
+            for (first, second) in self.data.iter_mut().zip(other.data.iter()) {
+                *first += scalar * *second;
+            }
+            Ok(())
+        } else {
+            *self = Self::add(T::one(), self, scalar, other)?;
+            Ok(())
+        }
+    }
+}
+
+fn vector_norm<T: Scalar>(x: &[T]) -> T::Real {
+    Float::sqrt(
+        x.iter()
+            .fold(<T::Real as Zero>::zero(), |acc, &elem| acc + elem.square()),
+    )
+}
+
+impl<T: Scalar> NormFrob for CsrMatrix<T> {
+    fn norm_frob(&self) -> <Self::T as Scalar>::Real {
+        Float::sqrt(
+            self.data
+                .iter()
+                .fold(<T::Real as Zero>::zero(), |acc, &elem| acc + elem.square()),
+        )
+    }
+}
+
+impl<T: Scalar> Norm1 for CsrMatrix<T> {
+    fn norm_1(&self) -> <Self::T as Scalar>::Real {
+        let mut col_sums = vec![<T::Real as Zero>::zero(); self.shape.1];
+        for (&col, &elem) in self.indices.iter().zip(self.data.iter()) {
+            col_sums[col] += elem.abs();
+        }
+        col_sums
+            .into_iter()
+            .fold(<T::Real as Zero>::zero(), Float::max)
+    }
+}
+
+impl<T: Scalar> NormInfty for CsrMatrix<T> {
+    fn norm_infty(&self) -> <Self::T as Scalar>::Real {
+        (0..self.shape.0)
+            .map(|row| {
+                self.data[self.indptr[row]..self.indptr[1 + row]]
+                    .iter()
+                    .fold(<T::Real as Zero>::zero(), |acc, &elem| acc + elem.abs())
+            })
+            .fold(<T::Real as Zero>::zero(), Float::max)
+    }
+}
+
+impl<T: Scalar> Norm2 for CsrMatrix<T> {
+    /// Estimate the 2-norm with [CsrMatrix::estimate_norm_2].
+    ///
+    /// Uses at most 100 iterations and a relative tolerance of `sqrt(eps)`.
+    fn norm_2(&self) -> <Self::T as Scalar>::Real {
+        self.estimate_norm_2(100, Float::sqrt(<T::Real as Float>::epsilon()))
+    }
+}
+
+impl<T: Scalar> Trace for CsrMatrix<T> {
+    /// Sum of the diagonal entries. For rectangular matrices the diagonal has
+    /// `min(rows, cols)` entries.
+    fn trace(&self) -> Self::T {
+        let mut acc = T::zero();
+        for row in 0..self.shape.0.min(self.shape.1) {
+            for index in self.indptr[row]..self.indptr[1 + row] {
+                if self.indices[index] == row {
+                    acc += self.data[index];
+                }
+            }
+        }
+        acc
+    }
+}
+
+fn check_same_shape<T: Scalar>(a: &CsrMatrix<T>, b: &CsrMatrix<T>) -> SparseLinAlgResult<()> {
+    if a.shape.0 != b.shape.0 {
+        return Err(SparseLinAlgError::SingleDimensionError {
+            expected: a.shape.0,
+            actual: b.shape.0,
+        });
+    }
+    if a.shape.1 != b.shape.1 {
+        return Err(SparseLinAlgError::SingleDimensionError {
+            expected: a.shape.1,
+            actual: b.shape.1,
+        });
+    }
+    Ok(())
+}
+
+impl<T: Scalar> OperatorBase for CsrMatrix<T> {
+    type Domain = LocalIndexableVectorSpace<T>;
+    type Range = LocalIndexableVectorSpace<T>;
+
+    /// The space of vectors the matrix acts on. Its dimension is the number of columns.
+    fn domain(&self) -> &Self::Domain {
+        &self.domain
+    }
+
+    /// The space of vectors the matrix maps into. Its dimension is the number of rows.
+    fn range(&self) -> &Self::Range {
+        &self.range
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_transpose(
+        &self,
+    ) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<T: Scalar> AsApply for CsrMatrix<T> {
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        let x_view = x.view().unwrap();
+        let mut y_view = y.view_mut().unwrap();
+
+        check_apply_dimensions((self.shape.1, self.shape.0), x_view.len(), y_view.len())?;
+
+        self.matmul(T::one(), x_view.data(), T::zero(), y_view.data_mut());
+        Ok(())
+    }
+}
+
+impl<T: Scalar> AsApplyTranspose for CsrMatrix<T> {
+    fn apply_transpose(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let x_view = x.view().unwrap();
+        let mut y_view = y.view_mut().unwrap();
+
+        check_apply_dimensions((self.shape.0, self.shape.1), x_view.len(), y_view.len())?;
+
+        self.matmul_transpose(T::one(), x_view.data(), T::zero(), y_view.data_mut());
+        Ok(())
+    }
+}
+
+impl<T: Scalar> AsApplyAdjoint for CsrMatrix<T> {
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let x_view = x.view().unwrap();
+        let mut y_view = y.view_mut().unwrap();
+
+        check_apply_dimensions((self.shape.0, self.shape.1), x_view.len(), y_view.len())?;
+
+        self.matmul_adjoint(T::one(), x_view.data(), T::zero(), y_view.data_mut());
+        Ok(())
     }
 }
 
@@ -106,6 +691,9 @@ impl<T: Scalar> CsrMatrix<T> {
 mod test {
 
     use super::*;
+    use crate::local::indexable_vector::LocalIndexableVector;
+    use cauchy::c64;
+    use sparse_traits::{Element, IndexLayout, LinearSpace};
 
     #[test]
     fn test_csr_from_aij() {
@@ -153,4 +741,331 @@ mod test {
         assert_eq!(res[0], 35.0);
         assert_eq!(res[1], 79.0);
     }
+
+    #[test]
+    #[should_panic]
+    fn test_csr_matmul_invalid_column() {
+        // The column index 5 is out of bounds for the unchecked constructor.
+        let csr = CsrMatrix::new((2, 2), vec![0, 5], vec![0, 1, 2], vec![1.0, 2.0]);
+        let mut res = vec![0.0; 2];
+        csr.matmul(1.0, &[1.0, 1.0], 0.0, &mut res);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_csr_matmul_wrong_length() {
+        let csr = CsrMatrix::from_aij((2, 2), &[0, 1], &[0, 1], &[1.0, 2.0]).unwrap();
+        let mut res = vec![0.0; 3];
+        csr.matmul(1.0, &[1.0, 1.0], 0.0, &mut res);
+    }
+
+    #[test]
+    fn test_csr_transpose() {
+        // Test the matrix [[1, 2, 0], [0, 3, 4]]
+        let rows = vec![0, 0, 1, 1];
+        let cols = vec![0, 1, 1, 2];
+        let data = vec![1.0, 2.0, 3.0, 4.0];
+
+        let csr = CsrMatrix::from_aij((2, 3), &rows, &cols, &data).unwrap();
+        let transpose = csr.transpose();
+
+        assert_eq!(transpose.shape(), (3, 2));
+        assert_eq!(transpose.indptr(), &[0, 1, 3, 4]);
+        assert_eq!(transpose.indices(), &[0, 0, 1, 1]);
+        assert_eq!(transpose.data(), &[1.0, 2.0, 3.0, 4.0]);
+
+        // Execute 2 * [1, 2, 3] + A^T * x with x = [1, 2].
+        // Expected result is [3, 12, 14].
+        let x = vec![1.0, 2.0];
+        let mut res = vec![1.0, 2.0, 3.0];
+
+        csr.matmul_transpose(1.0, &x, 2.0, &mut res);
+
+        assert_eq!(res, vec![3.0, 12.0, 14.0]);
+    }
+
+    #[test]
+    fn test_csr_conj_transpose() {
+        // Test the matrix [[1 + 2i, 0], [3i, 4 - i]]
+        let rows = vec![0, 1, 1];
+        let cols = vec![0, 0, 1];
+        let data = vec![c64::new(1.0, 2.0), c64::new(0.0, 3.0), c64::new(4.0, -1.0)];
+
+        let csr = CsrMatrix::from_aij((2, 2), &rows, &cols, &data).unwrap();
+        let adjoint = csr.conj_transpose();
+
+        assert_eq!(adjoint.indptr(), &[0, 2, 3]);
+        assert_eq!(adjoint.indices(), &[0, 1, 1]);
+        assert_eq!(
+            adjoint.data(),
+            &[c64::new(1.0, -2.0), c64::new(0.0, -3.0), c64::new(4.0, 1.0)]
+        );
+
+        let x = vec![c64::new(1.0, 1.0), c64::new(2.0, 0.0)];
+        let alpha = c64::new(0.5, 2.0);
+        let beta = c64::new(1.0, -1.0);
+
+        // Compare the kernels against a matvec with the explicitly formed matrices.
+        let mut expected = vec![c64::new(1.0, 0.0), c64::new(0.0, 1.0)];
+        let mut actual = expected.clone();
+        adjoint.matmul(alpha, &x, beta, &mut expected);
+        csr.matmul_adjoint(alpha, &x, beta, &mut actual);
+        assert_eq!(actual, expected);
+
+        let mut expected = vec![c64::new(1.0, 0.0), c64::new(0.0, 1.0)];
+        let mut actual = expected.clone();
+        csr.transpose().matmul(alpha, &x, beta, &mut expected);
+        csr.matmul_transpose(alpha, &x, beta, &mut actual);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_csr_try_new() {
+        // Test the matrix [[1, 2], [0, 3]]
+        let csr = CsrMatrix::try_new((2, 2), vec![0, 1, 1], vec![0, 2, 3], vec![1.0, 2.0, 3.0]);
+        assert!(csr.is_ok());
+
+        assert!(matches!(
+            CsrMatrix::try_new((2, 2), vec![0, 1, 1], vec![0, 2], vec![1.0, 2.0, 3.0]),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 3,
+                actual: 2
+            })
+        ));
+        assert!(matches!(
+            CsrMatrix::try_new((2, 2), vec![0, 1], vec![0, 2, 3], vec![1.0, 2.0, 3.0]),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 3,
+                actual: 2
+            })
+        ));
+        assert!(matches!(
+            CsrMatrix::try_new((2, 2), vec![0, 1, 1], vec![0, 2, 1], vec![1.0, 2.0, 3.0]),
+            Err(SparseLinAlgError::InvalidStructure(_))
+        ));
+        assert!(matches!(
+            CsrMatrix::try_new((2, 2), vec![0, 1, 1], vec![0, 2, 4], vec![1.0, 2.0, 3.0]),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 3,
+                actual: 4
+            })
+        ));
+        assert!(matches!(
+            CsrMatrix::try_new((2, 2), vec![0, 1, 2], vec![0, 2, 3], vec![1.0, 2.0, 3.0]),
+            Err(SparseLinAlgError::IndexOutOfBounds { index: 2, bound: 2 })
+        ));
+
+        // `from_aij` rejects out of bounds columns.
+        assert!(CsrMatrix::from_aij((2, 2), &[0], &[2], &[1.0]).is_err());
+    }
+
+    #[test]
+    fn test_csr_canonicalize() {
+        // The matrix [[3, 0, 2], [0, 0, 0], [0, 4, 0]] with unsorted rows, a
+        // duplicate entry at (0, 0) and an explicit zero at (2, 2).
+        let mut csr = CsrMatrix::try_new(
+            (3, 3),
+            vec![2, 0, 0, 2, 1],
+            vec![0, 3, 3, 5],
+            vec![2.0, 1.0, 2.0, 0.0, 4.0],
+        )
+        .unwrap();
+
+        assert!(!csr.has_canonical_format());
+
+        csr.sort_indices();
+        assert_eq!(csr.indices(), &[0, 0, 2, 1, 2]);
+        assert!(!csr.has_canonical_format());
+
+        csr.sum_duplicates();
+        assert_eq!(csr.indptr(), &[0, 2, 2, 4]);
+        assert_eq!(csr.indices(), &[0, 2, 1, 2]);
+        assert_eq!(csr.data(), &[3.0, 2.0, 4.0, 0.0]);
+        assert!(csr.has_canonical_format());
+
+        csr.eliminate_zeros();
+        assert_eq!(csr.nnz(), 3);
+        assert_eq!(csr.indptr(), &[0, 2, 2, 3]);
+        assert_eq!(csr.indices(), &[0, 2, 1]);
+        assert_eq!(csr.data(), &[3.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn test_csr_add() {
+        // A = [[1, 0], [2, 3]], B = [[0, 4], [5, 0]]
+        let a = CsrMatrix::from_aij((2, 2), &[0, 1, 1], &[0, 0, 1], &[1.0, 2.0, 3.0]).unwrap();
+        let b = CsrMatrix::from_aij((2, 2), &[0, 1], &[1, 0], &[4.0, 5.0]).unwrap();
+
+        // 2 * A - B = [[2, -4], [-1, 6]]
+        let c = CsrMatrix::add(2.0, &a, -1.0, &b).unwrap();
+
+        assert_eq!(c.indptr(), &[0, 2, 4]);
+        assert_eq!(c.indices(), &[0, 1, 0, 1]);
+        assert_eq!(c.data(), &[2.0, -4.0, -1.0, 6.0]);
+
+        let wrong_shape = CsrMatrix::<f64>::from_aij((2, 3), &[], &[], &[]).unwrap();
+        assert!(CsrMatrix::add(1.0, &a, 1.0, &wrong_shape).is_err());
+    }
+
+    #[test]
+    fn test_csr_mult_sum_into() {
+        let mut a = CsrMatrix::from_aij((2, 2), &[0, 1, 1], &[0, 0, 1], &[1.0, 2.0, 3.0]).unwrap();
+
+        // Same pattern: A + 0.5 * A
+        let other = CsrMatrix::from_aij((2, 2), &[0, 1, 1], &[0, 0, 1], &[2.0, 2.0, 2.0]).unwrap();
+        a.mult_sum_into(&other, 0.5).unwrap();
+
+        assert_eq!(a.indices(), &[0, 0, 1]);
+        assert_eq!(a.data(), &[2.0, 3.0, 4.0]);
+
+        // Different pattern: shift by -sigma * I with sigma = 2.
+        let identity = CsrMatrix::from_aij((2, 2), &[0, 1], &[0, 1], &[1.0, 1.0]).unwrap();
+        a.mult_sum_into(&identity, -2.0).unwrap();
+
+        assert_eq!(a.indices(), &[0, 0, 1]);
+        assert_eq!(a.data(), &[0.0, 3.0, 2.0]);
+
+        let upper = CsrMatrix::from_aij((2, 2), &[0], &[1], &[1.0]).unwrap();
+        a.mult_sum_into(&upper, 1.0).unwrap();
+
+        assert_eq!(a.indptr(), &[0, 2, 4]);
+        assert_eq!(a.indices(), &[0, 1, 0, 1]);
+        assert_eq!(a.data(), &[0.0, 1.0, 3.0, 2.0]);
+
+        a.scalar_mult(2.0);
+        assert_eq!(a.data(), &[0.0, 2.0, 6.0, 4.0]);
+    }
+
+    #[test]
+    fn test_csr_matrix_trait() {
+        // Test the matrix [[1, 2, 0], [0, 3, 4]]
+        let mut csr =
+            CsrMatrix::from_aij((2, 3), &[0, 0, 1, 1], &[0, 1, 1, 2], &[1.0, 2.0, 3.0, 4.0])
+                .unwrap();
+
+        assert_eq!(Matrix::shape(&csr), (2, 3));
+        assert_eq!(csr.row_layout().number_of_global_indices(), 2);
+        assert_eq!(csr.column_layout().number_of_global_indices(), 3);
+
+        csr.view_mut().unwrap().data_mut()[3] = 5.0;
+
+        let view = csr.view().unwrap();
+        assert_eq!(view.shape(), (2, 3));
+        assert_eq!(view.indptr(), &[0, 2, 4]);
+        assert_eq!(view.data(), &[1.0, 2.0, 3.0, 5.0]);
+    }
+
+    #[test]
+    fn test_csr_norms() {
+        // Test the matrix [[1 + i, -2], [0, 3i]]
+        let data = vec![c64::new(1.0, 1.0), c64::new(-2.0, 0.0), c64::new(0.0, 3.0)];
+        let csr = CsrMatrix::from_aij((2, 2), &[0, 0, 1], &[0, 1, 1], &data).unwrap();
+
+        float_eq::assert_float_eq!(csr.norm_frob(), 15.0_f64.sqrt(), ulps <= 4);
+        float_eq::assert_float_eq!(csr.norm_1(), 5.0, ulps <= 4);
+        float_eq::assert_float_eq!(csr.norm_infty(), 2.0 + 2.0_f64.sqrt(), ulps <= 4);
+        assert_eq!(csr.trace(), c64::new(1.0, 4.0));
+
+        // The singular values of [[3, 0, 0], [0, -5, 0]] are 5 and 3.
+        let csr = CsrMatrix::from_aij((2, 3), &[0, 1], &[0, 1], &[3.0, -5.0]).unwrap();
+
+        float_eq::assert_float_eq!(csr.norm_2(), 5.0, rmax <= 1e-6);
+        assert_eq!(csr.trace(), -2.0);
+    }
+
+    #[test]
+    fn test_csr_apply() -> SparseLinAlgResult<()> {
+        // Test the matrix [[1, 2, 0], [0, 3, 4]]
+        let rows = vec![0, 0, 1, 1];
+        let cols = vec![0, 1, 1, 2];
+        let data = vec![1.0, 2.0, 3.0, 4.0];
+
+        let csr = CsrMatrix::from_aij((2, 3), &rows, &cols, &data)?;
+
+        let mut x = csr.domain().create_element();
+        let mut y = csr.range().create_element();
+
+        x.view_mut()
+            .view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&[1.0, 2.0, 3.0]);
+
+        // Apply through the base trait object to check that `as_apply` is wired up.
+        let op = &csr as &dyn OperatorBase<Domain = _, Range = _>;
+        assert!(op.has_apply());
+        op.apply(x.view(), y.view_mut())?;
+
+        assert_eq!(y.view().view().unwrap().data(), &[5.0, 18.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_csr_apply_adjoint() -> SparseLinAlgResult<()> {
+        // Test the matrix [[1, i, 0], [0, 2, 1 - i]]
+        let rows = vec![0, 0, 1, 1];
+        let cols = vec![0, 1, 1, 2];
+        let data = vec![
+            c64::new(1.0, 0.0),
+            c64::new(0.0, 1.0),
+            c64::new(2.0, 0.0),
+            c64::new(1.0, -1.0),
+        ];
+
+        let csr = CsrMatrix::from_aij((2, 3), &rows, &cols, &data)?;
+
+        let mut x = csr.range().create_element();
+        let mut y = csr.domain().create_element();
+        x.view_mut()
+            .view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&[c64::new(1.0, 0.0), c64::new(0.0, 1.0)]);
+
+        csr.apply_adjoint(x.view(), y.view_mut())?;
+
+        let expected = [c64::new(1.0, 0.0), c64::new(0.0, 1.0), c64::new(-1.0, 1.0)];
+        for (actual, expected) in y.view().view().unwrap().iter().zip(expected.iter()) {
+            assert_eq!(actual, expected);
+        }
+
+        // The transpose does not conjugate and is found through the base trait object.
+        let op = &csr as &dyn OperatorBase<Domain = _, Range = _>;
+        assert!(op.has_apply_transpose());
+        assert!(op.has_apply_adjoint());
+        op.apply_transpose(x.view(), y.view_mut())?;
+
+        let expected = [c64::new(1.0, 0.0), c64::new(0.0, 3.0), c64::new(1.0, 1.0)];
+        for (actual, expected) in y.view().view().unwrap().iter().zip(expected.iter()) {
+            assert_eq!(actual, expected);
+        }
+
+        // The domain and range of the adjoint are swapped.
+        let result = csr.apply_adjoint(y.view(), x.view_mut());
+        assert!(matches!(
+            result,
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 2,
+                actual: 3
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_csr_apply_dimension_mismatch() {
+        let csr = CsrMatrix::<f64>::from_aij((2, 3), &[0], &[0], &[1.0]).unwrap();
+
+        let x = LocalIndexableVector::<f64>::new(2);
+        let mut y = LocalIndexableVector::<f64>::new(2);
+
+        let result = csr.apply(&x, &mut y);
+        assert!(matches!(
+            result,
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
 }
@@ -0,0 +1,198 @@
+//! Sparse matrix-matrix products of CSR matrices.
+//!
+//! The product `C = A * B` is computed in two phases. The symbolic phase
+//! determines the sparsity pattern of `C` from the patterns of `A` and `B`.
+//! The numeric phase fills in the values. If only the values of `A` and `B`
+//! change, as for Galerkin products in multigrid, the pattern can be reused.
+
+use crate::local::sparse::csr_mat::CsrMatrix;
+use sparse_traits::types::{IndexType, Scalar};
+use sparse_traits::types::{SparseLinAlgError, SparseLinAlgResult};
+
+/// The sparsity pattern of a product `A * B` of two CSR matrices.
+#[derive(Debug, Clone)]
+pub struct MatMatPattern {
+    shape: (IndexType, IndexType),
+    indices: Vec<IndexType>,
+    indptr: Vec<IndexType>,
+}
+
+impl MatMatPattern {
+    pub fn shape(&self) -> (IndexType, IndexType) {
+        self.shape
+    }
+
+    pub fn indices(&self) -> &[IndexType] {
+        &self.indices
+    }
+
+    pub fn indptr(&self) -> &[IndexType] {
+        &self.indptr
+    }
+
+    /// Number of entries in the product.
+    pub fn nnz(&self) -> IndexType {
+        self.indices.len()
+    }
+}
+
+fn check_inner_dimension<T: Scalar>(a: &CsrMatrix<T>, b: &CsrMatrix<T>) -> SparseLinAlgResult<()> {
+    if a.shape().1 != b.shape().0 {
+        return Err(SparseLinAlgError::SingleDimensionError {
+            expected: a.shape().1,
+            actual: b.shape().0,
+        });
+    }
+    Ok(())
+}
+
+impl<T: Scalar> CsrMatrix<T> {
+    /// Compute the sparsity pattern of `self * other`.
+    ///
+    /// The column indices of the pattern are sorted within each row.
+    pub fn matmat_symbolic(&self, other: &CsrMatrix<T>) -> SparseLinAlgResult<MatMatPattern> {
+        check_inner_dimension(self, other)?;
+
+        let shape = (self.shape().0, other.shape().1);
+        let mut indptr = Vec::<IndexType>::with_capacity(1 + shape.0);
+        let mut indices = Vec::<IndexType>::new();
+
+        // `marker[col]` is the last row in which `col` was found.
+        let mut marker = vec![IndexType::MAX; shape.1];
+
+        indptr.push(0);
+        for row in 0..shape.0 {
+            let row_start = indices.len();
+            for index in self.indptr()[row]..self.indptr()[1 + row] {
+                let inner = self.indices()[index];
+                for &col in &other.indices()[other.indptr()[inner]..other.indptr()[1 + inner]] {
+                    if marker[col] != row {
+                        marker[col] = row;
+                        indices.push(col);
+                    }
+                }
+            }
+            indices[row_start..].sort_unstable();
+            indptr.push(indices.len());
+        }
+
+        Ok(MatMatPattern {
+            shape,
+            indices,
+            indptr,
+        })
+    }
+
+    /// Compute the values of `self * other` on a pattern from [CsrMatrix::matmat_symbolic].
+    ///
+    /// The pattern must have been computed from matrices with the same sparsity
+    /// patterns as `self` and `other`. Otherwise an error is returned.
+    pub fn matmat_numeric(
+        &self,
+        other: &CsrMatrix<T>,
+        pattern: &MatMatPattern,
+    ) -> SparseLinAlgResult<CsrMatrix<T>> {
+        check_inner_dimension(self, other)?;
+
+        let shape = (self.shape().0, other.shape().1);
+        if pattern.shape != shape {
+            return Err(SparseLinAlgError::InvalidStructure(format!(
+                "Pattern has shape {:?} but product has shape {:?}",
+                pattern.shape, shape
+            )));
+        }
+
+        let mut data = vec![T::zero(); pattern.nnz()];
+
+        // `position[col]` is the index into `data` for `col` in the current row.
+        // `marker[col]` records the row for which `position[col]` is valid.
+        let mut position = vec![0 as IndexType; shape.1];
+        let mut marker = vec![IndexType::MAX; shape.1];
+
+        for row in 0..shape.0 {
+            for index in pattern.indptr[row]..pattern.indptr[1 + row] {
+                position[pattern.indices[index]] = index;
+                marker[pattern.indices[index]] = row;
+            }
+
+            for index in self.indptr()[row]..self.indptr()[1 + row] {
+                let inner = self.indices()[index];
+                let value = self.data()[index];
+                for other_index in other.indptr()[inner]..other.indptr()[1 + inner] {
+                    let col = other.indices()[other_index];
+                    if marker[col] != row {
+                        return Err(SparseLinAlgError::InvalidStructure(format!(
+                            "Entry ({row}, {col}) of the product is not in the pattern"
+                        )));
+                    }
+                    data[position[col]] += value * other.data()[other_index];
+                }
+            }
+        }
+
+        Ok(CsrMatrix::new(
+            shape,
+            pattern.indices.clone(),
+            pattern.indptr.clone(),
+            data,
+        ))
+    }
+
+    /// Compute the sparse matrix product `self * other`.
+    pub fn matmat(&self, other: &CsrMatrix<T>) -> SparseLinAlgResult<CsrMatrix<T>> {
+        let pattern = self.matmat_symbolic(other)?;
+        self.matmat_numeric(other, &pattern)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_matmat() {
+        // A = [[1, 2, 0], [0, 0, 3]], B = [[1, 0], [0, 2], [4, 5]]
+        // A * B = [[1, 4], [12, 15]]
+        let a = CsrMatrix::from_aij((2, 3), &[0, 0, 1], &[0, 1, 2], &[1.0, 2.0, 3.0]).unwrap();
+        let b = CsrMatrix::from_aij((3, 2), &[0, 1, 2, 2], &[0, 1, 1, 0], &[1.0, 2.0, 5.0, 4.0])
+            .unwrap();
+
+        let c = a.matmat(&b).unwrap();
+
+        assert_eq!(c.shape(), (2, 2));
+        assert_eq!(c.indptr(), &[0, 2, 4]);
+        assert_eq!(c.indices(), &[0, 1, 0, 1]);
+        assert_eq!(c.data(), &[1.0, 4.0, 12.0, 15.0]);
+        assert!(c.has_canonical_format());
+
+        assert!(matches!(
+            a.matmat(&a),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_matmat_reuse_pattern() {
+        // A = [[1, 0], [1, 1]], B = [[0, 2], [3, 0]]
+        let a = CsrMatrix::from_aij((2, 2), &[0, 1, 1], &[0, 0, 1], &[1.0, 1.0, 1.0]).unwrap();
+        let b = CsrMatrix::from_aij((2, 2), &[0, 1], &[1, 0], &[2.0, 3.0]).unwrap();
+
+        let pattern = a.matmat_symbolic(&b).unwrap();
+        assert_eq!(pattern.nnz(), 3);
+
+        // Change the values of A to [[2, 0], [1, -1]] and reuse the pattern.
+        let a = CsrMatrix::new((2, 2), vec![0, 0, 1], vec![0, 1, 3], vec![2.0, 1.0, -1.0]);
+        let c = a.matmat_numeric(&b, &pattern).unwrap();
+
+        assert_eq!(c.indices(), &[1, 0, 1]);
+        assert_eq!(c.data(), &[4.0, -3.0, 2.0]);
+
+        // A pattern that misses entries of the product is rejected.
+        let pattern = b.matmat_symbolic(&b).unwrap();
+        assert!(a.matmat_numeric(&b, &pattern).is_err());
+    }
+}
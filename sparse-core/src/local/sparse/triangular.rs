@@ -0,0 +1,607 @@
+//! Sparse triangular solves with CSR matrices.
+//!
+//! The solves use one triangle of a square CSR matrix and ignore the entries of the
+//! other one, so the factors of an incomplete or complete factorization can be stored
+//! in a single matrix. A right-hand side is overwritten by the solution. Several
+//! right-hand sides are solved at once by storing them one after the other.
+//!
+//! Forward and backward substitution are sequential in general, but row `i` only
+//! depends on the rows `j` with a nonzero entry `T[i, j]`. The [level sets](LevelSets)
+//! of a triangle group its rows such that each row only depends on rows of earlier
+//! levels. The rows of a level are independent and can be solved concurrently, see
+//! [CsrMatrix::solve_triangular_levels].
+
+use crate::local::sparse::csr_mat::CsrMatrix;
+use sparse_traits::types::{IndexType, Scalar};
+use sparse_traits::types::{SparseLinAlgError, SparseLinAlgResult};
+use std::sync::{Barrier, RwLock};
+
+/// Minimum number of rows of a level that each thread of a level-scheduled solve gets.
+///
+/// Smaller levels are solved on the calling thread.
+const MIN_ROWS_PER_THREAD: usize = 32;
+
+/// The triangle of a matrix used by a triangular solve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Triangle {
+    Lower,
+    Upper,
+}
+
+/// Whether a triangular solve uses the stored diagonal or assumes a unit diagonal.
+///
+/// For [Diagonal::Unit] the stored diagonal entries are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagonal {
+    NonUnit,
+    Unit,
+}
+
+/// The level sets of a triangle of a square matrix.
+///
+/// The rows of level `k` only depend on rows of the levels `0..k`. Within a level
+/// the rows are sorted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelSets {
+    triangle: Triangle,
+    nnz: IndexType,
+    indptr: Vec<IndexType>,
+    rows: Vec<IndexType>,
+}
+
+impl LevelSets {
+    /// The triangle the level sets were computed for.
+    pub fn triangle(&self) -> Triangle {
+        self.triangle
+    }
+
+    /// The number of rows of the matrix.
+    pub fn dimension(&self) -> IndexType {
+        self.rows.len()
+    }
+
+    /// The number of stored entries of the matrix.
+    pub fn nnz(&self) -> IndexType {
+        self.nnz
+    }
+
+    /// The number of levels, which is the length of the longest chain of dependent rows.
+    pub fn number_of_levels(&self) -> usize {
+        self.indptr.len() - 1
+    }
+
+    /// The rows of the given level.
+    pub fn level(&self, level: usize) -> &[IndexType] {
+        &self.rows[self.indptr[level]..self.indptr[1 + level]]
+    }
+}
+
+/// Whether `(row, col)` lies strictly inside the triangle.
+fn in_triangle(triangle: Triangle, row: IndexType, col: IndexType) -> bool {
+    match triangle {
+        Triangle::Lower => col < row,
+        Triangle::Upper => col > row,
+    }
+}
+
+fn zero_diagonal(row: IndexType) -> SparseLinAlgError {
+    SparseLinAlgError::OperationFailed(format!(
+        "triangular solve with the zero diagonal entry in row {row}"
+    ))
+}
+
+impl<T: Scalar> CsrMatrix<T> {
+    /// Solve `T x = b` for the given triangle `T` of the matrix.
+    ///
+    /// On entry `x` holds one or more right-hand sides of the length of the matrix
+    /// one after the other, on exit the solutions. Duplicate diagonal entries are
+    /// summed. A zero diagonal entry is an error, in which case `x` is left partially
+    /// updated.
+    pub fn solve_triangular(
+        &self,
+        triangle: Triangle,
+        diagonal: Diagonal,
+        x: &mut [T],
+    ) -> SparseLinAlgResult<()> {
+        let n = self.check_triangular_solve(x.len())?;
+
+        for rhs in x.chunks_mut(n.max(1)) {
+            for step in 0..n {
+                let row = match triangle {
+                    Triangle::Lower => step,
+                    Triangle::Upper => n - 1 - step,
+                };
+                rhs[row] = self.solve_triangular_row(triangle, diagonal, row, rhs, 0, n)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Solve `T^T x = b` for the given triangle `T` of the matrix without forming the
+    /// transpose.
+    ///
+    /// See [CsrMatrix::solve_triangular] for the layout of `x`.
+    pub fn solve_triangular_transpose(
+        &self,
+        triangle: Triangle,
+        diagonal: Diagonal,
+        x: &mut [T],
+    ) -> SparseLinAlgResult<()> {
+        self.solve_triangular_transpose_impl(triangle, diagonal, x, |value| value)
+    }
+
+    /// Solve `T^H x = b` for the given triangle `T` of the matrix without forming the
+    /// conjugate transpose.
+    ///
+    /// See [CsrMatrix::solve_triangular] for the layout of `x`.
+    pub fn solve_triangular_adjoint(
+        &self,
+        triangle: Triangle,
+        diagonal: Diagonal,
+        x: &mut [T],
+    ) -> SparseLinAlgResult<()> {
+        self.solve_triangular_transpose_impl(triangle, diagonal, x, |value| value.conj())
+    }
+
+    /// Compute the level sets of the given triangle of the matrix.
+    ///
+    /// The level of a row is one more than the highest level of the rows it depends
+    /// on, or zero if it depends on no other row. The cost is O(nnz + n). The level
+    /// sets of `T^T` are those of the other triangle of [CsrMatrix::transpose].
+    pub fn level_sets(&self, triangle: Triangle) -> SparseLinAlgResult<LevelSets> {
+        let n = self.check_triangular_solve(0)?;
+
+        let mut level = vec![0 as IndexType; n];
+        let mut number_of_levels = 0;
+        for step in 0..n {
+            let row = match triangle {
+                Triangle::Lower => step,
+                Triangle::Upper => n - 1 - step,
+            };
+            level[row] = (self.indptr()[row]..self.indptr()[1 + row])
+                .map(|index| self.indices()[index])
+                .filter(|&col| in_triangle(triangle, row, col))
+                .map(|col| 1 + level[col])
+                .max()
+                .unwrap_or(0);
+            number_of_levels = number_of_levels.max(1 + level[row]);
+        }
+
+        // Bucket the rows by level, keeping them sorted within each level.
+        let mut indptr = vec![0 as IndexType; 1 + number_of_levels];
+        for &row_level in &level {
+            indptr[1 + row_level] += 1;
+        }
+        for index in 0..number_of_levels {
+            indptr[1 + index] += indptr[index];
+        }
+        let mut next = indptr.clone();
+        let mut rows = vec![0 as IndexType; n];
+        for (row, &row_level) in level.iter().enumerate() {
+            rows[next[row_level]] = row;
+            next[row_level] += 1;
+        }
+
+        Ok(LevelSets {
+            triangle,
+            nnz: self.nnz(),
+            indptr,
+            rows,
+        })
+    }
+
+    /// Solve `T x = b` level by level, using up to `threads` threads.
+    ///
+    /// The triangle `T` is the one `levels` was computed for, and `levels` must have
+    /// been computed from a matrix with the sparsity pattern of `self`. Level sets of a
+    /// matrix with a different dimension or number of stored entries give an error.
+    ///
+    /// The threads are started once per solve and wait for each other at the end of
+    /// every level. Levels with fewer than 32 rows per thread are solved by a single
+    /// thread, and if all levels are that small the solve runs on the calling thread.
+    /// See [CsrMatrix::solve_triangular] for the layout of `x`.
+    pub fn solve_triangular_levels(
+        &self,
+        levels: &LevelSets,
+        diagonal: Diagonal,
+        x: &mut [T],
+        threads: usize,
+    ) -> SparseLinAlgResult<()>
+    where
+        T: Send + Sync,
+    {
+        let n = self.check_triangular_solve(x.len())?;
+        if levels.dimension() != n {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: n,
+                actual: levels.dimension(),
+            });
+        }
+        if levels.nnz() != self.nnz() {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: self.nnz(),
+                actual: levels.nnz(),
+            });
+        }
+        let threads = threads.max(1);
+        let is_parallel = |level: usize| {
+            threads > 1 && levels.level(level).len().div_ceil(threads) >= MIN_ROWS_PER_THREAD
+        };
+
+        if x.is_empty() || !(0..levels.number_of_levels()).any(is_parallel) {
+            for level in 0..levels.number_of_levels() {
+                self.solve_triangular_rows(levels, diagonal, levels.level(level), x, n)?;
+            }
+            return Ok(());
+        }
+
+        // The rows of a parallel level are solved from the shared solution into a buffer
+        // of each thread, and written back once every thread is done reading. The rows
+        // of the other levels are solved in place by the first thread.
+        let x = RwLock::new(x);
+        let barrier = Barrier::new(threads);
+        let nrhs = x.read().unwrap().len() / n;
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|thread| {
+                    let (x, barrier, is_parallel) = (&x, &barrier, &is_parallel);
+                    scope.spawn(move || {
+                        let mut result = Ok(());
+                        let mut values = Vec::new();
+                        for level in 0..levels.number_of_levels() {
+                            let rows = levels.level(level);
+                            let parallel = is_parallel(level);
+                            let chunk = rows
+                                .chunks(rows.len().div_ceil(threads))
+                                .nth(thread)
+                                .unwrap_or_default();
+
+                            if parallel && result.is_ok() {
+                                let x = x.read().unwrap();
+                                values.clear();
+                                result = chunk.iter().try_for_each(|&row| {
+                                    for rhs in 0..nrhs {
+                                        values.push(self.solve_triangular_row(
+                                            levels.triangle(),
+                                            diagonal,
+                                            row,
+                                            &x,
+                                            rhs * n,
+                                            n,
+                                        )?);
+                                    }
+                                    Ok(())
+                                });
+                            }
+                            barrier.wait();
+
+                            if result.is_ok() {
+                                let mut x = x.write().unwrap();
+                                if parallel {
+                                    for (&row, row_values) in chunk.iter().zip(values.chunks(nrhs))
+                                    {
+                                        for (rhs, &value) in row_values.iter().enumerate() {
+                                            x[rhs * n + row] = value;
+                                        }
+                                    }
+                                } else if thread == 0 {
+                                    result = self
+                                        .solve_triangular_rows(levels, diagonal, rows, &mut x, n);
+                                }
+                            }
+                            barrier.wait();
+                        }
+                        result
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .try_for_each(|handle| handle.join().unwrap())
+        })
+    }
+
+    /// Solve the independent `rows` of a level in place.
+    fn solve_triangular_rows(
+        &self,
+        levels: &LevelSets,
+        diagonal: Diagonal,
+        rows: &[IndexType],
+        x: &mut [T],
+        n: IndexType,
+    ) -> SparseLinAlgResult<()> {
+        for &row in rows {
+            for offset in (0..x.len()).step_by(n) {
+                x[offset + row] =
+                    self.solve_triangular_row(levels.triangle(), diagonal, row, x, offset, n)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the matrix is square and that `len` is a multiple of its dimension.
+    ///
+    /// Returns the dimension of the matrix.
+    fn check_triangular_solve(&self, len: usize) -> SparseLinAlgResult<IndexType> {
+        let (rows, cols) = self.shape();
+        if rows != cols {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: rows,
+                actual: cols,
+            });
+        }
+        if !len.is_multiple_of(rows) {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: rows * len.div_ceil(rows.max(1)),
+                actual: len,
+            });
+        }
+        Ok(rows)
+    }
+
+    /// The solution in `row` of the right-hand side `x[offset..offset + n]`, given the
+    /// solution in the rows it depends on.
+    fn solve_triangular_row(
+        &self,
+        triangle: Triangle,
+        diagonal: Diagonal,
+        row: IndexType,
+        x: &[T],
+        offset: usize,
+        n: IndexType,
+    ) -> SparseLinAlgResult<T> {
+        let x = &x[offset..offset + n];
+        let mut acc = x[row];
+        let mut diag = T::zero();
+        for index in self.indptr()[row]..self.indptr()[1 + row] {
+            let col = self.indices()[index];
+            if col == row {
+                diag += self.data()[index];
+            } else if in_triangle(triangle, row, col) {
+                acc -= self.data()[index] * x[col];
+            }
+        }
+
+        match diagonal {
+            Diagonal::Unit => Ok(acc),
+            Diagonal::NonUnit if diag.is_zero() => Err(zero_diagonal(row)),
+            Diagonal::NonUnit => Ok(acc / diag),
+        }
+    }
+
+    /// Solve `op(T)^T x = b` by substitution with the columns of `op(T)^T`, which are the
+    /// rows of the matrix.
+    fn solve_triangular_transpose_impl<F: Fn(T) -> T>(
+        &self,
+        triangle: Triangle,
+        diagonal: Diagonal,
+        x: &mut [T],
+        op: F,
+    ) -> SparseLinAlgResult<()> {
+        let n = self.check_triangular_solve(x.len())?;
+
+        for rhs in x.chunks_mut(n.max(1)) {
+            for step in 0..n {
+                // The transpose of a lower triangle is upper triangular and vice versa.
+                let row = match triangle {
+                    Triangle::Lower => n - 1 - step,
+                    Triangle::Upper => step,
+                };
+                let range = self.indptr()[row]..self.indptr()[1 + row];
+
+                if diagonal == Diagonal::NonUnit {
+                    let diag = range
+                        .clone()
+                        .filter(|&index| self.indices()[index] == row)
+                        .fold(T::zero(), |acc, index| acc + op(self.data()[index]));
+                    if diag.is_zero() {
+                        return Err(zero_diagonal(row));
+                    }
+                    rhs[row] /= diag;
+                }
+
+                let value = rhs[row];
+                for index in range {
+                    let col = self.indices()[index];
+                    if in_triangle(triangle, row, col) {
+                        rhs[col] -= op(self.data()[index]) * value;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::solvers::test_problems::laplace_1d;
+    use cauchy::c64;
+
+    /// The matrix [[4, 1, 0], [2, 5, 1], [1, 3, 6]].
+    fn example() -> CsrMatrix<f64> {
+        CsrMatrix::from_aij(
+            (3, 3),
+            &[0, 0, 1, 1, 1, 2, 2, 2],
+            &[0, 1, 0, 1, 2, 0, 1, 2],
+            &[4.0, 1.0, 2.0, 5.0, 1.0, 1.0, 3.0, 6.0],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_solve_triangular() {
+        let mat = example();
+
+        // The right-hand sides are the triangles applied to [1, 2, 3] and [-1, 0, 1].
+        let mut x = vec![4.0, 12.0, 25.0, -4.0, -2.0, 5.0];
+        mat.solve_triangular(Triangle::Lower, Diagonal::NonUnit, &mut x)
+            .unwrap();
+        assert_eq!(x, [1.0, 2.0, 3.0, -1.0, 0.0, 1.0]);
+
+        let mut x = vec![6.0, 13.0, 18.0];
+        mat.solve_triangular(Triangle::Upper, Diagonal::NonUnit, &mut x)
+            .unwrap();
+        assert_eq!(x, [1.0, 2.0, 3.0]);
+
+        let mut x = vec![1.0, 4.0, 10.0];
+        mat.solve_triangular(Triangle::Lower, Diagonal::Unit, &mut x)
+            .unwrap();
+        assert_eq!(x, [1.0, 2.0, 3.0]);
+
+        let mut x = vec![11.0, 19.0, 18.0];
+        mat.solve_triangular_transpose(Triangle::Lower, Diagonal::NonUnit, &mut x)
+            .unwrap();
+        assert_eq!(x, [1.0, 2.0, 3.0]);
+
+        let mut x = vec![4.0, 11.0, 20.0, -4.0, -1.0, 6.0];
+        mat.solve_triangular_transpose(Triangle::Upper, Diagonal::NonUnit, &mut x)
+            .unwrap();
+        assert_eq!(x, [1.0, 2.0, 3.0, -1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_solve_triangular_adjoint() {
+        // L = [[1 + i, 0], [2, 1 - i]], so that L^H [1, i] = [1 + i, -1 + i].
+        let mat = CsrMatrix::from_aij(
+            (2, 2),
+            &[0, 0, 1, 1],
+            &[0, 1, 0, 1],
+            &[
+                c64::new(1.0, 1.0),
+                c64::new(7.0, 0.0),
+                c64::new(2.0, 0.0),
+                c64::new(1.0, -1.0),
+            ],
+        )
+        .unwrap();
+
+        let mut x = vec![c64::new(1.0, 1.0), c64::new(-1.0, 1.0)];
+        mat.solve_triangular_adjoint(Triangle::Lower, Diagonal::NonUnit, &mut x)
+            .unwrap();
+        assert_eq!(x, [c64::new(1.0, 0.0), c64::new(0.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_solve_triangular_errors() {
+        // A zero diagonal entry in row 1.
+        let mat = CsrMatrix::from_aij((2, 2), &[0, 1], &[0, 0], &[1.0, 1.0]).unwrap();
+        let mut x = vec![1.0, 1.0];
+        assert!(matches!(
+            mat.solve_triangular(Triangle::Lower, Diagonal::NonUnit, &mut x),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+        assert!(mat
+            .solve_triangular_transpose(Triangle::Lower, Diagonal::NonUnit, &mut x)
+            .is_err());
+
+        // The missing diagonal is not needed for a unit diagonal.
+        let mut x = vec![1.0, 1.0];
+        mat.solve_triangular(Triangle::Lower, Diagonal::Unit, &mut x)
+            .unwrap();
+        assert_eq!(x, [1.0, 0.0]);
+
+        let mut x = vec![1.0, 1.0, 1.0];
+        assert!(matches!(
+            mat.solve_triangular(Triangle::Lower, Diagonal::Unit, &mut x),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 4,
+                actual: 3
+            })
+        ));
+
+        let rectangular = CsrMatrix::from_aij((2, 3), &[0], &[0], &[1.0]).unwrap();
+        assert!(rectangular.level_sets(Triangle::Upper).is_err());
+    }
+
+    #[test]
+    fn test_level_sets() {
+        let mat = example();
+
+        let levels = mat.level_sets(Triangle::Lower).unwrap();
+        assert_eq!(levels.number_of_levels(), 3);
+        assert_eq!(levels.level(2), &[2]);
+
+        // Row 0 of the upper triangle depends on row 1, which depends on row 2.
+        let levels = mat.level_sets(Triangle::Upper).unwrap();
+        assert_eq!(levels.triangle(), Triangle::Upper);
+        assert_eq!(levels.level(0), &[2]);
+        assert_eq!(levels.level(2), &[0]);
+
+        // A tridiagonal matrix has one row per level.
+        let levels = laplace_1d(10).level_sets(Triangle::Lower).unwrap();
+        assert_eq!(levels.number_of_levels(), 10);
+
+        // Row i of L = I + E depends on row i - 100 only, giving five levels.
+        let n = 500;
+        let mut rows: Vec<usize> = (0..n).collect();
+        let mut cols: Vec<usize> = (0..n).collect();
+        let mut data = vec![2.0; n];
+        for row in 100..n {
+            rows.push(row);
+            cols.push(row - 100);
+            data.push(((row % 7) as f64) - 3.0);
+        }
+        let mat = CsrMatrix::from_aij((n, n), &rows, &cols, &data).unwrap();
+        let levels = mat.level_sets(Triangle::Lower).unwrap();
+        assert_eq!(levels.number_of_levels(), 5);
+        assert_eq!(levels.level(1), (100..200).collect::<Vec<_>>());
+
+        let b: Vec<f64> = (0..2 * n).map(|index| ((7 * index) % 11) as f64).collect();
+        let mut expected = b.clone();
+        mat.solve_triangular(Triangle::Lower, Diagonal::NonUnit, &mut expected)
+            .unwrap();
+
+        for threads in [1, 3] {
+            let mut x = b.clone();
+            mat.solve_triangular_levels(&levels, Diagonal::NonUnit, &mut x, threads)
+                .unwrap();
+            assert_eq!(x, expected);
+        }
+
+        // A chain of five rows followed by a level of 200 rows that depend on its end,
+        // so that single threaded and parallel levels alternate.
+        let n = 205;
+        let mut rows: Vec<usize> = (0..n).collect();
+        let mut cols: Vec<usize> = (0..n).collect();
+        let mut data = vec![4.0; n];
+        for row in 1..n {
+            rows.push(row);
+            cols.push(row.min(5) - 1);
+            data.push(1.0);
+        }
+        let chain = CsrMatrix::from_aij((n, n), &rows, &cols, &data).unwrap();
+        let chain_levels = chain.level_sets(Triangle::Lower).unwrap();
+        assert_eq!(chain_levels.number_of_levels(), 6);
+
+        let mut expected = b[..n].to_vec();
+        chain
+            .solve_triangular(Triangle::Lower, Diagonal::NonUnit, &mut expected)
+            .unwrap();
+        let mut x = b[..n].to_vec();
+        chain
+            .solve_triangular_levels(&chain_levels, Diagonal::NonUnit, &mut x, 3)
+            .unwrap();
+        assert_eq!(x, expected);
+
+        let levels = laplace_1d(10).level_sets(Triangle::Lower).unwrap();
+        let mut x = b;
+        assert!(mat
+            .solve_triangular_levels(&levels, Diagonal::NonUnit, &mut x, 2)
+            .is_err());
+
+        // The level sets of the diagonal do not fit a matrix of the same dimension with
+        // entries below the diagonal.
+        let diagonal = CsrMatrix::from_aij((n, n), &rows[..n], &cols[..n], &data[..n]).unwrap();
+        let diagonal_levels = diagonal.level_sets(Triangle::Lower).unwrap();
+        assert_eq!(diagonal_levels.nnz(), n);
+        let mut x = vec![1.0; n];
+        assert!(matches!(
+            chain.solve_triangular_levels(&diagonal_levels, Diagonal::NonUnit, &mut x, 2),
+            Err(SparseLinAlgError::SingleDimensionError { expected, actual })
+                if expected == 2 * n - 1 && actual == n
+        ));
+    }
+}
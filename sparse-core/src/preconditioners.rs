@@ -0,0 +1,134 @@
+//! Preconditioners built from sparse matrices.
+//!
+//! A preconditioner `M` is an operator that approximates the system matrix `A` and
+//! supports [as_apply_inverse](sparse_traits::OperatorBase::as_apply_inverse), which is
+//! what the [solvers](crate::solvers) apply. Where `M` is known explicitly, as for the
+//! Jacobi and incomplete factorization preconditioners, it can be applied as well, for
+//! example to check a factorization. [Relaxation] only defines `M` through its inverse.
+//!
+//! The preconditioners act on the locally owned entries of a vector, see [LocalEntries].
+//! On a distributed space they are built from the locally owned rows of the matrix and
+//! only couple the entries of a rank among themselves, so that applying them needs no
+//! communication.
+
+pub mod ic;
+pub mod ilu;
+pub mod jacobi;
+pub mod relaxation;
+
+pub use ic::{Ic, IcOptions};
+pub use ilu::{Ilu, IluPattern};
+pub use jacobi::{BlockJacobi, Jacobi};
+pub use relaxation::{Relaxation, SweepDirection};
+
+use mpi::traits::{Communicator, Equivalence};
+use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+use sparse_traits::types::{Scalar, SparseLinAlgError, SparseLinAlgResult};
+use sparse_traits::{ElementView, ElementViewMut, IndexLayout, IndexableSpace, LinearSpace};
+
+use crate::distributed::indexable_space::DistributedIndexableVectorSpace;
+use crate::local::indexable_space::LocalIndexableVectorSpace;
+use crate::local::sparse::csr_mat::CsrMatrix;
+
+/// Spaces whose elements store their locally owned entries contiguously.
+pub trait LocalEntries: LinearSpace {
+    /// The number of locally owned entries of an element.
+    fn number_of_local_entries(&self) -> usize;
+
+    /// Call `f` with the locally owned entries of `x` and `y`.
+    fn with_local_entries<'a, 'b, R>(
+        x: ElementView<'a, Self>,
+        y: ElementViewMut<'b, Self>,
+        f: impl FnOnce(&[Self::F], &mut [Self::F]) -> R,
+    ) -> R
+    where
+        Self: 'a + 'b;
+}
+
+impl<T: Scalar> LocalEntries for LocalIndexableVectorSpace<T> {
+    fn number_of_local_entries(&self) -> usize {
+        self.dimension()
+    }
+
+    fn with_local_entries<'a, 'b, R>(
+        x: ElementView<'a, Self>,
+        y: ElementViewMut<'b, Self>,
+        f: impl FnOnce(&[T], &mut [T]) -> R,
+    ) -> R
+    where
+        Self: 'a + 'b,
+    {
+        let x = x.view().unwrap();
+        let mut y = y.view_mut().unwrap();
+        f(x.data(), y.data_mut())
+    }
+}
+
+impl<'comm, T: Scalar + Equivalence, C: Communicator> LocalEntries
+    for DistributedIndexableVectorSpace<'comm, T, C>
+where
+    T::Real: Equivalence,
+{
+    fn number_of_local_entries(&self) -> usize {
+        self.index_layout().number_of_local_indices()
+    }
+
+    fn with_local_entries<'a, 'b, R>(
+        x: ElementView<'a, Self>,
+        y: ElementViewMut<'b, Self>,
+        f: impl FnOnce(&[T], &mut [T]) -> R,
+    ) -> R
+    where
+        Self: 'a + 'b,
+    {
+        let x = x.view().unwrap();
+        let mut y = y.view_mut().unwrap();
+        f(x.data(), y.data_mut())
+    }
+}
+
+/// Return an error unless the matrix has the given shape.
+fn check_shape<T: Scalar>(mat: &CsrMatrix<T>, rows: usize, cols: usize) -> SparseLinAlgResult<()> {
+    let (actual_rows, actual_cols) = mat.shape();
+    if actual_rows != rows {
+        return Err(SparseLinAlgError::SingleDimensionError {
+            expected: rows,
+            actual: actual_rows,
+        });
+    }
+    if actual_cols != cols {
+        return Err(SparseLinAlgError::SingleDimensionError {
+            expected: cols,
+            actual: actual_cols,
+        });
+    }
+    Ok(())
+}
+
+/// The entries `A[row, offset + row]` of all rows, summing duplicates.
+fn diagonal<T: Scalar>(mat: &CsrMatrix<T>, offset: usize) -> Vec<T> {
+    (0..mat.shape().0)
+        .map(|row| {
+            (mat.indptr()[row]..mat.indptr()[1 + row])
+                .filter(|&index| mat.indices()[index] == offset + row)
+                .fold(T::zero(), |acc, index| acc + mat.data()[index])
+        })
+        .collect()
+}
+
+/// The inverses of the diagonal entries, or an error if one of them is zero.
+fn invert_diagonal<T: Scalar>(diagonal: &[T]) -> SparseLinAlgResult<Vec<T>> {
+    diagonal
+        .iter()
+        .enumerate()
+        .map(|(row, &value)| {
+            if value.is_zero() {
+                Err(SparseLinAlgError::OperationFailed(format!(
+                    "inversion of the zero diagonal entry in row {row}"
+                )))
+            } else {
+                Ok(T::one() / value)
+            }
+        })
+        .collect()
+}
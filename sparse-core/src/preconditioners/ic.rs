@@ -0,0 +1,431 @@
+//! Incomplete Cholesky factorizations.
+
+use num::{Float, NumCast, One, Zero};
+use sparse_traits::linalg::matrix_traits::Matrix;
+use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+use sparse_traits::types::{Scalar, SparseLinAlgError, SparseLinAlgResult};
+use sparse_traits::{
+    AsApply, AsApplyAdjoint, AsApplyInverse, ElementView, ElementViewMut, OperatorBase,
+};
+
+use super::check_shape;
+use crate::local::indexable_space::LocalIndexableVectorSpace;
+use crate::local::sparse::check_apply_dimensions;
+use crate::local::sparse::csr_mat::CsrMatrix;
+use crate::local::sparse::triangular::{Diagonal, Triangle};
+
+/// Options of the incomplete Cholesky factorization.
+///
+/// The factorization of a Hermitian positive definite matrix can still break down with a
+/// pivot that is not positive. It is then restarted for `A + alpha diag(A)` with a shift
+/// `alpha` that is doubled, starting from `shift` or from `1E-3` if `shift` is zero,
+/// until it succeeds or `max_restarts` restarts have failed.
+#[derive(Debug, Clone)]
+pub struct IcOptions<R: Float> {
+    /// Add the dropped fill to the diagonal, which gives the modified incomplete Cholesky
+    /// factorization (MIC). For real matrices it preserves the row sums, `M 1 = A 1`.
+    pub modified: bool,
+    /// The shift `alpha` of the first attempt, which factorizes `A + alpha diag(A)`.
+    pub shift: R,
+    /// The number of restarts with a larger shift after a breakdown.
+    pub max_restarts: usize,
+}
+
+impl<R: Float> IcOptions<R> {
+    pub fn new(modified: bool, shift: R, max_restarts: usize) -> Self {
+        Self {
+            modified,
+            shift,
+            max_restarts,
+        }
+    }
+}
+
+impl<R: Float> Default for IcOptions<R> {
+    /// IC(0) without shift, which fails at the first breakdown.
+    fn default() -> Self {
+        Self::new(false, R::zero(), 0)
+    }
+}
+
+/// An incomplete Cholesky factorization `M = L L^H` of a Hermitian positive definite
+/// matrix.
+///
+/// `L` is lower triangular with a positive real diagonal and has the pattern of the lower
+/// triangle of the matrix, so this is IC(0), or MIC(0) with [IcOptions::modified]. Only
+/// the lower triangle of the matrix is read. `M` is Hermitian positive definite, so it
+/// is a symmetric preconditioner for CG and MINRES, and the solvers apply `M^{-1}` by a
+/// forward and a backward substitution with [CsrMatrix::solve_triangular_adjoint] and
+/// [CsrMatrix::solve_triangular]. As for [Ilu](super::Ilu) the substitutions are
+/// sequential.
+#[derive(Debug)]
+pub struct Ic<T: Scalar> {
+    space: LocalIndexableVectorSpace<T>,
+    // The pattern of L by columns, with the diagonal first in every column.
+    colptr: Vec<usize>,
+    rows: Vec<usize>,
+    // L^H by rows, which are the conjugated columns of L.
+    factor: CsrMatrix<T>,
+    shift: T::Real,
+}
+
+impl<T: Scalar> Ic<T> {
+    /// The IC(0) factorization without shift.
+    pub fn ic0(mat: &CsrMatrix<T>) -> SparseLinAlgResult<Self> {
+        Self::new(mat, &IcOptions::default())
+    }
+
+    /// The incomplete Cholesky factorization of the lower triangle of a square matrix.
+    ///
+    /// Returns an error if a pivot is not positive after all restarts.
+    pub fn new(mat: &CsrMatrix<T>, options: &IcOptions<T::Real>) -> SparseLinAlgResult<Self> {
+        let n = mat.shape().0;
+        check_shape(mat, n, n)?;
+
+        // Gather the lower triangle by columns, summing duplicates and adding missing
+        // diagonal entries.
+        let mut columns: Vec<Vec<(usize, T)>> = (0..n).map(|col| vec![(col, T::zero())]).collect();
+        for row in 0..n {
+            for index in mat.indptr()[row]..mat.indptr()[1 + row] {
+                let col = mat.indices()[index];
+                if col <= row {
+                    columns[col].push((row, mat.data()[index]));
+                }
+            }
+        }
+        let mut colptr = vec![0];
+        let mut rows = Vec::with_capacity(mat.nnz());
+        let mut lower = Vec::with_capacity(mat.nnz());
+        for mut column in columns {
+            column.sort_by_key(|&(row, _)| row);
+            for (row, value) in column {
+                if rows.len() > *colptr.last().unwrap() && *rows.last().unwrap() == row {
+                    *lower.last_mut().unwrap() += value;
+                } else {
+                    rows.push(row);
+                    lower.push(value);
+                }
+            }
+            colptr.push(rows.len());
+        }
+
+        let factor = CsrMatrix::new((n, n), rows.clone(), colptr.clone(), lower.clone());
+        let mut ic = Self {
+            space: LocalIndexableVectorSpace::new(n),
+            colptr,
+            rows,
+            factor,
+            shift: options.shift,
+        };
+        let mut restarts = 0;
+        loop {
+            match ic.factorize(&lower, options.modified) {
+                Ok(()) => return Ok(ic),
+                Err(row) if restarts == options.max_restarts => {
+                    return Err(SparseLinAlgError::OperationFailed(format!(
+                        "incomplete Cholesky factorization with a nonpositive pivot in row {row}"
+                    )));
+                }
+                Err(_) => {
+                    let two: T::Real = NumCast::from(2.0).unwrap();
+                    ic.shift = if ic.shift.is_zero() {
+                        NumCast::from(1E-3).unwrap()
+                    } else {
+                        two * ic.shift
+                    };
+                    restarts += 1;
+                }
+            }
+        }
+    }
+
+    /// The shift `alpha` of the successful attempt, which factorized `A + alpha diag(A)`.
+    pub fn shift(&self) -> T::Real {
+        self.shift
+    }
+
+    /// The number of entries of `L`.
+    pub fn nnz(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Factorize the lower triangle `lower` with the current shift in place of the values
+    /// of `L^H`, or return the row with a pivot that is not positive.
+    fn factorize(&mut self, lower: &[T], modified: bool) -> Result<(), usize> {
+        let colptr = &self.colptr;
+        let rows = &self.rows;
+        let mut factor = self.factor.view_mut().unwrap();
+        let values = factor.data_mut();
+        values.copy_from_slice(lower);
+        let scale = T::Real::one() + self.shift;
+        for col in 0..colptr.len() - 1 {
+            values[colptr[col]] = values[colptr[col]].mul_real(scale);
+        }
+
+        for col in 0..colptr.len() - 1 {
+            let (start, end) = (colptr[col], colptr[1 + col]);
+            let pivot = values[start].re();
+            if pivot <= T::Real::zero() || !pivot.is_finite() {
+                return Err(col);
+            }
+            let diagonal = Float::sqrt(pivot);
+            values[start] = T::from_real(diagonal);
+            for value in &mut values[1 + start..end] {
+                *value = value.div_real(diagonal);
+            }
+
+            // Update the columns to the right with the entries of this column.
+            for first in 1 + start..end {
+                let target = rows[first];
+                let target_rows = &rows[colptr[target]..colptr[1 + target]];
+                let factor = values[first].conj();
+                for second in first..end {
+                    let row = rows[second];
+                    let update = values[second] * factor;
+                    match target_rows.binary_search(&row) {
+                        Ok(offset) => values[colptr[target] + offset] -= update,
+                        Err(_) if modified => {
+                            values[colptr[row]] = values[colptr[row]].sub_real(update.re());
+                            values[colptr[target]] = values[colptr[target]].sub_real(update.re());
+                        }
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+        for value in values.iter_mut() {
+            *value = value.conj();
+        }
+        Ok(())
+    }
+
+    /// Overwrite `x` with `L^{-H} L^{-1} x`.
+    fn solve(&self, x: &mut [T]) -> SparseLinAlgResult<()> {
+        self.factor
+            .solve_triangular_adjoint(Triangle::Upper, Diagonal::NonUnit, x)?;
+        self.factor
+            .solve_triangular(Triangle::Upper, Diagonal::NonUnit, x)
+    }
+
+    /// Compute `y = L L^H x`.
+    fn multiply(&self, x: &[T], y: &mut [T]) {
+        let mut z = vec![T::zero(); x.len()];
+        self.factor.matmul(T::one(), x, T::zero(), &mut z);
+        y.fill(T::zero());
+        self.factor.matmul_adjoint(T::one(), &z, T::zero(), y);
+    }
+}
+
+impl<T: Scalar> OperatorBase for Ic<T> {
+    type Domain = LocalIndexableVectorSpace<T>;
+    type Range = LocalIndexableVectorSpace<T>;
+
+    fn domain(&self) -> &Self::Domain {
+        &self.space
+    }
+
+    fn range(&self) -> &Self::Range {
+        &self.space
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_inverse(
+        &self,
+    ) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<T: Scalar> AsApply for Ic<T> {
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        let n = self.colptr.len() - 1;
+        let x = x.view().unwrap();
+        let mut y = y.view_mut().unwrap();
+        check_apply_dimensions((n, n), x.len(), y.len())?;
+        self.multiply(x.data(), y.data_mut());
+        Ok(())
+    }
+}
+
+impl<T: Scalar> AsApplyAdjoint for Ic<T> {
+    /// `M` is Hermitian, so this is the same as [AsApply::apply].
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        self.apply(x, y)
+    }
+}
+
+impl<T: Scalar> AsApplyInverse for Ic<T> {
+    fn apply_inverse(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let n = self.colptr.len() - 1;
+        let x = x.view().unwrap();
+        let mut y = y.view_mut().unwrap();
+        check_apply_dimensions((n, n), x.len(), y.len())?;
+        y.data_mut().copy_from_slice(x.data());
+        self.solve(y.data_mut())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::solvers::cg::cg;
+    use crate::solvers::test_problems::{cg_iterations, laplace_1d, laplace_2d};
+    use crate::solvers::SolverOptions;
+    use cauchy::c64;
+    use sparse_traits::{Element, LinearSpace};
+
+    /// Return `M x`.
+    fn apply<T: Scalar>(ic: &Ic<T>, x: &[T]) -> Vec<T> {
+        let mut y = vec![T::zero(); x.len()];
+        ic.multiply(x, &mut y);
+        y
+    }
+
+    #[test]
+    fn test_ic0() {
+        // Without fill IC(0) is the exact Cholesky factorization.
+        let n = 20;
+        let mat = laplace_1d(n);
+        let ic = Ic::ic0(&mat).unwrap();
+        assert_eq!(ic.nnz(), 2 * n - 1);
+        assert_eq!(ic.shift(), 0.0);
+        assert!(cg_iterations(&mat, Some(&ic)) <= 1);
+
+        // The product L L^T agrees with A on the pattern of A.
+        let m = 8;
+        let mat = laplace_2d(m);
+        let ic = Ic::ic0(&mat).unwrap();
+        for col in [0, 9, 30, 63] {
+            let mut x = vec![0.0; m * m];
+            x[col] = 1.0;
+            let column = apply(&ic, &x);
+            for (row, value) in column.iter().enumerate() {
+                let index = (mat.indptr()[row]..mat.indptr()[1 + row])
+                    .find(|&index| mat.indices()[index] == col);
+                if let Some(index) = index {
+                    assert!((value - mat.data()[index]).abs() < 1E-14);
+                }
+            }
+        }
+
+        let x: Vec<f64> = (0..m * m).map(|index| 1.0 + index as f64).collect();
+        let mut y = apply(&ic, &x);
+        ic.solve(&mut y).unwrap();
+        for (actual, expected) in y.iter().zip(&x) {
+            assert!((actual - expected).abs() < 1E-12 * expected);
+        }
+        assert!(ic.has_apply_adjoint());
+
+        let plain = cg_iterations(&mat, None);
+        let preconditioned = cg_iterations(&mat, Some(&ic));
+        assert!(preconditioned < plain);
+
+        // Modified IC preserves the row sums.
+        let options = IcOptions::new(true, 0.0, 0);
+        let mic = Ic::new(&mat, &options).unwrap();
+        let ones = vec![1.0; m * m];
+        let mut row_sums = vec![0.0; m * m];
+        mat.matmul(1.0, &ones, 0.0, &mut row_sums);
+        for (actual, expected) in apply(&mic, &ones).iter().zip(row_sums) {
+            assert!((actual - expected).abs() < 1E-13);
+        }
+        assert!(cg_iterations(&mat, Some(&mic)) < plain);
+    }
+
+    #[test]
+    fn test_ic_shift() {
+        // A shift of the first attempt factorizes A + alpha diag(A).
+        let n = 10;
+        let mat = laplace_1d(n);
+        let ic = Ic::new(&mat, &IcOptions::new(false, 0.5, 0)).unwrap();
+        assert_eq!(ic.shift(), 0.5);
+        let x: Vec<f64> = (0..n).map(|index| 1.0 + index as f64).collect();
+        let mut expected = vec![0.0; n];
+        mat.matmul(1.0, &x, 0.0, &mut expected);
+        for (index, value) in expected.iter_mut().enumerate() {
+            *value += x[index];
+        }
+        for (actual, expected) in apply(&ic, &x).iter().zip(expected) {
+            assert!((actual - expected).abs() < 1E-12 * expected.abs());
+        }
+
+        // The indefinite matrix [[1, 2], [2, 1]] breaks down, and the restarts double the
+        // shift until it exceeds 1.
+        let mat = CsrMatrix::from_aij((2, 2), &[0, 1, 1], &[0, 0, 1], &[1.0, 2.0, 1.0]).unwrap();
+        assert!(matches!(
+            Ic::ic0(&mat),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+        assert!(Ic::new(&mat, &IcOptions::new(false, 0.0, 5)).is_err());
+        let ic = Ic::new(&mat, &IcOptions::new(false, 0.0, 20)).unwrap();
+        assert_eq!(ic.shift(), 1E-3 * 1024.0);
+        let ic = Ic::new(&mat, &IcOptions::new(false, 0.75, 1)).unwrap();
+        assert_eq!(ic.shift(), 1.5);
+    }
+
+    #[test]
+    fn test_ic_complex_hermitian() {
+        // The Hermitian positive definite matrix [[4, 1 - i], [1 + i, 3]] and its lower
+        // triangle give the same exact factorization.
+        let data = vec![
+            c64::new(4.0, 0.0),
+            c64::new(1.0, -1.0),
+            c64::new(1.0, 1.0),
+            c64::new(3.0, 0.0),
+        ];
+        let mat = CsrMatrix::from_aij((2, 2), &[0, 0, 1, 1], &[0, 1, 0, 1], &data).unwrap();
+        let lower =
+            CsrMatrix::from_aij((2, 2), &[0, 1, 1], &[0, 0, 1], &[data[0], data[2], data[3]])
+                .unwrap();
+        let ic = Ic::ic0(&mat).unwrap();
+        assert_eq!(ic.factor.data(), Ic::ic0(&lower).unwrap().factor.data());
+
+        let x = [c64::new(1.0, 2.0), c64::new(-1.0, 0.5)];
+        let mut expected = vec![c64::new(0.0, 0.0); 2];
+        mat.matmul(c64::new(1.0, 0.0), &x, c64::new(0.0, 0.0), &mut expected);
+        for (actual, expected) in apply(&ic, &x).iter().zip(&expected) {
+            assert!((actual - expected).norm() < 1E-14);
+        }
+        let mut y = expected.clone();
+        ic.solve(&mut y).unwrap();
+        for (actual, expected) in y.iter().zip(&x) {
+            assert!((actual - expected).norm() < 1E-14);
+        }
+
+        let space = LocalIndexableVectorSpace::<c64>::new(2);
+        let mut b = space.create_element();
+        b.view_mut()
+            .view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&expected);
+        let mut solution = space.create_element();
+        let prec = &ic as &dyn OperatorBase<Domain = _, Range = _>;
+        let options = SolverOptions::new(1E-12, 0.0, 10);
+        let result = cg(&space, &mat, Some(prec), &b, &mut solution, &options).unwrap();
+        assert!(result.converged());
+        assert!(result.iterations <= 1);
+    }
+}
@@ -0,0 +1,614 @@
+//! Incomplete LU factorizations.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use num::{Float, Zero};
+use sparse_traits::linalg::matrix_traits::Matrix;
+use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+use sparse_traits::types::{Scalar, SparseLinAlgError, SparseLinAlgResult};
+use sparse_traits::{AsApply, AsApplyInverse, ElementView, ElementViewMut, OperatorBase};
+
+use super::check_shape;
+use crate::local::indexable_space::LocalIndexableVectorSpace;
+use crate::local::sparse::check_apply_dimensions;
+use crate::local::sparse::csr_mat::CsrMatrix;
+use crate::local::sparse::triangular::{Diagonal, Triangle};
+
+/// The sparsity pattern of incomplete LU factors, the result of the symbolic phase.
+///
+/// Every row holds the strictly lower part of `L`, whose unit diagonal is implied, followed
+/// by the upper part of `U` including the diagonal. The column indices of each row are
+/// sorted. A pattern only depends on the positions of the nonzeros of a matrix, so it can
+/// be reused for matrices whose values change, see [Ilu::factorize] and
+/// [Ilu::refactorize].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IluPattern {
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    diagonal: Vec<usize>,
+}
+
+impl IluPattern {
+    /// The pattern of ILU(0), which is the pattern of the matrix and its diagonal.
+    pub fn ilu0<T: Scalar>(mat: &CsrMatrix<T>) -> SparseLinAlgResult<Self> {
+        Self::iluk(mat, 0)
+    }
+
+    /// The pattern of ILU(k), which allows fill up to the given level.
+    ///
+    /// The entries of the matrix have level zero, and an update of the entry `(i, j)`
+    /// by the entries `(i, k)` and `(k, j)` creates fill of level
+    /// `level(i, k) + level(k, j) + 1`.
+    pub fn iluk<T: Scalar>(mat: &CsrMatrix<T>, level: usize) -> SparseLinAlgResult<Self> {
+        let n = mat.shape().0;
+        check_shape(mat, n, n)?;
+
+        // The columns and levels of the strictly upper part of the rows that are done.
+        let mut upper: Vec<Vec<(usize, usize)>> = Vec::with_capacity(n);
+        let mut rows = Vec::with_capacity(n);
+        for row in 0..n {
+            let mut levels: BTreeMap<usize, usize> = mat.indices()
+                [mat.indptr()[row]..mat.indptr()[1 + row]]
+                .iter()
+                .map(|&col| (col, 0))
+                .collect();
+            levels.insert(row, 0);
+
+            // Fill only appears to the right of the column that is eliminated, so the
+            // columns can be visited in order while the row grows.
+            let mut next = levels.range(..row).next().map(|(&col, &lev)| (col, lev));
+            while let Some((col, col_level)) = next {
+                for &(fill_col, fill_level) in &upper[col] {
+                    let fill_level = col_level + fill_level + 1;
+                    if fill_level <= level {
+                        let entry = levels.entry(fill_col).or_insert(fill_level);
+                        *entry = (*entry).min(fill_level);
+                    }
+                }
+                next = levels
+                    .range(1 + col..row)
+                    .next()
+                    .map(|(&col, &lev)| (col, lev));
+            }
+
+            upper.push(
+                levels
+                    .range(1 + row..)
+                    .map(|(&col, &lev)| (col, lev))
+                    .collect(),
+            );
+            rows.push(levels.into_keys().collect());
+        }
+        Ok(Self::from_rows(rows))
+    }
+
+    /// Create the pattern from the sorted columns of every row, which include the diagonal.
+    fn from_rows(rows: Vec<Vec<usize>>) -> Self {
+        let mut indptr = vec![0];
+        let mut indices = Vec::new();
+        let mut diagonal = Vec::with_capacity(rows.len());
+        for (row, cols) in rows.into_iter().enumerate() {
+            diagonal.push(indices.len() + cols.partition_point(|&col| col < row));
+            indices.extend(cols);
+            indptr.push(indices.len());
+        }
+        Self {
+            indptr,
+            indices,
+            diagonal,
+        }
+    }
+
+    /// The factors with the given values on the pattern, as one CSR matrix.
+    fn factors<T: Scalar>(&self, values: Vec<T>) -> CsrMatrix<T> {
+        let n = self.dimension();
+        CsrMatrix::new((n, n), self.indices.clone(), self.indptr.clone(), values)
+    }
+
+    /// The dimension of the factorized matrix.
+    pub fn dimension(&self) -> usize {
+        self.diagonal.len()
+    }
+
+    /// The number of entries of both factors, counting the diagonal once.
+    pub fn nnz(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn indptr(&self) -> &[usize] {
+        &self.indptr
+    }
+
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+}
+
+/// An incomplete LU factorization `M = L U` of a square matrix.
+///
+/// `L` is unit lower triangular and `U` is upper triangular, and both only have entries
+/// in a prescribed [IluPattern]. ILU(0) keeps the pattern of the matrix, ILU(k) allows
+/// fill up to a given level and ILUT determines the pattern during the factorization by
+/// dropping small entries. As a preconditioner the solvers apply `M^{-1}` by a forward
+/// and a backward substitution with [CsrMatrix::solve_triangular]. The substitutions are
+/// sequential, since [CsrMatrix::solve_triangular_levels] needs scalars that are
+/// `Send + Sync`, which [Scalar] does not require.
+#[derive(Debug)]
+pub struct Ilu<T: Scalar> {
+    space: LocalIndexableVectorSpace<T>,
+    pattern: IluPattern,
+    // The values of the factors on the pattern.
+    factors: CsrMatrix<T>,
+}
+
+impl<T: Scalar> Ilu<T> {
+    /// The ILU(0) factorization with the pattern of the matrix.
+    pub fn ilu0(mat: &CsrMatrix<T>) -> SparseLinAlgResult<Self> {
+        Self::factorize(IluPattern::ilu0(mat)?, mat)
+    }
+
+    /// The ILU(k) factorization with fill up to the given level.
+    pub fn iluk(mat: &CsrMatrix<T>, level: usize) -> SparseLinAlgResult<Self> {
+        Self::factorize(IluPattern::iluk(mat, level)?, mat)
+    }
+
+    /// Factorize a matrix on a pattern from the symbolic phase.
+    ///
+    /// Entries of the matrix outside the pattern are dropped, just as the fill outside the
+    /// pattern. Returns an error if a pivot is zero.
+    pub fn factorize(pattern: IluPattern, mat: &CsrMatrix<T>) -> SparseLinAlgResult<Self> {
+        let mut ilu = Self {
+            space: LocalIndexableVectorSpace::new(pattern.dimension()),
+            factors: pattern.factors(vec![T::zero(); pattern.nnz()]),
+            pattern,
+        };
+        ilu.refactorize(mat)?;
+        Ok(ilu)
+    }
+
+    /// Factorize a matrix with new values on the pattern of this factorization.
+    pub fn refactorize(&mut self, mat: &CsrMatrix<T>) -> SparseLinAlgResult<()> {
+        let n = self.pattern.dimension();
+        check_shape(mat, n, n)?;
+        let indptr = &self.pattern.indptr;
+        let indices = &self.pattern.indices;
+        let diagonal = &self.pattern.diagonal;
+        let mut factors = self.factors.view_mut().unwrap();
+        let values = factors.data_mut();
+        values.fill(T::zero());
+
+        // The position of every column of the current row in the pattern.
+        let mut position = vec![usize::MAX; n];
+        for row in 0..n {
+            for index in indptr[row]..indptr[1 + row] {
+                position[indices[index]] = index;
+            }
+            for index in mat.indptr()[row]..mat.indptr()[1 + row] {
+                let pos = position[mat.indices()[index]];
+                if pos != usize::MAX {
+                    values[pos] += mat.data()[index];
+                }
+            }
+
+            for index in indptr[row]..diagonal[row] {
+                let col = indices[index];
+                let factor = values[index] / values[diagonal[col]];
+                values[index] = factor;
+                for upper in 1 + diagonal[col]..indptr[1 + col] {
+                    let pos = position[indices[upper]];
+                    if pos != usize::MAX {
+                        let value = values[upper];
+                        values[pos] -= factor * value;
+                    }
+                }
+            }
+            if values[diagonal[row]].is_zero() {
+                return Err(zero_pivot(row));
+            }
+
+            for index in indptr[row]..indptr[1 + row] {
+                position[indices[index]] = usize::MAX;
+            }
+        }
+        Ok(())
+    }
+
+    /// The ILUT factorization with a drop tolerance and a fill limit.
+    ///
+    /// In row `i` all entries of `L` and `U` with `|value| <= drop_tol * |a_i|` are
+    /// dropped, where `|a_i|` is the 2-norm of the row of the matrix. Of the remaining
+    /// entries, at most `fill` of the largest are kept in each of the strictly lower and
+    /// strictly upper parts, and the diagonal is always kept. The pattern is computed
+    /// together with the values, so there is no separate symbolic phase, but
+    /// [Ilu::refactorize] can reuse the pattern for a matrix with similar values.
+    /// A zero pivot or a non-finite entry of the factors, for example from a NaN in the
+    /// matrix, is an error.
+    pub fn ilut(mat: &CsrMatrix<T>, drop_tol: T::Real, fill: usize) -> SparseLinAlgResult<Self> {
+        let n = mat.shape().0;
+        check_shape(mat, n, n)?;
+
+        let mut rows = Vec::with_capacity(n);
+        let mut row_values = Vec::with_capacity(n);
+        // The diagonal and the strictly upper part of the rows of U that are done.
+        let mut pivots: Vec<T> = Vec::with_capacity(n);
+        let mut upper: Vec<Vec<(usize, T)>> = Vec::with_capacity(n);
+
+        let mut work = vec![T::zero(); n];
+        let mut nonzero = vec![false; n];
+        for row in 0..n {
+            let mut lower_cols = BTreeSet::new();
+            let mut upper_cols = vec![row];
+            nonzero[row] = true;
+            let mut norm = T::Real::zero();
+            for index in mat.indptr()[row]..mat.indptr()[1 + row] {
+                let col = mat.indices()[index];
+                if !nonzero[col] {
+                    nonzero[col] = true;
+                    if col < row {
+                        lower_cols.insert(col);
+                    } else {
+                        upper_cols.push(col);
+                    }
+                }
+                work[col] += mat.data()[index];
+                norm += mat.data()[index].square();
+            }
+            let tol = drop_tol * Float::sqrt(norm);
+
+            let mut lower = Vec::new();
+            while let Some(col) = lower_cols.pop_first() {
+                let factor = work[col] / pivots[col];
+                work[col] = T::zero();
+                nonzero[col] = false;
+                if factor.abs() <= tol {
+                    continue;
+                }
+                for &(fill_col, value) in &upper[col] {
+                    if !nonzero[fill_col] {
+                        nonzero[fill_col] = true;
+                        if fill_col < row {
+                            lower_cols.insert(fill_col);
+                        } else {
+                            upper_cols.push(fill_col);
+                        }
+                    }
+                    work[fill_col] -= factor * value;
+                }
+                lower.push((col, factor));
+            }
+
+            let pivot = work[row];
+            let mut strict_upper: Vec<(usize, T)> = upper_cols[1..]
+                .iter()
+                .map(|&col| (col, work[col]))
+                .filter(|(_, value)| value.abs() > tol)
+                .collect();
+            for &col in &upper_cols {
+                work[col] = T::zero();
+                nonzero[col] = false;
+            }
+            if pivot.is_zero() {
+                return Err(zero_pivot(row));
+            }
+            let mut entries = lower.iter().chain(&strict_upper);
+            if !pivot.abs().is_finite() || entries.any(|(_, value)| !value.abs().is_finite()) {
+                return Err(SparseLinAlgError::OperationFailed(format!(
+                    "incomplete LU factorization with a non-finite entry in row {row}"
+                )));
+            }
+            keep_largest(&mut lower, fill);
+            keep_largest(&mut strict_upper, fill);
+
+            let diagonal = [(row, pivot)];
+            let entries = lower.iter().chain(&diagonal).chain(&strict_upper);
+            rows.push(entries.clone().map(|&(col, _)| col).collect());
+            row_values.extend(entries.map(|&(_, value)| value));
+            pivots.push(pivot);
+            upper.push(strict_upper);
+        }
+
+        let pattern = IluPattern::from_rows(rows);
+        Ok(Self {
+            space: LocalIndexableVectorSpace::new(n),
+            factors: pattern.factors(row_values),
+            pattern,
+        })
+    }
+
+    /// The pattern of the factors.
+    pub fn pattern(&self) -> &IluPattern {
+        &self.pattern
+    }
+
+    /// The values of the factors in the order of the pattern.
+    pub fn values(&self) -> &[T] {
+        self.factors.data()
+    }
+
+    /// Overwrite `x` with `U^{-1} L^{-1} x`.
+    pub(super) fn solve(&self, x: &mut [T]) -> SparseLinAlgResult<()> {
+        self.factors
+            .solve_triangular(Triangle::Lower, Diagonal::Unit, x)?;
+        self.factors
+            .solve_triangular(Triangle::Upper, Diagonal::NonUnit, x)
+    }
+
+    /// Compute `y = L U x`.
+    pub(super) fn multiply(&self, x: &[T], y: &mut [T]) {
+        let pattern = &self.pattern;
+        let values = self.factors.data();
+        for (row, y) in y.iter_mut().enumerate() {
+            *y = (pattern.diagonal[row]..pattern.indptr[1 + row]).fold(T::zero(), |acc, index| {
+                acc + values[index] * x[pattern.indices[index]]
+            });
+        }
+        for row in (0..pattern.dimension()).rev() {
+            let sum = (pattern.indptr[row]..pattern.diagonal[row]).fold(T::zero(), |acc, index| {
+                acc + values[index] * y[pattern.indices[index]]
+            });
+            y[row] += sum;
+        }
+    }
+}
+
+/// Keep the `count` entries of largest magnitude, sorted by their column.
+///
+/// The entries must be finite, so that their magnitudes are totally ordered.
+fn keep_largest<T: Scalar>(entries: &mut Vec<(usize, T)>, count: usize) {
+    if entries.len() > count {
+        entries.sort_by(|a, b| b.1.abs().partial_cmp(&a.1.abs()).unwrap());
+        entries.truncate(count);
+    }
+    entries.sort_by_key(|&(col, _)| col);
+}
+
+fn zero_pivot(row: usize) -> SparseLinAlgError {
+    SparseLinAlgError::OperationFailed(format!(
+        "incomplete LU factorization with a zero pivot in row {row}"
+    ))
+}
+
+impl<T: Scalar> OperatorBase for Ilu<T> {
+    type Domain = LocalIndexableVectorSpace<T>;
+    type Range = LocalIndexableVectorSpace<T>;
+
+    fn domain(&self) -> &Self::Domain {
+        &self.space
+    }
+
+    fn range(&self) -> &Self::Range {
+        &self.space
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_inverse(
+        &self,
+    ) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<T: Scalar> AsApply for Ilu<T> {
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        let n = self.pattern.dimension();
+        let x = x.view().unwrap();
+        let mut y = y.view_mut().unwrap();
+        check_apply_dimensions((n, n), x.len(), y.len())?;
+        self.multiply(x.data(), y.data_mut());
+        Ok(())
+    }
+}
+
+impl<T: Scalar> AsApplyInverse for Ilu<T> {
+    fn apply_inverse(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let n = self.pattern.dimension();
+        let x = x.view().unwrap();
+        let mut y = y.view_mut().unwrap();
+        check_apply_dimensions((n, n), x.len(), y.len())?;
+        y.data_mut().copy_from_slice(x.data());
+        self.solve(y.data_mut())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::solvers::test_problems::{
+        convection_diffusion, convection_diffusion_2d, gmres_iterations,
+    };
+    use sparse_traits::{Element, LinearSpace};
+
+    /// Return `M e_col`.
+    fn column(ilu: &Ilu<f64>, col: usize) -> Vec<f64> {
+        let n = ilu.pattern().dimension();
+        let mut x = vec![0.0; n];
+        x[col] = 1.0;
+        let mut y = vec![0.0; n];
+        ilu.multiply(&x, &mut y);
+        y
+    }
+
+    #[test]
+    fn test_ilu0() {
+        // Without fill ILU(0) is the exact LU factorization.
+        let mat = convection_diffusion(20);
+        let ilu = Ilu::ilu0(&mat).unwrap();
+        assert_eq!(ilu.pattern().nnz(), mat.nnz());
+        assert!(gmres_iterations(&mat, Some(&ilu)) <= 1);
+
+        // The product L U agrees with A on the pattern of A.
+        let m = 6;
+        let mat = convection_diffusion_2d(m);
+        let ilu = Ilu::ilu0(&mat).unwrap();
+        assert_eq!(ilu.pattern().nnz(), mat.nnz());
+        for col in [0, 7, 20, 35] {
+            let lu = column(&ilu, col);
+            for (row, value) in lu.iter().enumerate() {
+                let index = (mat.indptr()[row]..mat.indptr()[1 + row])
+                    .find(|&index| mat.indices()[index] == col);
+                if let Some(index) = index {
+                    assert!((value - mat.data()[index]).abs() < 1E-14);
+                }
+            }
+        }
+
+        let mut y = vec![0.0; m * m];
+        let x: Vec<f64> = (0..m * m).map(|index| 1.0 + index as f64).collect();
+        ilu.multiply(&x, &mut y);
+        ilu.solve(&mut y).unwrap();
+        for (actual, expected) in y.iter().zip(x) {
+            assert!((actual - expected).abs() < 1E-12 * expected);
+        }
+
+        assert!(gmres_iterations(&mat, Some(&ilu)) < gmres_iterations(&mat, None));
+    }
+
+    #[test]
+    fn test_iluk() {
+        let m = 8;
+        let mat = convection_diffusion_2d(m);
+        let level0 = IluPattern::iluk(&mat, 0).unwrap();
+        assert_eq!(level0, IluPattern::ilu0(&mat).unwrap());
+
+        // The patterns and the quality of the preconditioner grow with the level.
+        let mut nnz = level0.nnz();
+        let mut iterations = gmres_iterations(&mat, Some(&Ilu::ilu0(&mat).unwrap()));
+        for level in 1..3 {
+            let ilu = Ilu::iluk(&mat, level).unwrap();
+            assert!(ilu.pattern().nnz() > nnz);
+            nnz = ilu.pattern().nnz();
+            let level_iterations = gmres_iterations(&mat, Some(&ilu));
+            assert!(level_iterations <= iterations);
+            iterations = level_iterations;
+        }
+        // The complete factorization stays within the band.
+        let complete = IluPattern::iluk(&mat, m * m).unwrap();
+        assert!(complete.nnz() > nnz);
+        assert!(complete.nnz() <= (2 * m + 1) * m * m);
+        let exact = Ilu::iluk(&mat, m * m).unwrap();
+        assert!(gmres_iterations(&mat, Some(&exact)) <= 1);
+
+        // ILU(1) adds the fill of the products of two original entries.
+        let pattern = IluPattern::iluk(&mat, 1).unwrap();
+        let row = m + 1;
+        let cols = &pattern.indices()[pattern.indptr()[row]..pattern.indptr()[1 + row]];
+        assert_eq!(cols, [1, 2, m, m + 1, m + 2, 2 * m, 2 * m + 1]);
+    }
+
+    #[test]
+    fn test_ilu_refactorize() {
+        let mat = convection_diffusion_2d(5);
+        let pattern = IluPattern::iluk(&mat, 1).unwrap();
+        let ilu = Ilu::factorize(pattern.clone(), &mat).unwrap();
+
+        // Scaling the matrix scales U and leaves L unchanged.
+        let scaled = CsrMatrix::new(
+            mat.shape(),
+            mat.indices().to_vec(),
+            mat.indptr().to_vec(),
+            mat.data().iter().map(|value| 2.0 * value).collect(),
+        );
+        let mut refactorized = Ilu::factorize(pattern.clone(), &mat).unwrap();
+        refactorized.refactorize(&scaled).unwrap();
+        assert_eq!(refactorized.pattern(), &pattern);
+        for row in 0..pattern.dimension() {
+            for index in pattern.indptr()[row]..pattern.indptr()[1 + row] {
+                let factor = if pattern.indices()[index] < row {
+                    1.0
+                } else {
+                    2.0
+                };
+                let expected = factor * ilu.values()[index];
+                assert!((refactorized.values()[index] - expected).abs() < 1E-14);
+            }
+        }
+
+        let other = CsrMatrix::from_aij((3, 3), &[0, 1, 2], &[0, 1, 2], &[1.0; 3]).unwrap();
+        assert!(matches!(
+            refactorized.refactorize(&other),
+            Err(SparseLinAlgError::SingleDimensionError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ilut() {
+        let m = 8;
+        let mat = convection_diffusion_2d(m);
+
+        // Without dropping ILUT is the exact LU factorization.
+        let exact = Ilu::ilut(&mat, 0.0, m * m).unwrap();
+        assert_eq!(exact.pattern(), &IluPattern::iluk(&mat, m * m).unwrap());
+        assert!(gmres_iterations(&mat, Some(&exact)) <= 1);
+
+        // A larger drop tolerance and a smaller fill limit give sparser factors.
+        let fine = Ilu::ilut(&mat, 1E-3, 10).unwrap();
+        let coarse = Ilu::ilut(&mat, 1E-1, 10).unwrap();
+        let limited = Ilu::ilut(&mat, 1E-3, 1).unwrap();
+        assert!(coarse.pattern().nnz() < fine.pattern().nnz());
+        assert!(limited.pattern().nnz() <= 3 * m * m);
+        for row in 0..m * m {
+            let start = limited.pattern().indptr()[row];
+            let end = limited.pattern().indptr()[1 + row];
+            assert!(end - start <= 3);
+        }
+
+        let plain = gmres_iterations(&mat, None);
+        let fine_iterations = gmres_iterations(&mat, Some(&fine));
+        assert!(fine_iterations < gmres_iterations(&mat, Some(&coarse)));
+        assert!(fine_iterations < plain);
+
+        // The pattern of ILUT can be reused.
+        let mut reused = Ilu::factorize(fine.pattern().clone(), &mat).unwrap();
+        reused.refactorize(&mat).unwrap();
+        assert!(gmres_iterations(&mat, Some(&reused)) < plain);
+    }
+
+    #[test]
+    fn test_ilu_errors() {
+        // [[0, 1], [1, 0]] has a zero pivot without pivoting.
+        let mat = CsrMatrix::from_aij((2, 2), &[0, 1], &[1, 0], &[1.0, 1.0]).unwrap();
+        assert!(matches!(
+            Ilu::ilu0(&mat),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+        assert!(matches!(
+            Ilu::ilut(&mat, 0.0, 2),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+
+        // A NaN entry in a row with more lower entries than the fill limit.
+        let mat = CsrMatrix::from_aij(
+            (3, 3),
+            &[0, 1, 2, 2, 2],
+            &[0, 1, 0, 1, 2],
+            &[4.0, 4.0, f64::NAN, 1.0, 4.0],
+        )
+        .unwrap();
+        assert!(matches!(
+            Ilu::ilut(&mat, 0.0, 1),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+
+        let rectangular = CsrMatrix::from_aij((2, 3), &[0, 1], &[0, 1], &[1.0, 1.0]).unwrap();
+        assert!(IluPattern::ilu0(&rectangular).is_err());
+        assert!(Ilu::ilut(&rectangular, 0.0, 2).is_err());
+
+        let ilu = Ilu::ilu0(&convection_diffusion(4)).unwrap();
+        let short = LocalIndexableVectorSpace::<f64>::new(3);
+        let x = short.create_element();
+        let mut y = short.create_element();
+        assert!(ilu.apply_inverse(x.view(), y.view_mut()).is_err());
+    }
+}
@@ -0,0 +1,698 @@
+//! Point and block Jacobi preconditioners.
+
+use std::fmt::Debug;
+
+use mpi::traits::{Communicator, Equivalence};
+use sparse_traits::types::{Scalar, SparseLinAlgError, SparseLinAlgResult};
+use sparse_traits::{
+    AsApply, AsApplyInverse, ElementView, ElementViewMut, IndexLayout, OperatorBase,
+};
+
+use super::{check_shape, diagonal, invert_diagonal, Ilu, LocalEntries};
+use crate::distributed::index_layout::DistributedIndexLayout;
+use crate::distributed::indexable_space::DistributedIndexableVectorSpace;
+use crate::local::indexable_space::LocalIndexableVectorSpace;
+use crate::local::sparse::check_apply_dimensions;
+use crate::local::sparse::csr_mat::CsrMatrix;
+
+/// The row-major dense block of the rows `first..last` and the columns
+/// `offset + first..offset + last`, summing duplicates.
+fn dense_block<T: Scalar>(mat: &CsrMatrix<T>, offset: usize, first: usize, last: usize) -> Vec<T> {
+    let size = last - first;
+    let mut block = vec![T::zero(); size * size];
+    for row in first..last {
+        for index in mat.indptr()[row]..mat.indptr()[1 + row] {
+            let col = mat.indices()[index];
+            if offset + first <= col && col < offset + last {
+                block[(row - first) * size + col - offset - first] += mat.data()[index];
+            }
+        }
+    }
+    block
+}
+
+/// The square block of the rows of `mat` and the columns `offset..offset + rows`, with
+/// the column indices shifted by `offset`.
+fn diagonal_block<T: Scalar>(mat: &CsrMatrix<T>, offset: usize) -> CsrMatrix<T> {
+    let n = mat.shape().0;
+    let mut indptr = vec![0];
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    for row in 0..n {
+        for index in mat.indptr()[row]..mat.indptr()[1 + row] {
+            let col = mat.indices()[index];
+            if (offset..offset + n).contains(&col) {
+                indices.push(col - offset);
+                data.push(mat.data()[index]);
+            }
+        }
+        indptr.push(indices.len());
+    }
+    CsrMatrix::new((n, n), indices, indptr, data)
+}
+
+/// The point Jacobi preconditioner `M = D`, where `D` is the diagonal of `A`.
+///
+/// Applying the inverse divides every entry by the diagonal entry of its row. In the
+/// distributed case every rank stores the diagonal entries of its local rows.
+pub struct Jacobi<Space: LocalEntries> {
+    space: Space,
+    diagonal: Vec<Space::F>,
+    inverse: Vec<Space::F>,
+}
+
+impl<Space: LocalEntries> Jacobi<Space> {
+    /// Create the preconditioner from the locally owned diagonal entries.
+    ///
+    /// Returns an error if an entry is zero.
+    pub fn from_diagonal(space: Space, diagonal: Vec<Space::F>) -> SparseLinAlgResult<Self> {
+        if diagonal.len() != space.number_of_local_entries() {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: space.number_of_local_entries(),
+                actual: diagonal.len(),
+            });
+        }
+        let inverse = invert_diagonal(&diagonal)?;
+        Ok(Self {
+            space,
+            diagonal,
+            inverse,
+        })
+    }
+
+    /// The locally owned diagonal entries.
+    pub fn diagonal(&self) -> &[Space::F] {
+        &self.diagonal
+    }
+
+    /// Compute `y_i = factors_i x_i` for the local entries.
+    fn scale(
+        &self,
+        factors: &[Space::F],
+        x: ElementView<Space>,
+        y: ElementViewMut<Space>,
+    ) -> SparseLinAlgResult<()> {
+        Space::with_local_entries(x, y, |x, y| {
+            check_apply_dimensions((factors.len(), factors.len()), x.len(), y.len())?;
+            for ((y, &x), &factor) in y.iter_mut().zip(x).zip(factors) {
+                *y = factor * x;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<T: Scalar> Jacobi<LocalIndexableVectorSpace<T>> {
+    /// Create the preconditioner for a square matrix.
+    pub fn new(mat: &CsrMatrix<T>) -> SparseLinAlgResult<Self> {
+        let n = mat.shape().0;
+        check_shape(mat, n, n)?;
+        Self::from_diagonal(LocalIndexableVectorSpace::new(n), diagonal(mat, 0))
+    }
+}
+
+impl<'comm, T: Scalar + Equivalence, C: Communicator>
+    Jacobi<DistributedIndexableVectorSpace<'comm, T, C>>
+where
+    T::Real: Equivalence,
+{
+    /// Create the preconditioner from the locally owned rows of a distributed matrix.
+    ///
+    /// `local_rows` holds the rows in the local range of `layout` with global column
+    /// indices.
+    pub fn distributed(
+        layout: &'comm DistributedIndexLayout<'comm, C>,
+        local_rows: &CsrMatrix<T>,
+    ) -> SparseLinAlgResult<Self> {
+        check_shape(
+            local_rows,
+            layout.number_of_local_indices(),
+            layout.number_of_global_indices(),
+        )?;
+        let first = layout.local_range().0;
+        Self::from_diagonal(
+            DistributedIndexableVectorSpace::new(layout),
+            diagonal(local_rows, first),
+        )
+    }
+}
+
+impl<Space: LocalEntries> Debug for Jacobi<Space> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Jacobi")
+            .field("diagonal", &self.diagonal)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Space: LocalEntries> OperatorBase for Jacobi<Space> {
+    type Domain = Space;
+    type Range = Space;
+
+    fn domain(&self) -> &Self::Domain {
+        &self.space
+    }
+
+    fn range(&self) -> &Self::Range {
+        &self.space
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_inverse(
+        &self,
+    ) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<Space: LocalEntries> AsApply for Jacobi<Space> {
+    fn apply(&self, x: ElementView<Space>, y: ElementViewMut<Space>) -> SparseLinAlgResult<()> {
+        self.scale(&self.diagonal, x, y)
+    }
+}
+
+impl<Space: LocalEntries> AsApplyInverse for Jacobi<Space> {
+    fn apply_inverse(
+        &self,
+        x: ElementView<Space>,
+        y: ElementViewMut<Space>,
+    ) -> SparseLinAlgResult<()> {
+        self.scale(&self.inverse, x, y)
+    }
+}
+
+/// A dense LU factorization `P A = L U` with partial pivoting.
+///
+/// `L` and `U` are stored row-major in place of `A`, with the unit diagonal of `L`
+/// implied. `pivots[k]` is the row that was swapped with row `k` in step `k`.
+struct DenseLu<T: Scalar> {
+    size: usize,
+    lu: Vec<T>,
+    pivots: Vec<usize>,
+}
+
+impl<T: Scalar> DenseLu<T> {
+    /// Factorize the row-major matrix `lu`, or return `None` if it is singular.
+    fn new(size: usize, mut lu: Vec<T>) -> Option<Self> {
+        let mut pivots = Vec::with_capacity(size);
+        for step in 0..size {
+            let mut pivot = step;
+            for row in 1 + step..size {
+                if lu[row * size + step].abs() > lu[pivot * size + step].abs() {
+                    pivot = row;
+                }
+            }
+            if lu[pivot * size + step].is_zero() {
+                return None;
+            }
+            pivots.push(pivot);
+            for col in 0..size {
+                lu.swap(step * size + col, pivot * size + col);
+            }
+
+            let diag = lu[step * size + step];
+            for row in 1 + step..size {
+                let factor = lu[row * size + step] / diag;
+                lu[row * size + step] = factor;
+                for col in 1 + step..size {
+                    let value = lu[step * size + col];
+                    lu[row * size + col] -= factor * value;
+                }
+            }
+        }
+        Some(Self { size, lu, pivots })
+    }
+
+    /// Overwrite `x` with `A^{-1} x`.
+    fn solve(&self, x: &mut [T]) {
+        let size = self.size;
+        for (step, &pivot) in self.pivots.iter().enumerate() {
+            x.swap(step, pivot);
+        }
+        for row in 0..size {
+            let sum = (0..row).fold(T::zero(), |acc, col| {
+                acc + self.lu[row * size + col] * x[col]
+            });
+            x[row] -= sum;
+        }
+        for row in (0..size).rev() {
+            let sum = (1 + row..size).fold(T::zero(), |acc, col| {
+                acc + self.lu[row * size + col] * x[col]
+            });
+            x[row] = (x[row] - sum) / self.lu[row * size + row];
+        }
+    }
+
+    /// Overwrite `x` with `A x = P^T L U x`.
+    fn multiply(&self, x: &mut [T]) {
+        let size = self.size;
+        for row in 0..size {
+            x[row] = (row..size).fold(T::zero(), |acc, col| {
+                acc + self.lu[row * size + col] * x[col]
+            });
+        }
+        for row in (0..size).rev() {
+            let sum = (0..row).fold(T::zero(), |acc, col| {
+                acc + self.lu[row * size + col] * x[col]
+            });
+            x[row] += sum;
+        }
+        for (step, &pivot) in self.pivots.iter().enumerate().rev() {
+            x.swap(step, pivot);
+        }
+    }
+}
+
+/// The factorization of a diagonal block of [BlockJacobi].
+enum BlockFactor<T: Scalar> {
+    /// An exact dense factorization of a small block.
+    Dense(DenseLu<T>),
+    /// An incomplete sparse factorization of a large block.
+    Incomplete(Ilu<T>),
+}
+
+impl<T: Scalar> BlockFactor<T> {
+    /// Overwrite `x` with the inverse of the factorization applied to `x`.
+    fn solve(&self, x: &mut [T]) -> SparseLinAlgResult<()> {
+        match self {
+            Self::Dense(lu) => {
+                lu.solve(x);
+                Ok(())
+            }
+            Self::Incomplete(ilu) => ilu.solve(x),
+        }
+    }
+
+    /// Overwrite `x` with the factorization applied to `x`.
+    fn multiply(&self, x: &mut [T]) {
+        match self {
+            Self::Dense(lu) => lu.multiply(x),
+            Self::Incomplete(ilu) => {
+                let copy = x.to_vec();
+                ilu.multiply(&copy, x);
+            }
+        }
+    }
+}
+
+/// The block Jacobi preconditioner `M = diag(A_00, A_11, ...)`.
+///
+/// On a single node the diagonal blocks are factorized with a dense LU decomposition
+/// with partial pivoting, so they should be small, for example the unknowns of a node
+/// or a cell. In the distributed case every rank has a single block of its local rows.
+/// That block is large, so it is factorized with [ILU(0)](Ilu::ilu0) instead and `M`
+/// only approximates the block diagonal of `A`.
+pub struct BlockJacobi<Space: LocalEntries> {
+    space: Space,
+    offsets: Vec<usize>,
+    blocks: Vec<BlockFactor<Space::F>>,
+}
+
+impl<Space: LocalEntries> BlockJacobi<Space> {
+    /// Factorize the diagonal blocks with the given sizes of the rows of `mat`. The
+    /// columns of the local rows start at `offset`.
+    fn factorize(
+        space: Space,
+        mat: &CsrMatrix<Space::F>,
+        offset: usize,
+        sizes: &[usize],
+    ) -> SparseLinAlgResult<Self> {
+        let total = sizes.iter().sum();
+        if total != space.number_of_local_entries() {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: space.number_of_local_entries(),
+                actual: total,
+            });
+        }
+
+        let mut offsets = vec![0];
+        let mut blocks = Vec::with_capacity(sizes.len());
+        for (index, &size) in sizes.iter().enumerate() {
+            let first = offsets[index];
+            let block = dense_block(mat, offset, first, first + size);
+            let lu = DenseLu::new(size, block).ok_or_else(|| {
+                SparseLinAlgError::OperationFailed(format!(
+                    "LU factorization of diagonal block {index}"
+                ))
+            })?;
+            blocks.push(BlockFactor::Dense(lu));
+            offsets.push(first + size);
+        }
+        Ok(Self {
+            space,
+            offsets,
+            blocks,
+        })
+    }
+
+    /// The number of diagonal blocks.
+    pub fn number_of_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// The local rows of the block with the given index.
+    pub fn block_range(&self, index: usize) -> (usize, usize) {
+        (self.offsets[index], self.offsets[1 + index])
+    }
+
+    /// Apply `op` to the local entries of every block of `y = x`.
+    fn for_each_block(
+        &self,
+        x: ElementView<Space>,
+        y: ElementViewMut<Space>,
+        op: impl Fn(&BlockFactor<Space::F>, &mut [Space::F]) -> SparseLinAlgResult<()>,
+    ) -> SparseLinAlgResult<()> {
+        let n = self.offsets[self.blocks.len()];
+        Space::with_local_entries(x, y, |x, y| {
+            check_apply_dimensions((n, n), x.len(), y.len())?;
+            y.copy_from_slice(x);
+            for (block, range) in self.blocks.iter().zip(self.offsets.windows(2)) {
+                op(block, &mut y[range[0]..range[1]])?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<T: Scalar> BlockJacobi<LocalIndexableVectorSpace<T>> {
+    /// Create the preconditioner for a square matrix with blocks of `block_size` rows.
+    ///
+    /// The last block is smaller if `block_size` does not divide the dimension.
+    pub fn new(mat: &CsrMatrix<T>, block_size: usize) -> SparseLinAlgResult<Self> {
+        if block_size == 0 {
+            return Err(SparseLinAlgError::OperationFailed(
+                "block Jacobi with block size zero".to_string(),
+            ));
+        }
+        let n = mat.shape().0;
+        let sizes: Vec<usize> = (0..n)
+            .step_by(block_size)
+            .map(|first| block_size.min(n - first))
+            .collect();
+        Self::with_block_sizes(mat, &sizes)
+    }
+
+    /// Create the preconditioner for a square matrix with blocks of the given sizes.
+    ///
+    /// The sizes must add up to the dimension of the matrix.
+    pub fn with_block_sizes(mat: &CsrMatrix<T>, sizes: &[usize]) -> SparseLinAlgResult<Self> {
+        let n = mat.shape().0;
+        check_shape(mat, n, n)?;
+        Self::factorize(LocalIndexableVectorSpace::new(n), mat, 0, sizes)
+    }
+}
+
+impl<'comm, T: Scalar + Equivalence, C: Communicator>
+    BlockJacobi<DistributedIndexableVectorSpace<'comm, T, C>>
+where
+    T::Real: Equivalence,
+{
+    /// Create the preconditioner from the locally owned rows of a distributed matrix,
+    /// with one block per rank that is factorized with ILU(0).
+    ///
+    /// `local_rows` holds the rows in the local range of `layout` with global column
+    /// indices. The block of a rank consists of the columns in its local range.
+    pub fn distributed(
+        layout: &'comm DistributedIndexLayout<'comm, C>,
+        local_rows: &CsrMatrix<T>,
+    ) -> SparseLinAlgResult<Self> {
+        let n = layout.number_of_local_indices();
+        check_shape(local_rows, n, layout.number_of_global_indices())?;
+        let block = diagonal_block(local_rows, layout.local_range().0);
+        Ok(Self {
+            space: DistributedIndexableVectorSpace::new(layout),
+            offsets: vec![0, n],
+            blocks: vec![BlockFactor::Incomplete(Ilu::ilu0(&block)?)],
+        })
+    }
+}
+
+impl<Space: LocalEntries> Debug for BlockJacobi<Space> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockJacobi")
+            .field("offsets", &self.offsets)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Space: LocalEntries> OperatorBase for BlockJacobi<Space> {
+    type Domain = Space;
+    type Range = Space;
+
+    fn domain(&self) -> &Self::Domain {
+        &self.space
+    }
+
+    fn range(&self) -> &Self::Range {
+        &self.space
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_inverse(
+        &self,
+    ) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<Space: LocalEntries> AsApply for BlockJacobi<Space> {
+    fn apply(&self, x: ElementView<Space>, y: ElementViewMut<Space>) -> SparseLinAlgResult<()> {
+        self.for_each_block(x, y, |block, y| {
+            block.multiply(y);
+            Ok(())
+        })
+    }
+}
+
+impl<Space: LocalEntries> AsApplyInverse for BlockJacobi<Space> {
+    fn apply_inverse(
+        &self,
+        x: ElementView<Space>,
+        y: ElementViewMut<Space>,
+    ) -> SparseLinAlgResult<()> {
+        self.for_each_block(x, y, |block, y| block.solve(y))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::solvers::cg::cg;
+    use crate::solvers::gmres::{gmres, GmresOptions};
+    use crate::solvers::test_problems::{convection_diffusion, laplace_1d, tridiagonal};
+    use crate::solvers::SolverOptions;
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::{Element, LinearSpace};
+
+    /// Check that `M^{-1} M x = x` and return `M x` for `x_i = 1 + i`.
+    fn check_inverse<Op>(op: &Op) -> Vec<f64>
+    where
+        Op: OperatorBase<
+            Domain = LocalIndexableVectorSpace<f64>,
+            Range = LocalIndexableVectorSpace<f64>,
+        >,
+    {
+        let space = op.domain();
+        let mut x = space.create_element();
+        for (index, value) in x.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = 1.0 + index as f64;
+        }
+        let mut mx = space.create_element();
+        let mut y = space.create_element();
+        op.as_apply()
+            .unwrap()
+            .apply(x.view(), mx.view_mut())
+            .unwrap();
+        op.as_apply_inverse()
+            .unwrap()
+            .apply_inverse(mx.view(), y.view_mut())
+            .unwrap();
+        for (actual, expected) in y
+            .view()
+            .view()
+            .unwrap()
+            .iter()
+            .zip(x.view().view().unwrap().iter())
+        {
+            assert!((actual - expected).abs() < 1E-12 * expected);
+        }
+        mx.view().view().unwrap().data().to_vec()
+    }
+
+    /// A Laplacian with a strongly varying diagonal shift.
+    fn shifted_laplace(n: usize) -> CsrMatrix<f64> {
+        let diagonal: Vec<f64> = (0..n)
+            .map(|index| {
+                let value = ((17 * index * index) % 101) as f64;
+                2.0 + 1E-2 * value * value
+            })
+            .collect();
+        tridiagonal(&diagonal)
+    }
+
+    #[test]
+    fn test_jacobi() {
+        let mat = convection_diffusion(10);
+        let jacobi = Jacobi::new(&mat).unwrap();
+        assert_eq!(jacobi.diagonal()[3], 5.0);
+        let mx = check_inverse(&jacobi);
+        for (index, value) in mx.iter().enumerate() {
+            assert_eq!(*value, (2.0 + (index % 7) as f64) * (1.0 + index as f64));
+        }
+
+        let n = 100;
+        let mat = shifted_laplace(n);
+        let jacobi = Jacobi::new(&mat).unwrap();
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-10, 0.0, 1000);
+
+        let mut x = space.create_element();
+        let plain = cg(&space, &mat, None, &b, &mut x, &options).unwrap();
+        let mut x = space.create_element();
+        let preconditioned = cg(
+            &space,
+            &mat,
+            Some(&jacobi as &dyn OperatorBase<Domain = _, Range = _>),
+            &b,
+            &mut x,
+            &options,
+        )
+        .unwrap();
+        assert!(preconditioned.converged());
+        assert!(preconditioned.iterations < plain.iterations);
+
+        let mut res = vec![0.0; n];
+        mat.matmul(1.0, x.view().view().unwrap().data(), 0.0, &mut res);
+        assert!(res.iter().all(|value| (value - 1.0).abs() < 1E-8));
+    }
+
+    #[test]
+    fn test_block_jacobi() {
+        let n = 10;
+        let mat = convection_diffusion(n);
+        let block_jacobi = BlockJacobi::new(&mat, 3).unwrap();
+        assert_eq!(block_jacobi.number_of_blocks(), 4);
+        assert_eq!(block_jacobi.block_range(3), (9, 10));
+
+        // M x is A x without the couplings between the blocks.
+        let mx = check_inverse(&block_jacobi);
+        let x: Vec<f64> = (0..n).map(|index| 1.0 + index as f64).collect();
+        let mut expected = vec![0.0; n];
+        mat.matmul(1.0, &x, 0.0, &mut expected);
+        for row in [2, 5, 8] {
+            expected[row] += 0.5 * x[row + 1];
+            expected[row + 1] += 1.5 * x[row];
+        }
+        for (actual, expected) in mx.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1E-12 * expected.abs());
+        }
+
+        let block_jacobi = BlockJacobi::with_block_sizes(&mat, &[1, 4, 5]).unwrap();
+        assert_eq!(block_jacobi.block_range(1), (1, 5));
+        check_inverse(&block_jacobi);
+
+        // With a single block the preconditioner is exact.
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let exact = BlockJacobi::new(&mat, n).unwrap();
+        let mut x = space.create_element();
+        let options = SolverOptions::new(1E-10, 0.0, 100);
+        let result = gmres(
+            &space,
+            &mat,
+            Some(&exact as &dyn OperatorBase<Domain = _, Range = _>),
+            &b,
+            &mut x,
+            &options,
+            &GmresOptions::default(),
+        )
+        .unwrap();
+        assert!(result.converged());
+        assert!(result.iterations <= 1);
+
+        let n = 100;
+        let mat = shifted_laplace(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-10, 0.0, 1000);
+        let jacobi = Jacobi::new(&mat).unwrap();
+        let block_jacobi = BlockJacobi::new(&mat, 4).unwrap();
+
+        let mut x = space.create_element();
+        let point = cg(
+            &space,
+            &mat,
+            Some(&jacobi as &dyn OperatorBase<Domain = _, Range = _>),
+            &b,
+            &mut x,
+            &options,
+        )
+        .unwrap();
+        let mut x = space.create_element();
+        let block = cg(
+            &space,
+            &mat,
+            Some(&block_jacobi as &dyn OperatorBase<Domain = _, Range = _>),
+            &b,
+            &mut x,
+            &options,
+        )
+        .unwrap();
+        assert!(block.converged());
+        assert!(block.iterations < point.iterations);
+    }
+
+    #[test]
+    fn test_jacobi_errors() {
+        let zero_diagonal = CsrMatrix::from_aij((2, 2), &[0, 1], &[1, 1], &[1.0, 1.0]).unwrap();
+        assert!(matches!(
+            Jacobi::new(&zero_diagonal),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+        assert!(matches!(
+            BlockJacobi::new(&zero_diagonal, 1),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+        // The block [[0, 1], [0, 1]] is singular, but pivoting handles the zero diagonal
+        // of [[0, 1], [1, 0]].
+        assert!(BlockJacobi::new(&zero_diagonal, 2).is_err());
+        let swap = CsrMatrix::from_aij((2, 2), &[0, 1], &[1, 0], &[1.0, 1.0]).unwrap();
+        check_inverse(&BlockJacobi::new(&swap, 2).unwrap());
+
+        let rectangular = CsrMatrix::from_aij((2, 3), &[0, 1], &[0, 1], &[1.0, 1.0]).unwrap();
+        assert!(matches!(
+            Jacobi::new(&rectangular),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 2,
+                actual: 3
+            })
+        ));
+
+        let mat = laplace_1d(5);
+        assert!(BlockJacobi::new(&mat, 0).is_err());
+        assert!(matches!(
+            BlockJacobi::with_block_sizes(&mat, &[2, 2]),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 5,
+                actual: 4
+            })
+        ));
+
+        let jacobi = Jacobi::new(&mat).unwrap();
+        let short = LocalIndexableVectorSpace::<f64>::new(4);
+        let x = short.create_element();
+        let mut y = short.create_element();
+        assert!(jacobi.apply_inverse(x.view(), y.view_mut()).is_err());
+    }
+}
@@ -0,0 +1,420 @@
+//! Gauss-Seidel, SOR and SSOR relaxation.
+
+use std::fmt::Debug;
+
+use num::One;
+
+use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut, Norm2};
+use sparse_traits::types::{Scalar, SparseLinAlgResult};
+use sparse_traits::{AsApplyInverse, ElementView, ElementViewMut, OperatorBase};
+
+use super::{check_shape, diagonal, invert_diagonal};
+use crate::local::indexable_space::LocalIndexableVectorSpace;
+use crate::local::indexable_vector::LocalIndexableVector;
+use crate::local::sparse::check_apply_dimensions;
+use crate::local::sparse::csr_mat::CsrMatrix;
+use crate::solvers::{ConvergenceMonitor, ConvergenceReason, SolverOptions, SolverResult};
+
+/// The order in which a relaxation sweep updates the rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepDirection {
+    /// Update the rows from first to last.
+    Forward,
+    /// Update the rows from last to first.
+    Backward,
+    /// A forward sweep followed by a backward sweep.
+    Symmetric,
+}
+
+/// Gauss-Seidel and SOR relaxation with the rows of a square CSR matrix.
+///
+/// A sweep updates the rows in turn with
+/// `x_i <- x_i + omega (b_i - sum_j a_ij x_j) / a_ii`, using the entries of `x` that
+/// were already updated in the same sweep. `omega = 1` gives Gauss-Seidel, and a
+/// symmetric sweep gives symmetric Gauss-Seidel or SSOR. SOR converges for Hermitian
+/// positive definite matrices if `0 < omega < 2`.
+///
+/// The relaxation can run as an iterative method on its own with [Relaxation::solve], as
+/// a smoother with [Relaxation::relax], or as a preconditioner. The sweeps define an
+/// approximation `M` of `A`, such as `D / omega + L` for a forward sweep, that is only
+/// known through its inverse. The operator therefore supports
+/// [as_apply_inverse](OperatorBase::as_apply_inverse), which applies the sweeps to a zero
+/// initial guess, but not [as_apply](OperatorBase::as_apply). With a symmetric sweep
+/// `M^{-1}` is a Hermitian positive definite preconditioner for CG.
+pub struct Relaxation<'a, T: Scalar> {
+    mat: &'a CsrMatrix<T>,
+    inverse_diagonal: Vec<T>,
+    direction: SweepDirection,
+    omega: T::Real,
+    sweeps: usize,
+}
+
+impl<'a, T: Scalar> Relaxation<'a, T> {
+    /// Gauss-Seidel relaxation with a single sweep in the given direction.
+    pub fn gauss_seidel(
+        mat: &'a CsrMatrix<T>,
+        direction: SweepDirection,
+    ) -> SparseLinAlgResult<Self> {
+        Self::sor(mat, direction, T::Real::one())
+    }
+
+    /// SOR relaxation, or SSOR for a symmetric sweep, with a single sweep in the given
+    /// direction.
+    ///
+    /// Returns an error if the matrix is not square or has a zero diagonal entry.
+    pub fn sor(
+        mat: &'a CsrMatrix<T>,
+        direction: SweepDirection,
+        omega: T::Real,
+    ) -> SparseLinAlgResult<Self> {
+        let n = mat.shape().0;
+        check_shape(mat, n, n)?;
+        Ok(Self {
+            mat,
+            inverse_diagonal: invert_diagonal(&diagonal(mat, 0))?,
+            direction,
+            omega,
+            sweeps: 1,
+        })
+    }
+
+    /// Perform `sweeps` sweeps in every call of [Relaxation::relax] and in every
+    /// application of the inverse.
+    pub fn with_sweeps(mut self, sweeps: usize) -> Self {
+        self.sweeps = sweeps;
+        self
+    }
+
+    /// The direction of the sweeps.
+    pub fn direction(&self) -> SweepDirection {
+        self.direction
+    }
+
+    /// The relaxation parameter.
+    pub fn omega(&self) -> T::Real {
+        self.omega
+    }
+
+    /// The number of sweeps per call.
+    pub fn sweeps(&self) -> usize {
+        self.sweeps
+    }
+
+    /// Improve the approximate solution `x` of `A x = b` in place by the configured sweeps.
+    ///
+    /// If `zero_guess` is true the entries of `x` are ignored and the initial guess is
+    /// zero. The first sweep then skips the entries of the rows that are not yet updated,
+    /// which halves its cost.
+    pub fn relax(
+        &self,
+        b: &LocalIndexableVector<T>,
+        x: &mut LocalIndexableVector<T>,
+        zero_guess: bool,
+    ) -> SparseLinAlgResult<()> {
+        let n = self.inverse_diagonal.len();
+        let b = b.view().unwrap();
+        let mut x = x.view_mut().unwrap();
+        check_apply_dimensions((n, n), b.len(), x.len())?;
+        let (b, x) = (b.data(), x.data_mut());
+
+        if zero_guess && self.sweeps == 0 {
+            x.fill(T::zero());
+        }
+        let mut zero_guess = zero_guess;
+        for _ in 0..self.sweeps {
+            match self.direction {
+                SweepDirection::Forward => self.sweep(b, x, true, zero_guess),
+                SweepDirection::Backward => self.sweep(b, x, false, zero_guess),
+                SweepDirection::Symmetric => {
+                    self.sweep(b, x, true, zero_guess);
+                    self.sweep(b, x, false, false);
+                }
+            }
+            zero_guess = false;
+        }
+        Ok(())
+    }
+
+    /// Solve `A x = b` by repeated relaxation, with `x` as the initial guess.
+    ///
+    /// Every iteration performs the configured sweeps and then checks the residual
+    /// against the tolerances of `options`.
+    pub fn solve(
+        &self,
+        b: &LocalIndexableVector<T>,
+        x: &mut LocalIndexableVector<T>,
+        options: &SolverOptions<T::Real>,
+    ) -> SparseLinAlgResult<SolverResult<T::Real>> {
+        let n = self.inverse_diagonal.len();
+        let mut r = LocalIndexableVector::new(n);
+        let mut monitor = ConvergenceMonitor::new(options, b.norm_2());
+
+        for iteration in 0..=options.max_iter {
+            if iteration > 0 {
+                self.relax(b, x, false)?;
+            }
+            {
+                let x = x.view().unwrap();
+                let mut r = r.view_mut().unwrap();
+                check_apply_dimensions((n, n), x.len(), r.len())?;
+                r.data_mut().copy_from_slice(b.view().unwrap().data());
+                self.mat.matmul(-T::one(), x.data(), T::one(), r.data_mut());
+            }
+            if let Some(reason) = monitor.check(r.norm_2()) {
+                return Ok(monitor.finish(reason, iteration));
+            }
+        }
+        Ok(monitor.finish(ConvergenceReason::MaxIterations, options.max_iter))
+    }
+
+    /// Update all rows in the given direction. With `zero_guess` the rows that are not yet
+    /// updated are zero, so only the entries of the updated rows are used.
+    fn sweep(&self, b: &[T], x: &mut [T], forward: bool, zero_guess: bool) {
+        let indptr = self.mat.indptr();
+        let indices = self.mat.indices();
+        let data = self.mat.data();
+        let omega = T::from_real(self.omega);
+        let n = self.inverse_diagonal.len();
+
+        for step in 0..n {
+            let row = if forward { step } else { n - 1 - step };
+            let mut sum = b[row];
+            for index in indptr[row]..indptr[1 + row] {
+                let col = indices[index];
+                if !zero_guess || (forward && col < row) || (!forward && col > row) {
+                    sum -= data[index] * x[col];
+                }
+            }
+            let correction = omega * sum * self.inverse_diagonal[row];
+            x[row] = if zero_guess {
+                correction
+            } else {
+                x[row] + correction
+            };
+        }
+    }
+}
+
+impl<'a, T: Scalar> Debug for Relaxation<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Relaxation")
+            .field("direction", &self.direction)
+            .field("omega", &self.omega)
+            .field("sweeps", &self.sweeps)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T: Scalar> OperatorBase for Relaxation<'a, T> {
+    type Domain = LocalIndexableVectorSpace<T>;
+    type Range = LocalIndexableVectorSpace<T>;
+
+    fn domain(&self) -> &Self::Domain {
+        self.mat.domain()
+    }
+
+    fn range(&self) -> &Self::Range {
+        self.mat.range()
+    }
+
+    fn as_apply_inverse(
+        &self,
+    ) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<'a, T: Scalar> AsApplyInverse for Relaxation<'a, T> {
+    fn apply_inverse(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        self.relax(x, y, true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::solvers::cg::cg;
+    use crate::solvers::test_problems::{
+        convection_diffusion, laplace_1d, max_residual, tridiagonal,
+    };
+    use sparse_traits::types::SparseLinAlgError;
+    use sparse_traits::{Element, LinearSpace};
+
+    fn vector(data: &[f64]) -> LocalIndexableVector<f64> {
+        let mut x = LocalIndexableVector::new(data.len());
+        x.view_mut().unwrap().data_mut().copy_from_slice(data);
+        x
+    }
+
+    fn assert_close(x: &LocalIndexableVector<f64>, expected: &[f64]) {
+        for (actual, expected) in x.view().unwrap().iter().zip(expected) {
+            assert!((actual - expected).abs() < 1E-14, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn test_sweeps() {
+        // [[2, -1, 0], [-1, 2, -1], [0, -1, 2]] x = [1, 1, 1]
+        let mat = laplace_1d(3);
+        let b = vector(&[1.0, 1.0, 1.0]);
+
+        let forward = Relaxation::gauss_seidel(&mat, SweepDirection::Forward).unwrap();
+        let mut x = vector(&[7.0, 7.0, 7.0]);
+        forward.relax(&b, &mut x, true).unwrap();
+        assert_close(&x, &[0.5, 0.75, 0.875]);
+
+        // The fast path gives the same result as an explicit zero initial guess.
+        let mut zero = vector(&[0.0; 3]);
+        forward.relax(&b, &mut zero, false).unwrap();
+        assert_close(&zero, &[0.5, 0.75, 0.875]);
+
+        forward.relax(&b, &mut x, false).unwrap();
+        assert_close(&x, &[0.875, 1.375, 1.1875]);
+
+        let backward = Relaxation::gauss_seidel(&mat, SweepDirection::Backward).unwrap();
+        let mut x = vector(&[7.0, 7.0, 7.0]);
+        backward.relax(&b, &mut x, true).unwrap();
+        assert_close(&x, &[0.875, 0.75, 0.5]);
+
+        // A symmetric sweep equals a forward sweep followed by a backward sweep, and
+        // two sweeps per call equal two calls.
+        let symmetric = Relaxation::gauss_seidel(&mat, SweepDirection::Symmetric)
+            .unwrap()
+            .with_sweeps(2);
+        assert_eq!(symmetric.sweeps(), 2);
+        let mut x = vector(&[7.0, 7.0, 7.0]);
+        symmetric.relax(&b, &mut x, true).unwrap();
+        let mut expected = vector(&[0.0; 3]);
+        for _ in 0..2 {
+            forward.relax(&b, &mut expected, false).unwrap();
+            backward.relax(&b, &mut expected, false).unwrap();
+        }
+        assert_close(&x, expected.view().unwrap().data());
+
+        // SOR scales every correction.
+        let sor = Relaxation::sor(&mat, SweepDirection::Forward, 1.5).unwrap();
+        assert_eq!(sor.omega(), 1.5);
+        let mut x = vector(&[0.0; 3]);
+        sor.relax(&b, &mut x, true).unwrap();
+        assert_close(&x, &[0.75, 1.3125, 1.734375]);
+        sor.relax(&b, &mut x, false).unwrap();
+        assert_close(&x, &[1.359375, 2.4140625, 1.693359375]);
+    }
+
+    #[test]
+    fn test_relaxation_solve() {
+        let n = 30;
+        let mat = convection_diffusion(n);
+        let mut b = LocalIndexableVector::new(n);
+        b.view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-10, 0.0, 1000);
+
+        for direction in [
+            SweepDirection::Forward,
+            SweepDirection::Backward,
+            SweepDirection::Symmetric,
+        ] {
+            let relaxation = Relaxation::gauss_seidel(&mat, direction).unwrap();
+            let mut x = LocalIndexableVector::new(n);
+            let result = relaxation.solve(&b, &mut x, &options).unwrap();
+            assert!(result.converged());
+            assert_eq!(result.residual_history.len(), 1 + result.iterations);
+            let x = x.view().unwrap();
+            assert!(max_residual(&mat, x.data(), b.view().unwrap().data()) < 1E-8);
+        }
+
+        // SOR with the optimal parameter for the Laplacian is much faster than Gauss-Seidel.
+        let mat = laplace_1d(n);
+        let omega = 2.0 / (1.0 + (std::f64::consts::PI / (n + 1) as f64).sin());
+        let gauss_seidel = Relaxation::gauss_seidel(&mat, SweepDirection::Forward).unwrap();
+        let sor = Relaxation::sor(&mat, SweepDirection::Forward, omega).unwrap();
+        let options = SolverOptions::new(1E-8, 0.0, 10000);
+        let mut x = LocalIndexableVector::new(n);
+        let slow = gauss_seidel.solve(&b, &mut x, &options).unwrap();
+        let mut x = LocalIndexableVector::new(n);
+        let fast = sor.solve(&b, &mut x, &options).unwrap();
+        assert!(slow.converged());
+        assert!(fast.converged());
+        assert!(2 * fast.iterations < slow.iterations);
+
+        let options = SolverOptions::new(1E-8, 0.0, 3);
+        let mut x = LocalIndexableVector::new(n);
+        let result = gauss_seidel.solve(&b, &mut x, &options).unwrap();
+        assert_eq!(result.reason, ConvergenceReason::MaxIterations);
+        assert_eq!(result.iterations, 3);
+    }
+
+    #[test]
+    fn test_ssor_preconditioner() {
+        let n = 100;
+        let mat = laplace_1d(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-10, 0.0, 1000);
+
+        let ssor = Relaxation::sor(&mat, SweepDirection::Symmetric, 1.5).unwrap();
+        assert!(ssor.has_apply_inverse() && !ssor.has_apply());
+        let prec = &ssor as &dyn OperatorBase<Domain = _, Range = _>;
+        let mut x = space.create_element();
+        let plain = cg(&space, &mat, None, &b, &mut x, &options).unwrap();
+        let mut x = space.create_element();
+        let preconditioned = cg(&space, &mat, Some(prec), &b, &mut x, &options).unwrap();
+        assert!(preconditioned.converged());
+        assert!(preconditioned.iterations < plain.iterations);
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(&mat, x.data(), b.data()) < 1E-7);
+
+        // For a symmetric matrix SSOR gives a symmetric M^{-1}, which CG needs, while a
+        // forward sweep does not.
+        let mat = tridiagonal(&[3.0, 2.5, 4.0, 2.0, 3.5, 5.0]);
+        let inverse = |relaxation: &Relaxation<f64>| -> Vec<Vec<f64>> {
+            (0..6)
+                .map(|col| {
+                    let mut unit = vector(&[0.0; 6]);
+                    unit.view_mut().unwrap().data_mut()[col] = 1.0;
+                    let mut x = vector(&[0.0; 6]);
+                    relaxation.relax(&unit, &mut x, true).unwrap();
+                    x.view().unwrap().data().to_vec()
+                })
+                .collect()
+        };
+        let ssor = inverse(&Relaxation::sor(&mat, SweepDirection::Symmetric, 1.3).unwrap());
+        let sor = inverse(&Relaxation::sor(&mat, SweepDirection::Forward, 1.3).unwrap());
+        for (row, values) in ssor.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                assert!((value - ssor[col][row]).abs() < 1E-14);
+            }
+        }
+        assert!((sor[1][0] - sor[0][1]).abs() > 0.1);
+    }
+
+    #[test]
+    fn test_relaxation_errors() {
+        let zero_diagonal = CsrMatrix::from_aij((2, 2), &[0, 1], &[1, 1], &[1.0, 1.0]).unwrap();
+        assert!(matches!(
+            Relaxation::gauss_seidel(&zero_diagonal, SweepDirection::Forward),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+        let rectangular = CsrMatrix::from_aij((2, 3), &[0, 1], &[0, 1], &[1.0, 1.0]).unwrap();
+        assert!(Relaxation::gauss_seidel(&rectangular, SweepDirection::Forward).is_err());
+
+        let mat = laplace_1d(3);
+        let relaxation = Relaxation::gauss_seidel(&mat, SweepDirection::Forward).unwrap();
+        let b = vector(&[1.0, 1.0]);
+        let mut x = vector(&[0.0; 3]);
+        assert!(matches!(
+            relaxation.relax(&b, &mut x, true),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+}
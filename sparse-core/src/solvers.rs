@@ -0,0 +1,565 @@
+//! Iterative solvers.
+//!
+//! The solvers are written against the space and operator traits of `sparse_traits`.
+//! They only create elements through a [LinearSpace], combine them with the
+//! in-place operations of the space and measure them with [InnerProductSpace::inner].
+//! The same code therefore runs on local and on distributed vectors.
+//!
+//! A preconditioner is any square operator. If it supports
+//! [as_apply_inverse](OperatorBase::as_apply_inverse) it is taken to approximate `A`, and
+//! the solvers apply its inverse, as for the factorizations in
+//! [preconditioners](crate::preconditioners). Otherwise it is applied directly and must
+//! itself approximate `A^{-1}`.
+
+pub mod bicgstab;
+pub mod cg;
+pub mod gmres;
+pub mod idrs;
+pub mod lsmr;
+pub mod lsqr;
+pub mod minres;
+pub mod newton_krylov;
+pub mod symmlq;
+
+use num::{Float, One};
+use sparse_traits::types::{Scalar, SparseLinAlgError, SparseLinAlgResult};
+use sparse_traits::{
+    apply_preconditioner, AsApply, Element, ElementView, InnerProductSpace, LinearSpace,
+    OperatorBase,
+};
+
+/// The real type associated with the field of a space.
+pub type RealType<Space> = <<Space as LinearSpace>::F as Scalar>::Real;
+
+/// Stopping criteria of an iterative solver.
+///
+/// A solver stops successfully once the residual norm satisfies
+/// `|r| <= max(rel_tol * |b|, abs_tol)`.
+#[derive(Debug, Clone)]
+pub struct SolverOptions<R: Float> {
+    /// Tolerance relative to the norm of the right-hand side.
+    pub rel_tol: R,
+    /// Absolute tolerance for the residual norm.
+    pub abs_tol: R,
+    /// Maximum number of iterations.
+    pub max_iter: usize,
+}
+
+impl<R: Float> SolverOptions<R> {
+    pub fn new(rel_tol: R, abs_tol: R, max_iter: usize) -> Self {
+        Self {
+            rel_tol,
+            abs_tol,
+            max_iter,
+        }
+    }
+}
+
+impl<R: Float> Default for SolverOptions<R> {
+    /// A relative tolerance of `1E-8`, no absolute tolerance and at most 1000 iterations.
+    fn default() -> Self {
+        Self::new(R::from(1E-8).unwrap(), R::zero(), 1000)
+    }
+}
+
+/// The reason why an iterative solver stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvergenceReason {
+    /// The residual norm is below `rel_tol * |b|`.
+    RelativeTolerance,
+    /// The residual norm is below `abs_tol`.
+    AbsoluteTolerance,
+    /// The maximum number of iterations was reached without convergence.
+    MaxIterations,
+}
+
+/// Summary of a run of an iterative solver.
+#[derive(Debug, Clone)]
+pub struct SolverResult<R: Float> {
+    pub reason: ConvergenceReason,
+    /// Number of iterations performed.
+    pub iterations: usize,
+    /// Norm of the final residual.
+    pub residual_norm: R,
+    /// Residual norm of the initial guess followed by the residual norm after each iteration.
+    pub residual_history: Vec<R>,
+}
+
+impl<R: Float> SolverResult<R> {
+    /// Return true if one of the tolerances was reached.
+    pub fn converged(&self) -> bool {
+        self.reason != ConvergenceReason::MaxIterations
+    }
+}
+
+/// Stopping criteria and damping of the least-squares solvers [lsqr](lsqr::lsqr) and
+/// [lsmr](lsmr::lsmr), which solve `min |A x - b|^2 + damp^2 |x|^2`.
+///
+/// With `r = b - A x` a solver stops if `|r| <= btol * |b| + atol * |A| |x|`, which
+/// indicates a compatible system, if `|A^H r| <= atol * |A| |r|`, which indicates a
+/// least-squares solution, or if the estimate of `cond(A)` exceeds `conlim`.
+/// A value of zero for `conlim` disables the last test.
+#[derive(Debug, Clone)]
+pub struct LeastSquaresOptions<R: Float> {
+    /// Damping parameter of the regularized problem.
+    pub damp: R,
+    /// Estimate of the relative error in the entries of `A`.
+    pub atol: R,
+    /// Estimate of the relative error in the entries of `b`.
+    pub btol: R,
+    /// Limit for the estimate of the condition number of `A`.
+    pub conlim: R,
+    /// Maximum number of iterations.
+    pub max_iter: usize,
+}
+
+impl<R: Float> LeastSquaresOptions<R> {
+    pub fn new(damp: R, atol: R, btol: R, conlim: R, max_iter: usize) -> Self {
+        Self {
+            damp,
+            atol,
+            btol,
+            conlim,
+            max_iter,
+        }
+    }
+}
+
+impl<R: Float> Default for LeastSquaresOptions<R> {
+    /// No damping, `atol = btol = 1E-8`, `conlim = 1E8` and at most 1000 iterations.
+    fn default() -> Self {
+        let tol = R::from(1E-8).unwrap();
+        Self::new(R::zero(), tol, tol, R::from(1E8).unwrap(), 1000)
+    }
+}
+
+/// The reason why a least-squares solver stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeastSquaresReason {
+    /// The right-hand side vanishes, so `x = 0` is the solution.
+    ZeroSolution,
+    /// `A x = b` is solved to the tolerances `atol` and `btol` or to machine precision.
+    Compatible,
+    /// The least-squares problem is solved to the tolerance `atol` or to machine precision.
+    LeastSquares,
+    /// The estimate of `cond(A)` exceeds `conlim` or is of the order of the inverse
+    /// machine precision.
+    ConditionLimit,
+    /// The maximum number of iterations was reached.
+    MaxIterations,
+}
+
+/// Summary of a run of a least-squares solver.
+///
+/// All norms are estimates that the solvers compute from scalar recurrences.
+#[derive(Debug, Clone)]
+pub struct LeastSquaresResult<R: Float> {
+    pub reason: LeastSquaresReason,
+    /// Number of iterations performed.
+    pub iterations: usize,
+    /// Norm of the residual of the damped problem, `sqrt(|b - A x|^2 + damp^2 |x|^2)`.
+    pub residual_norm: R,
+    /// Norm of the residual of the normal equations, `|A^H (b - A x) - damp^2 x|`.
+    pub normal_residual_norm: R,
+    /// Estimate of the Frobenius norm of `[A; damp I]`.
+    pub operator_norm: R,
+    /// Estimate of the condition number of `[A; damp I]`.
+    pub condition_number: R,
+    /// Norm of the solution.
+    pub solution_norm: R,
+    /// Residual norm of the initial guess followed by the residual norm after each iteration.
+    pub residual_history: Vec<R>,
+}
+
+impl<R: Float> LeastSquaresResult<R> {
+    /// Return true if the solver found a solution to the requested tolerances.
+    pub fn converged(&self) -> bool {
+        matches!(
+            self.reason,
+            LeastSquaresReason::ZeroSolution
+                | LeastSquaresReason::Compatible
+                | LeastSquaresReason::LeastSquares
+        )
+    }
+}
+
+// The stopping tests shared by LSQR and LSMR, in the order of the reference implementations.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn least_squares_reason<R: Float>(
+    options: &LeastSquaresOptions<R>,
+    iteration: usize,
+    b_norm: R,
+    residual_norm: R,
+    normal_residual_norm: R,
+    operator_norm: R,
+    condition_number: R,
+    solution_norm: R,
+) -> Option<LeastSquaresReason> {
+    let one = R::one();
+    let test1 = residual_norm / b_norm;
+    let product = operator_norm * residual_norm;
+    let test2 = if product == R::zero() {
+        R::infinity()
+    } else {
+        normal_residual_norm / product
+    };
+    let test3 = one / condition_number;
+    let t1 = test1 / (one + operator_norm * solution_norm / b_norm);
+    let rtol = options.btol + options.atol * operator_norm * solution_norm / b_norm;
+    let ctol = if options.conlim > R::zero() {
+        one / options.conlim
+    } else {
+        R::zero()
+    };
+
+    if test1 <= rtol {
+        Some(LeastSquaresReason::Compatible)
+    } else if test2 <= options.atol {
+        Some(LeastSquaresReason::LeastSquares)
+    } else if test3 <= ctol {
+        Some(LeastSquaresReason::ConditionLimit)
+    } else if one + t1 <= one {
+        Some(LeastSquaresReason::Compatible)
+    } else if one + test2 <= one {
+        Some(LeastSquaresReason::LeastSquares)
+    } else if one + test3 <= one {
+        Some(LeastSquaresReason::ConditionLimit)
+    } else if iteration >= options.max_iter {
+        Some(LeastSquaresReason::MaxIterations)
+    } else {
+        None
+    }
+}
+
+// A real plane rotation (c, s, r) with c a + s b = r and -s a + c b = 0.
+pub(crate) fn sym_ortho<R: Float>(a: R, b: R) -> (R, R, R) {
+    if b == R::zero() {
+        (a.signum(), R::zero(), a.abs())
+    } else if a == R::zero() {
+        (R::zero(), b.signum(), b.abs())
+    } else {
+        let r = a.hypot(b);
+        (a / r, b / r, r)
+    }
+}
+
+/// Records the residual history and checks the stopping criteria.
+///
+/// All solvers in this module report their progress through a monitor, so the
+/// stopping criteria and the [SolverResult] are the same for every method.
+pub(crate) struct ConvergenceMonitor<R: Float> {
+    rel_target: R,
+    abs_target: R,
+    history: Vec<R>,
+}
+
+impl<R: Float> ConvergenceMonitor<R> {
+    pub(crate) fn new(options: &SolverOptions<R>, rhs_norm: R) -> Self {
+        Self {
+            rel_target: options.rel_tol * rhs_norm,
+            abs_target: options.abs_tol,
+            history: Vec::new(),
+        }
+    }
+
+    /// Record a residual norm and return a reason if the solver has converged.
+    pub(crate) fn check(&mut self, residual_norm: R) -> Option<ConvergenceReason> {
+        self.history.push(residual_norm);
+        self.reason(residual_norm)
+    }
+
+    /// Return a reason if a residual norm satisfies the stopping criteria without recording it.
+    ///
+    /// This is used for intermediate residuals that only end an iteration early.
+    pub(crate) fn reason(&self, residual_norm: R) -> Option<ConvergenceReason> {
+        if residual_norm <= self.rel_target {
+            Some(ConvergenceReason::RelativeTolerance)
+        } else if residual_norm <= self.abs_target {
+            Some(ConvergenceReason::AbsoluteTolerance)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn finish(self, reason: ConvergenceReason, iterations: usize) -> SolverResult<R> {
+        SolverResult {
+            reason,
+            iterations,
+            residual_norm: *self.history.last().unwrap(),
+            residual_history: self.history,
+        }
+    }
+}
+
+/// The norm induced by the inner product of the space.
+pub(crate) fn norm<'a, Space>(
+    space: &Space,
+    x: &ElementView<'a, Space>,
+) -> SparseLinAlgResult<RealType<Space>>
+where
+    Space: InnerProductSpace + 'a,
+{
+    Ok(Float::sqrt(space.inner(x, x)?.re()))
+}
+
+/// Compute the residual `r = b - A x`.
+pub(crate) fn residual<Space, Op>(
+    space: &Space,
+    op: &Op,
+    b: &Space::E<'_>,
+    x: &Space::E<'_>,
+    r: &mut Space::E<'_>,
+) -> SparseLinAlgResult<()>
+where
+    Space: LinearSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+{
+    let mut ax = space.create_element();
+    op.apply(x.view(), ax.view_mut())?;
+    space.fill_inplace(&b.view(), &mut r.view_mut())?;
+    space.axpy_inplace(-Space::F::one(), &ax.view(), &mut r.view_mut())?;
+    Ok(())
+}
+
+/// Compute `z = M^{-1} r` for a preconditioner `M^{-1}`, or `z = r` without a preconditioner.
+pub(crate) fn precondition<Space: LinearSpace>(
+    preconditioner: Option<&dyn OperatorBase<Domain = Space, Range = Space>>,
+    space: &Space,
+    r: &Space::E<'_>,
+    z: &mut Space::E<'_>,
+) -> SparseLinAlgResult<()> {
+    match preconditioner {
+        Some(prec) => apply_preconditioner(prec, r.view(), z.view_mut()),
+        None => space.fill_inplace(&r.view(), &mut z.view_mut()),
+    }
+}
+
+/// The error returned when a solver cannot continue since it would divide by zero.
+pub(crate) fn breakdown(method: &str, iteration: usize, message: &str) -> SparseLinAlgError {
+    SparseLinAlgError::SolverBreakdown {
+        method: method.to_string(),
+        iteration,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_problems {
+    //! Matrices and helpers shared by the tests of the solvers and preconditioners.
+
+    use crate::local::indexable_space::LocalIndexableVectorSpace;
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::cg::cg;
+    use crate::solvers::gmres::{gmres, GmresOptions};
+    use crate::solvers::SolverOptions;
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::{Element, LinearSpace, OperatorBase};
+
+    /// A preconditioner of the matrices in this module.
+    pub(crate) type Preconditioner<'a> = &'a dyn OperatorBase<
+        Domain = LocalIndexableVectorSpace<f64>,
+        Range = LocalIndexableVectorSpace<f64>,
+    >;
+
+    /// The matrix of the 1D Laplacian with Dirichlet boundary conditions.
+    pub(crate) fn laplace_1d(n: usize) -> CsrMatrix<f64> {
+        tridiagonal(&vec![2.0; n])
+    }
+
+    /// A symmetric tridiagonal matrix with the given diagonal and -1 off the diagonal.
+    pub(crate) fn tridiagonal(diagonal: &[f64]) -> CsrMatrix<f64> {
+        let n = diagonal.len();
+        let mut rows = Vec::new();
+        let mut cols = Vec::new();
+        let mut data = Vec::new();
+        for (index, &value) in diagonal.iter().enumerate() {
+            rows.push(index);
+            cols.push(index);
+            data.push(value);
+            if index > 0 {
+                rows.push(index);
+                cols.push(index - 1);
+                data.push(-1.0);
+            }
+            if index + 1 < n {
+                rows.push(index);
+                cols.push(index + 1);
+                data.push(-1.0);
+            }
+        }
+        CsrMatrix::from_aij((n, n), &rows, &cols, &data).unwrap()
+    }
+
+    /// A nonsymmetric 1D convection-diffusion matrix with a varying diagonal.
+    pub(crate) fn convection_diffusion(n: usize) -> CsrMatrix<f64> {
+        let mut rows = Vec::new();
+        let mut cols = Vec::new();
+        let mut data = Vec::new();
+        for index in 0..n {
+            rows.push(index);
+            cols.push(index);
+            data.push(2.0 + (index % 7) as f64);
+            if index > 0 {
+                rows.push(index);
+                cols.push(index - 1);
+                data.push(-1.5);
+            }
+            if index + 1 < n {
+                rows.push(index);
+                cols.push(index + 1);
+                data.push(-0.5);
+            }
+        }
+        CsrMatrix::from_aij((n, n), &rows, &cols, &data).unwrap()
+    }
+
+    /// The 5-point Laplacian on an m x m grid.
+    pub(crate) fn laplace_2d(m: usize) -> CsrMatrix<f64> {
+        five_point(m, [-1.0, -1.0, -1.0, -1.0])
+    }
+
+    /// The 5-point Laplacian on an m x m grid with a convection term.
+    pub(crate) fn convection_diffusion_2d(m: usize) -> CsrMatrix<f64> {
+        five_point(m, [-1.5, -0.5, -1.0, -1.0])
+    }
+
+    /// A 5-point stencil on an m x m grid with 4 on the diagonal and the given couplings
+    /// to the rows above, below, to the left and to the right.
+    fn five_point(m: usize, couplings: [f64; 4]) -> CsrMatrix<f64> {
+        let mut rows = Vec::new();
+        let mut cols = Vec::new();
+        let mut data = Vec::new();
+        for row in 0..m * m {
+            let (i, j) = (row / m, row % m);
+            let mut push = |col: usize, value: f64| {
+                rows.push(row);
+                cols.push(col);
+                data.push(value);
+            };
+            push(row, 4.0);
+            if i > 0 {
+                push(row - m, couplings[0]);
+            }
+            if i + 1 < m {
+                push(row + m, couplings[1]);
+            }
+            if j > 0 {
+                push(row - 1, couplings[2]);
+            }
+            if j + 1 < m {
+                push(row + 1, couplings[3]);
+            }
+        }
+        CsrMatrix::from_aij((m * m, m * m), &rows, &cols, &data).unwrap()
+    }
+
+    /// A sparse m x n matrix with m >= n and full column rank.
+    pub(crate) fn overdetermined(m: usize, n: usize) -> CsrMatrix<f64> {
+        let mut rows = Vec::new();
+        let mut cols = Vec::new();
+        let mut data = Vec::new();
+        for row in 0..m {
+            for col in 0..n {
+                if col == row % n {
+                    rows.push(row);
+                    cols.push(col);
+                    data.push(2.0 + (row / n) as f64);
+                } else if (7 * row + 3 * col) % 5 == 0 {
+                    rows.push(row);
+                    cols.push(col);
+                    data.push(0.5 - 0.25 * ((row + 2 * col) % 4) as f64);
+                }
+            }
+        }
+        CsrMatrix::from_aij((m, n), &rows, &cols, &data).unwrap()
+    }
+
+    /// The inverse of the diagonal of a matrix.
+    pub(crate) fn jacobi(mat: &CsrMatrix<f64>) -> CsrMatrix<f64> {
+        let n = mat.shape().0;
+        let diag: Vec<usize> = (0..n).collect();
+        let inv_diag: Vec<f64> = (0..n)
+            .map(|row| {
+                let index = (mat.indptr()[row]..mat.indptr()[1 + row])
+                    .find(|&index| mat.indices()[index] == row)
+                    .unwrap();
+                1.0 / mat.data()[index]
+            })
+            .collect();
+        CsrMatrix::from_aij((n, n), &diag, &diag, &inv_diag).unwrap()
+    }
+
+    /// The largest entry of `b - A x`.
+    pub(crate) fn max_residual(mat: &CsrMatrix<f64>, x: &[f64], b: &[f64]) -> f64 {
+        let mut res = b.to_vec();
+        mat.matmul(-1.0, x, 1.0, &mut res);
+        res.iter()
+            .fold(0.0, |acc, value| f64::max(acc, value.abs()))
+    }
+
+    /// Solve `A x = b` with CG and the given preconditioner and return the iterations. The
+    /// right-hand side has no symmetries, which would favour CG without preconditioner.
+    pub(crate) fn cg_iterations(mat: &CsrMatrix<f64>, prec: Option<Preconditioner>) -> usize {
+        let n = mat.shape().0;
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = ((7 * index) % 11) as f64;
+        }
+        let mut x = space.create_element();
+        let options = SolverOptions::new(1E-10, 0.0, 500);
+        let result = cg(&space, mat, prec, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(mat, x.data(), b.data()) < 1E-8);
+        result.iterations
+    }
+
+    /// Solve `A x = 1` with GMRES and the given preconditioner and return the iterations.
+    pub(crate) fn gmres_iterations(mat: &CsrMatrix<f64>, prec: Option<Preconditioner>) -> usize {
+        let n = mat.shape().0;
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let mut x = space.create_element();
+        let options = SolverOptions::new(1E-10, 0.0, 500);
+        let result = gmres(
+            &space,
+            mat,
+            prec,
+            &b,
+            &mut x,
+            &options,
+            &GmresOptions::default(),
+        )
+        .unwrap();
+        assert!(result.converged());
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(mat, x.data(), b.data()) < 1E-8);
+        result.iterations
+    }
+
+    /// The largest entry of `A^H (b - A x) - damp^2 x`, the residual of the normal equations.
+    pub(crate) fn max_normal_residual(
+        mat: &CsrMatrix<f64>,
+        x: &[f64],
+        b: &[f64],
+        damp: f64,
+    ) -> f64 {
+        let mut res = b.to_vec();
+        mat.matmul(-1.0, x, 1.0, &mut res);
+        let mut normal_res: Vec<f64> = x.iter().map(|value| -damp * damp * value).collect();
+        mat.matmul_adjoint(1.0, &res, 1.0, &mut normal_res);
+        normal_res
+            .iter()
+            .fold(0.0, |acc, value| f64::max(acc, value.abs()))
+    }
+
+    /// The rotation [0, 1; -1, 0], for which the BiCG type methods break down immediately
+    /// if the shadow residual is the initial residual e_1.
+    pub(crate) fn rotation() -> CsrMatrix<f64> {
+        CsrMatrix::from_aij((2, 2), &[0, 1], &[1, 0], &[1.0, -1.0]).unwrap()
+    }
+}
@@ -0,0 +1,497 @@
+//! BiCGStab and BiCGStab(l).
+//!
+//! Both methods use short recurrences, so their memory use does not grow with the
+//! number of iterations. Preconditioning is applied from the right and the monitored
+//! residual is the residual of the original system.
+
+use num::{One, Zero};
+use sparse_traits::types::SparseLinAlgResult;
+use sparse_traits::{AsApply, Element, InnerProductSpace, OperatorBase};
+
+use super::{
+    breakdown, norm, precondition, residual, ConvergenceMonitor, ConvergenceReason, RealType,
+    SolverOptions, SolverResult,
+};
+
+/// Solve `A x = b` with the stabilized biconjugate gradient method BiCGStab.
+///
+/// The optional `preconditioner` applies an approximation of `A^{-1}` from the right.
+/// On entry `x` contains the initial guess and on exit the approximate solution.
+/// Every iteration requires two applications of the operator and of the preconditioner.
+///
+/// A breakdown of the recurrences is reported as a `SparseLinAlgError::SolverBreakdown`.
+pub fn bicgstab<Space, Op>(
+    space: &Space,
+    op: &Op,
+    preconditioner: Option<&dyn OperatorBase<Domain = Space, Range = Space>>,
+    b: &Space::E<'_>,
+    x: &mut Space::E<'_>,
+    options: &SolverOptions<RealType<Space>>,
+) -> SparseLinAlgResult<SolverResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+{
+    let mut r = space.create_element();
+    residual(space, op, b, x, &mut r)?;
+
+    let mut monitor = ConvergenceMonitor::new(options, norm(space, &b.view())?);
+    if let Some(reason) = monitor.check(norm(space, &r.view())?) {
+        return Ok(monitor.finish(reason, 0));
+    }
+
+    let mut r_hat = space.create_element();
+    let mut p = space.create_element();
+    let mut p_hat = space.create_element();
+    let mut v = space.create_element();
+    let mut s_hat = space.create_element();
+    let mut t = space.create_element();
+    space.fill_inplace(&r.view(), &mut r_hat.view_mut())?;
+
+    let mut rho = Space::F::one();
+    let mut alpha = Space::F::one();
+    let mut omega = Space::F::one();
+
+    for iteration in 1..=options.max_iter {
+        let rho_new = space.inner(&r.view(), &r_hat.view())?;
+        if rho_new == Space::F::zero() {
+            return Err(breakdown(
+                "BiCGStab",
+                iteration,
+                "the residual is orthogonal to the shadow residual",
+            ));
+        }
+
+        // p = r + beta * (p - omega * v)
+        let beta = (rho_new / rho) * (alpha / omega);
+        space.axpy_inplace(-omega, &v.view(), &mut p.view_mut())?;
+        space.scale_inplace(beta, &mut p.view_mut());
+        space.axpy_inplace(Space::F::one(), &r.view(), &mut p.view_mut())?;
+        rho = rho_new;
+
+        precondition(preconditioner, space, &p, &mut p_hat)?;
+        op.apply(p_hat.view(), v.view_mut())?;
+        let denominator = space.inner(&v.view(), &r_hat.view())?;
+        if denominator == Space::F::zero() {
+            return Err(breakdown(
+                "BiCGStab",
+                iteration,
+                "the search direction is orthogonal to the shadow residual",
+            ));
+        }
+        alpha = rho / denominator;
+
+        // r now holds the intermediate residual s = r - alpha * v.
+        space.axpy_inplace(alpha, &p_hat.view(), &mut x.view_mut())?;
+        space.axpy_inplace(-alpha, &v.view(), &mut r.view_mut())?;
+        let s_norm = norm(space, &r.view())?;
+        if let Some(reason) = monitor.reason(s_norm) {
+            monitor.check(s_norm);
+            return Ok(monitor.finish(reason, iteration));
+        }
+
+        precondition(preconditioner, space, &r, &mut s_hat)?;
+        op.apply(s_hat.view(), t.view_mut())?;
+        let tt = space.inner(&t.view(), &t.view())?;
+        if tt == Space::F::zero() {
+            return Err(breakdown(
+                "BiCGStab",
+                iteration,
+                "the stabilization direction vanishes",
+            ));
+        }
+        omega = space.inner(&r.view(), &t.view())? / tt;
+
+        space.axpy_inplace(omega, &s_hat.view(), &mut x.view_mut())?;
+        space.axpy_inplace(-omega, &t.view(), &mut r.view_mut())?;
+        if let Some(reason) = monitor.check(norm(space, &r.view())?) {
+            return Ok(monitor.finish(reason, iteration));
+        }
+
+        if omega == Space::F::zero() {
+            return Err(breakdown(
+                "BiCGStab",
+                iteration,
+                "the stabilization parameter vanishes",
+            ));
+        }
+    }
+
+    Ok(monitor.finish(ConvergenceReason::MaxIterations, options.max_iter))
+}
+
+/// Solve `A x = b` with BiCGStab(l).
+///
+/// Each iteration performs `l` BiCG steps followed by a minimal residual polynomial
+/// of degree `l`, which makes the method more robust than BiCGStab for operators with
+/// complex eigenvalues. An iteration requires `2 l` applications of the operator and of
+/// the preconditioner, and the residual is only checked at the end of an iteration.
+/// BiCGStab(1) is mathematically equivalent to BiCGStab.
+///
+/// The optional `preconditioner` applies an approximation of `A^{-1}` from the right.
+/// On entry `x` contains the initial guess and on exit the approximate solution.
+/// A breakdown of the recurrences is reported as a `SparseLinAlgError::SolverBreakdown`.
+pub fn bicgstab_l<Space, Op>(
+    space: &Space,
+    op: &Op,
+    preconditioner: Option<&dyn OperatorBase<Domain = Space, Range = Space>>,
+    b: &Space::E<'_>,
+    x: &mut Space::E<'_>,
+    l: usize,
+    options: &SolverOptions<RealType<Space>>,
+) -> SparseLinAlgResult<SolverResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+{
+    let l = l.max(1);
+    let zero = Space::F::zero();
+    let one = Space::F::one();
+
+    let mut rs: Vec<_> = (0..=l).map(|_| space.create_element()).collect();
+    let mut us: Vec<_> = (0..=l).map(|_| space.create_element()).collect();
+    residual(space, op, b, x, &mut rs[0])?;
+
+    let mut monitor = ConvergenceMonitor::new(options, norm(space, &b.view())?);
+    if let Some(reason) = monitor.check(norm(space, &rs[0].view())?) {
+        return Ok(monitor.finish(reason, 0));
+    }
+
+    let mut r_hat = space.create_element();
+    let mut tmp = space.create_element();
+    // The correction of the solution before the preconditioner is applied, x = x_0 + M^{-1} y.
+    let mut y = space.create_element();
+    space.fill_inplace(&rs[0].view(), &mut r_hat.view_mut())?;
+
+    let mut rho0 = one;
+    let mut alpha = zero;
+    let mut omega = one;
+
+    let mut iteration = 0;
+    let mut reason = ConvergenceReason::MaxIterations;
+    while iteration < options.max_iter {
+        iteration += 1;
+        rho0 = -omega * rho0;
+
+        // The BiCG part.
+        for j in 0..l {
+            let rho1 = space.inner(&rs[j].view(), &r_hat.view())?;
+            if rho0 == zero {
+                return Err(breakdown(
+                    "BiCGStab(l)",
+                    iteration,
+                    "the residual is orthogonal to the shadow residual",
+                ));
+            }
+            let beta = alpha * rho1 / rho0;
+            rho0 = rho1;
+
+            for (u, r) in us.iter_mut().zip(rs.iter()).take(j + 1) {
+                space.scale_inplace(-beta, &mut u.view_mut());
+                space.axpy_inplace(one, &r.view(), &mut u.view_mut())?;
+            }
+            let (head, tail) = us.split_at_mut(j + 1);
+            apply_preconditioned(space, op, preconditioner, &head[j], &mut tmp, &mut tail[0])?;
+
+            let gamma = space.inner(&us[j + 1].view(), &r_hat.view())?;
+            if gamma == zero {
+                return Err(breakdown(
+                    "BiCGStab(l)",
+                    iteration,
+                    "the search direction is orthogonal to the shadow residual",
+                ));
+            }
+            alpha = rho0 / gamma;
+
+            for (r, u) in rs.iter_mut().zip(us.iter().skip(1)).take(j + 1) {
+                space.axpy_inplace(-alpha, &u.view(), &mut r.view_mut())?;
+            }
+            let (head, tail) = rs.split_at_mut(j + 1);
+            apply_preconditioned(space, op, preconditioner, &head[j], &mut tmp, &mut tail[0])?;
+            space.axpy_inplace(alpha, &us[0].view(), &mut y.view_mut())?;
+        }
+
+        // The minimal residual part, with modified Gram-Schmidt on r_1, ..., r_l.
+        let mut tau = vec![vec![zero; l + 1]; l + 1];
+        let mut sigma = vec![zero; l + 1];
+        let mut gamma_prime = vec![zero; l + 1];
+        for j in 1..=l {
+            let (head, tail) = rs.split_at_mut(j);
+            let rj = &mut tail[0];
+            for i in 1..j {
+                tau[i][j] = space.inner(&rj.view(), &head[i].view())? / sigma[i];
+                space.axpy_inplace(-tau[i][j], &head[i].view(), &mut rj.view_mut())?;
+            }
+            sigma[j] = space.inner(&rj.view(), &rj.view())?;
+            if sigma[j] == zero {
+                return Err(breakdown(
+                    "BiCGStab(l)",
+                    iteration,
+                    "the minimal residual polynomial is not unique",
+                ));
+            }
+            gamma_prime[j] = space.inner(&head[0].view(), &rj.view())? / sigma[j];
+        }
+
+        let mut gamma = vec![zero; l + 1];
+        gamma[l] = gamma_prime[l];
+        omega = gamma[l];
+        for j in (1..l).rev() {
+            gamma[j] = gamma_prime[j];
+            for i in j + 1..=l {
+                let update = tau[j][i] * gamma[i];
+                gamma[j] -= update;
+            }
+        }
+        let mut gamma_second = vec![zero; l + 1];
+        for j in 1..l {
+            gamma_second[j] = gamma[j + 1];
+            for i in j + 1..l {
+                gamma_second[j] += tau[j][i] * gamma[i + 1];
+            }
+        }
+
+        space.axpy_inplace(gamma[1], &rs[0].view(), &mut y.view_mut())?;
+        {
+            let (r0, rest) = rs.split_at_mut(1);
+            let (u0, rest_u) = us.split_at_mut(1);
+            space.axpy_inplace(-gamma_prime[l], &rest[l - 1].view(), &mut r0[0].view_mut())?;
+            space.axpy_inplace(-gamma[l], &rest_u[l - 1].view(), &mut u0[0].view_mut())?;
+            for j in 1..l {
+                space.axpy_inplace(-gamma[j], &rest_u[j - 1].view(), &mut u0[0].view_mut())?;
+                space.axpy_inplace(gamma_second[j], &rest[j - 1].view(), &mut y.view_mut())?;
+                space.axpy_inplace(-gamma_prime[j], &rest[j - 1].view(), &mut r0[0].view_mut())?;
+            }
+        }
+
+        if let Some(converged) = monitor.check(norm(space, &rs[0].view())?) {
+            reason = converged;
+            break;
+        }
+        if omega == zero {
+            return Err(breakdown(
+                "BiCGStab(l)",
+                iteration,
+                "the stabilization parameter vanishes",
+            ));
+        }
+    }
+
+    precondition(preconditioner, space, &y, &mut tmp)?;
+    space.axpy_inplace(one, &tmp.view(), &mut x.view_mut())?;
+    Ok(monitor.finish(reason, iteration))
+}
+
+// Compute y = A M^{-1} x, using tmp to store M^{-1} x.
+fn apply_preconditioned<Space, Op>(
+    space: &Space,
+    op: &Op,
+    preconditioner: Option<&dyn OperatorBase<Domain = Space, Range = Space>>,
+    x: &Space::E<'_>,
+    tmp: &mut Space::E<'_>,
+    y: &mut Space::E<'_>,
+) -> SparseLinAlgResult<()>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+{
+    match preconditioner {
+        Some(_) => {
+            precondition(preconditioner, space, x, tmp)?;
+            op.apply(tmp.view(), y.view_mut())
+        }
+        None => op.apply(x.view(), y.view_mut()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::local::indexable_space::LocalIndexableVectorSpace;
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::test_problems::{convection_diffusion, jacobi, max_residual, rotation};
+    use cauchy::c64;
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::types::SparseLinAlgError;
+    use sparse_traits::LinearSpace;
+
+    #[test]
+    fn test_bicgstab() {
+        let n = 100;
+        let mat = convection_diffusion(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = (index as f64).sin();
+        }
+        let mut x = space.create_element();
+
+        let options = SolverOptions::new(1E-10, 0.0, 200);
+        let result = bicgstab(&space, &mat, None, &b, &mut x, &options).unwrap();
+
+        assert_eq!(result.reason, ConvergenceReason::RelativeTolerance);
+        assert_eq!(result.residual_history.len(), 1 + result.iterations);
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(&mat, x.data(), b.data()) < 1E-8);
+    }
+
+    #[test]
+    fn test_bicgstab_preconditioned() {
+        let n = 100;
+        let mat = convection_diffusion(n);
+        let prec = jacobi(&mat);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-10, 0.0, 200);
+
+        let mut x = space.create_element();
+        let plain = bicgstab(&space, &mat, None, &b, &mut x, &options).unwrap();
+
+        let mut x = space.create_element();
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+        let preconditioned = bicgstab(&space, &mat, Some(prec_op), &b, &mut x, &options).unwrap();
+
+        assert!(plain.converged());
+        assert!(preconditioned.converged());
+        assert!(preconditioned.iterations < plain.iterations);
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(&mat, x.data(), b.data()) < 1E-8);
+    }
+
+    #[test]
+    fn test_bicgstab_l() {
+        let n = 100;
+        let mat = convection_diffusion(n);
+        let prec = jacobi(&mat);
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = 1.0 + (index as f64).cos();
+        }
+        let options = SolverOptions::new(1E-10, 0.0, 200);
+
+        for l in [1, 2, 4] {
+            for preconditioner in [None, Some(prec_op)] {
+                let mut x = space.create_element();
+                let result =
+                    bicgstab_l(&space, &mat, preconditioner, &b, &mut x, l, &options).unwrap();
+
+                assert!(result.converged());
+                assert_eq!(result.residual_history.len(), 1 + result.iterations);
+                let x = x.view().view().unwrap();
+                let b = b.view().view().unwrap();
+                assert!(max_residual(&mat, x.data(), b.data()) < 1E-8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bicgstab_complex() {
+        let n = 30;
+        let mut rows = Vec::new();
+        let mut cols = Vec::new();
+        let mut data = Vec::new();
+        for index in 0..n {
+            rows.push(index);
+            cols.push(index);
+            data.push(c64::new(4.0, 1.0));
+            if index + 1 < n {
+                rows.push(index);
+                cols.push(index + 1);
+                data.push(c64::new(-1.0, 1.0));
+            }
+            if index > 0 {
+                rows.push(index);
+                cols.push(index - 1);
+                data.push(c64::new(-2.0, 0.0));
+            }
+        }
+        let mat = CsrMatrix::from_aij((n, n), &rows, &cols, &data).unwrap();
+        let space = LocalIndexableVectorSpace::<c64>::new(n);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = c64::new(1.0, -(index as f64));
+        }
+        let options = SolverOptions::new(1E-12, 0.0, 200);
+
+        let mut x = space.create_element();
+        let result = bicgstab(&space, &mat, None, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+
+        let mut x_l = space.create_element();
+        let result = bicgstab_l(&space, &mat, None, &b, &mut x_l, 2, &options).unwrap();
+        assert!(result.converged());
+
+        for solution in [&x, &x_l] {
+            let mut res = b.view().view().unwrap().data().to_vec();
+            mat.matmul(
+                c64::new(-1.0, 0.0),
+                solution.view().view().unwrap().data(),
+                c64::new(1.0, 0.0),
+                &mut res,
+            );
+            assert!(res.iter().all(|value| value.norm() < 1E-9));
+        }
+    }
+
+    #[test]
+    fn test_bicgstab_breakdown() {
+        let mat = rotation();
+        let space = LocalIndexableVectorSpace::<f64>::new(2);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut()[0] = 1.0;
+        let options = SolverOptions::default();
+
+        let mut x = space.create_element();
+        let err = bicgstab(&space, &mat, None, &b, &mut x, &options).unwrap_err();
+        assert!(matches!(
+            err,
+            SparseLinAlgError::SolverBreakdown { iteration: 1, .. }
+        ));
+
+        let mut x = space.create_element();
+        let err = bicgstab_l(&space, &mat, None, &b, &mut x, 2, &options).unwrap_err();
+        assert!(matches!(
+            err,
+            SparseLinAlgError::SolverBreakdown { iteration: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_bicgstab_max_iterations() {
+        let n = 100;
+        let mat = convection_diffusion(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-14, 0.0, 2);
+
+        let mut x = space.create_element();
+        let result = bicgstab(&space, &mat, None, &b, &mut x, &options).unwrap();
+        assert_eq!(result.reason, ConvergenceReason::MaxIterations);
+        assert_eq!(result.iterations, 2);
+
+        // The solution of BiCGStab(l) is updated even if it does not converge.
+        let mut x = space.create_element();
+        let result = bicgstab_l(&space, &mat, None, &b, &mut x, 2, &options).unwrap();
+        assert_eq!(result.reason, ConvergenceReason::MaxIterations);
+        assert_eq!(result.iterations, 2);
+
+        let x = x.view().view().unwrap();
+        let mut res = vec![1.0; n];
+        mat.matmul(-1.0, x.data(), 1.0, &mut res);
+        let true_norm = res.iter().map(|value| value * value).sum::<f64>().sqrt();
+        assert!((true_norm - result.residual_norm).abs() < 1E-8 * true_norm);
+    }
+}
@@ -0,0 +1,342 @@
+//! The preconditioned Conjugate Gradient method.
+
+use num::{One, Zero};
+use sparse_traits::types::{Scalar, SparseLinAlgResult};
+use sparse_traits::{AsApply, Element, InnerProductSpace, OperatorBase};
+
+use super::{
+    breakdown, norm, precondition, residual, ConvergenceMonitor, ConvergenceReason, RealType,
+    SolverOptions, SolverResult,
+};
+
+/// Solve `A x = b` with the preconditioned Conjugate Gradient method.
+///
+/// The operator `A` must be Hermitian positive definite with respect to the inner
+/// product of `space`. The optional `preconditioner` applies an approximation of
+/// `A^{-1}` and must be Hermitian positive definite as well. On entry `x` contains
+/// the initial guess and on exit the approximate solution.
+///
+/// An error is returned if an application fails. A search direction with
+/// `Re (A p, p) <= 0` shows that the operator is not positive definite, and a residual
+/// with `Re (r, M^{-1} r) <= 0` that the preconditioner is not. Both are reported as a
+/// `SparseLinAlgError::SolverBreakdown`.
+pub fn cg<Space, Op>(
+    space: &Space,
+    op: &Op,
+    preconditioner: Option<&dyn OperatorBase<Domain = Space, Range = Space>>,
+    b: &Space::E<'_>,
+    x: &mut Space::E<'_>,
+    options: &SolverOptions<RealType<Space>>,
+) -> SparseLinAlgResult<SolverResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+{
+    let mut r = space.create_element();
+    let mut z = space.create_element();
+    let mut p = space.create_element();
+    let mut ap = space.create_element();
+
+    residual(space, op, b, x, &mut r)?;
+
+    let mut monitor = ConvergenceMonitor::new(options, norm(space, &b.view())?);
+    if let Some(reason) = monitor.check(norm(space, &r.view())?) {
+        return Ok(monitor.finish(reason, 0));
+    }
+
+    precondition(preconditioner, space, &r, &mut z)?;
+    space.fill_inplace(&z.view(), &mut p.view_mut())?;
+    let mut rz = space.inner(&r.view(), &z.view())?;
+    check_preconditioned_norm::<Space>(rz, 0)?;
+
+    for iteration in 1..=options.max_iter {
+        op.apply(p.view(), ap.view_mut())?;
+        let pap = space.inner(&ap.view(), &p.view())?;
+        if pap.re() <= RealType::<Space>::zero() {
+            return Err(breakdown(
+                "CG",
+                iteration,
+                "operator is not positive definite",
+            ));
+        }
+
+        let alpha = rz / pap;
+        space.axpy_inplace(alpha, &p.view(), &mut x.view_mut())?;
+        space.axpy_inplace(-alpha, &ap.view(), &mut r.view_mut())?;
+
+        if let Some(reason) = monitor.check(norm(space, &r.view())?) {
+            return Ok(monitor.finish(reason, iteration));
+        }
+
+        precondition(preconditioner, space, &r, &mut z)?;
+        let rz_new = space.inner(&r.view(), &z.view())?;
+        check_preconditioned_norm::<Space>(rz_new, iteration)?;
+        let beta = rz_new / rz;
+        rz = rz_new;
+
+        // p = z + beta * p
+        space.scale_inplace(beta, &mut p.view_mut());
+        space.axpy_inplace(Space::F::one(), &z.view(), &mut p.view_mut())?;
+    }
+
+    Ok(monitor.finish(ConvergenceReason::MaxIterations, options.max_iter))
+}
+
+/// Check that `(r, M^{-1} r)` of a nonzero residual is positive.
+fn check_preconditioned_norm<Space: InnerProductSpace>(
+    rz: Space::F,
+    iteration: usize,
+) -> SparseLinAlgResult<()> {
+    if rz.re() <= RealType::<Space>::zero() {
+        return Err(breakdown(
+            "CG",
+            iteration,
+            "preconditioner is not positive definite",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::local::indexable_space::LocalIndexableVectorSpace;
+    use crate::local::indexable_vector::LocalIndexableVector;
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::test_problems::{laplace_1d, tridiagonal};
+    use cauchy::c64;
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::types::SparseLinAlgError;
+    use sparse_traits::{FnOperator, IdentityOperator, LinearSpace, SumOperator};
+
+    #[test]
+    fn test_cg_laplace() {
+        let n = 50;
+        let mat = laplace_1d(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        let mut x = space.create_element();
+        for value in b.view_mut().view_mut().unwrap().iter_mut() {
+            *value = 1.0;
+        }
+
+        let options = SolverOptions::new(1E-10, 0.0, 100);
+        let result = cg(&space, &mat, None, &b, &mut x, &options).unwrap();
+
+        assert_eq!(result.reason, ConvergenceReason::RelativeTolerance);
+        assert!(result.converged());
+        assert_eq!(result.residual_history.len(), 1 + result.iterations);
+        // In exact arithmetic CG converges in at most n / 2 steps for this problem
+        // since the right-hand side is symmetric.
+        assert!(result.iterations <= n / 2 + 1);
+
+        // The exact solution is x_i = (i + 1)(n - i) / 2.
+        let x_view = x.view().view().unwrap();
+        for (index, value) in x_view.iter().enumerate() {
+            let expected = ((index + 1) * (n - index)) as f64 / 2.0;
+            assert!((value - expected).abs() < 1E-6 * expected);
+        }
+    }
+
+    #[test]
+    fn test_pcg_jacobi() {
+        // A Laplacian with a strongly varying diagonal shift, where Jacobi preconditioning helps.
+        let n = 100;
+        let shift: Vec<f64> = (0..n)
+            .map(|index| {
+                let value = ((17 * index * index) % 101) as f64;
+                1E-2 * value * value
+            })
+            .collect();
+        let mat = tridiagonal(&shift.iter().map(|value| 2.0 + value).collect::<Vec<_>>());
+
+        let diag: Vec<usize> = (0..n).collect();
+        let inv_diag: Vec<f64> = (0..n).map(|i| 1.0 / (2.0 + shift[i])).collect();
+        let jacobi = CsrMatrix::from_aij((n, n), &diag, &diag, &inv_diag).unwrap();
+
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+
+        let options = SolverOptions::new(1E-10, 0.0, 1000);
+
+        let mut x = space.create_element();
+        let plain = cg(&space, &mat, None, &b, &mut x, &options).unwrap();
+
+        let mut x = space.create_element();
+        let prec = &jacobi as &dyn OperatorBase<Domain = _, Range = _>;
+        let preconditioned = cg(&space, &mat, Some(prec), &b, &mut x, &options).unwrap();
+
+        assert!(plain.converged());
+        assert!(preconditioned.converged());
+        assert!(preconditioned.iterations < plain.iterations);
+
+        // Check the residual of the preconditioned solution.
+        let mut res = vec![0.0; n];
+        mat.matmul(1.0, x.view().view().unwrap().data(), 0.0, &mut res);
+        assert!(res.iter().all(|value| (value - 1.0).abs() < 1E-8));
+    }
+
+    #[test]
+    fn test_cg_complex_hermitian() {
+        // The Hermitian positive definite matrix [[4, 1 - i], [1 + i, 3]].
+        let data = vec![
+            c64::new(4.0, 0.0),
+            c64::new(1.0, -1.0),
+            c64::new(1.0, 1.0),
+            c64::new(3.0, 0.0),
+        ];
+        let mat = CsrMatrix::from_aij((2, 2), &[0, 0, 1, 1], &[0, 1, 0, 1], &data).unwrap();
+        let space = LocalIndexableVectorSpace::<c64>::new(2);
+
+        let mut b = space.create_element();
+        let mut x = space.create_element();
+        b.view_mut()
+            .view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&[c64::new(1.0, 2.0), c64::new(0.0, -1.0)]);
+
+        let options = SolverOptions::new(1E-12, 0.0, 10);
+        let result = cg(&space, &mat, None, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+        assert!(result.iterations <= 3);
+
+        let mut res = vec![c64::new(0.0, 0.0); 2];
+        mat.matmul(
+            c64::new(1.0, 0.0),
+            x.view().view().unwrap().data(),
+            c64::new(0.0, 0.0),
+            &mut res,
+        );
+        for (actual, expected) in res.iter().zip(b.view().view().unwrap().iter()) {
+            assert!((actual - expected).norm() < 1E-10);
+        }
+    }
+
+    #[test]
+    fn test_cg_max_iterations() {
+        let n = 20;
+        let mat = laplace_1d(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        let mut x = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut()[n / 3] = 1.0;
+
+        let options = SolverOptions::new(1E-14, 0.0, 3);
+        let result = cg(&space, &mat, None, &b, &mut x, &options).unwrap();
+
+        assert_eq!(result.reason, ConvergenceReason::MaxIterations);
+        assert!(!result.converged());
+        assert_eq!(result.iterations, 3);
+        assert_eq!(result.residual_history.len(), 4);
+        assert_eq!(result.residual_norm, result.residual_history[3]);
+
+        // A zero right-hand side is solved by the zero initial guess.
+        let b = space.create_element();
+        let mut x = space.create_element();
+        let options = SolverOptions::new(1E-8, 1E-12, 10);
+        let result = cg(&space, &mat, None, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+        assert_eq!(result.iterations, 0);
+    }
+
+    #[test]
+    fn test_cg_indefinite() {
+        let space = LocalIndexableVectorSpace::<f64>::new(2);
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-10, 0.0, 10);
+
+        // (A b, b) = -1 for A = diag(1, -2).
+        let indefinite = CsrMatrix::from_aij((2, 2), &[0, 1], &[0, 1], &[1.0, -2.0]).unwrap();
+        let mut x = space.create_element();
+        assert!(matches!(
+            cg(&space, &indefinite, None, &b, &mut x, &options),
+            Err(SparseLinAlgError::SolverBreakdown { iteration: 1, .. })
+        ));
+
+        // The same matrix as a preconditioner of the identity gives (r, M^{-1} r) = -1.
+        let identity = CsrMatrix::from_aij((2, 2), &[0, 1], &[0, 1], &[1.0, 1.0]).unwrap();
+        let prec = &indefinite as &dyn OperatorBase<Domain = _, Range = _>;
+        let mut x = space.create_element();
+        assert!(matches!(
+            cg(&space, &identity, Some(prec), &b, &mut x, &options),
+            Err(SparseLinAlgError::SolverBreakdown { iteration: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_cg_operator_sum() {
+        // The lazy sum of the Laplacian and the identity equals tridiagonal(3, -1).
+        let n = 30;
+        let laplace = laplace_1d(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let shifted = SumOperator::new(&laplace, IdentityOperator::new(space.clone())).unwrap();
+        let assembled = tridiagonal(&vec![3.0; n]);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = (index % 5) as f64;
+        }
+        let options = SolverOptions::new(1E-12, 0.0, 100);
+
+        let mut x = space.create_element();
+        let lazy = cg(&space, &shifted, None, &b, &mut x, &options).unwrap();
+        let mut expected = space.create_element();
+        let result = cg(&space, &assembled, None, &b, &mut expected, &options).unwrap();
+
+        assert!(lazy.converged());
+        assert_eq!(lazy.iterations, result.iterations);
+        for (actual, expected) in x
+            .view()
+            .view()
+            .unwrap()
+            .iter()
+            .zip(expected.view().view().unwrap().iter())
+        {
+            assert!((actual - expected).abs() < 1E-12);
+        }
+    }
+
+    #[test]
+    fn test_cg_matrix_free() {
+        // The 1D Laplacian as a stencil closure.
+        let n = 40;
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let stencil = FnOperator::new(
+            &space,
+            &space,
+            |x: &LocalIndexableVector<f64>, y: &mut LocalIndexableVector<f64>| {
+                let x = x.view().unwrap();
+                let x = x.data();
+                for (index, out) in y.view_mut().unwrap().iter_mut().enumerate() {
+                    *out = 2.0 * x[index];
+                    if index > 0 {
+                        *out -= x[index - 1];
+                    }
+                    if index + 1 < n {
+                        *out -= x[index + 1];
+                    }
+                }
+                Ok(())
+            },
+        );
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-12, 0.0, 100);
+
+        let mut x = space.create_element();
+        let result = cg(&space, &stencil, None, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+
+        let mat = laplace_1d(n);
+        let mut res = vec![0.0; n];
+        mat.matmul(1.0, x.view().view().unwrap().data(), 0.0, &mut res);
+        assert!(res.iter().all(|value| (value - 1.0).abs() < 1E-9));
+    }
+}
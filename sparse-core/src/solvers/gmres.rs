@@ -0,0 +1,669 @@
+//! Restarted GMRES and flexible GMRES.
+//!
+//! Both methods build an orthonormal Krylov basis with modified Gram-Schmidt and track
+//! the residual norm with Givens rotations of the Hessenberg matrix. Preconditioning is
+//! applied from the right, so the monitored residual is the residual of the original system.
+
+use num::{Float, One, Zero};
+use sparse_traits::types::{Scalar, SparseLinAlgResult};
+use sparse_traits::{
+    apply_preconditioner, AsApply, Element, ElementView, ElementViewMut, InnerProductSpace,
+    OperatorBase,
+};
+
+use super::{
+    breakdown, norm, residual, ConvergenceMonitor, ConvergenceReason, RealType, SolverOptions,
+    SolverResult,
+};
+
+/// Options specific to GMRES.
+#[derive(Debug, Clone)]
+pub struct GmresOptions {
+    /// Number of iterations after which the method restarts.
+    pub restart: usize,
+    /// Perform a second pass of modified Gram-Schmidt in every iteration.
+    pub reorthogonalize: bool,
+}
+
+impl GmresOptions {
+    pub fn new(restart: usize, reorthogonalize: bool) -> Self {
+        Self {
+            restart,
+            reorthogonalize,
+        }
+    }
+}
+
+impl Default for GmresOptions {
+    /// Restart after 30 iterations without reorthogonalization.
+    fn default() -> Self {
+        Self::new(30, false)
+    }
+}
+
+/// Solve `A x = b` with restarted GMRES.
+///
+/// The optional `preconditioner` applies an approximation of `A^{-1}` from the right.
+/// On entry `x` contains the initial guess and on exit the approximate solution.
+///
+/// A singular Hessenberg matrix, which can only occur if the operator is singular, is
+/// reported as a `SparseLinAlgError::SolverBreakdown`.
+pub fn gmres<Space, Op>(
+    space: &Space,
+    op: &Op,
+    preconditioner: Option<&dyn OperatorBase<Domain = Space, Range = Space>>,
+    b: &Space::E<'_>,
+    x: &mut Space::E<'_>,
+    options: &SolverOptions<RealType<Space>>,
+    gmres_options: &GmresOptions,
+) -> SparseLinAlgResult<SolverResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+{
+    gmres_impl(
+        space,
+        op,
+        |_, r, mut z| match preconditioner {
+            Some(prec) => apply_preconditioner(prec, r, z),
+            None => space.fill_inplace(&r, &mut z),
+        },
+        false,
+        b,
+        x,
+        options,
+        gmres_options,
+    )
+}
+
+/// Solve `A x = b` with restarted flexible GMRES.
+///
+/// The preconditioner may change in every iteration. It is called as
+/// `preconditioner(iteration, v, z)` and must store an approximation of `A^{-1} v` in `z`,
+/// where `iteration` counts the iterations from one. Compared to [gmres] this requires
+/// storage for a second set of `restart` vectors.
+///
+/// A singular Hessenberg matrix is reported as a `SparseLinAlgError::SolverBreakdown`.
+pub fn fgmres<Space, Op, Prec>(
+    space: &Space,
+    op: &Op,
+    preconditioner: Prec,
+    b: &Space::E<'_>,
+    x: &mut Space::E<'_>,
+    options: &SolverOptions<RealType<Space>>,
+    gmres_options: &GmresOptions,
+) -> SparseLinAlgResult<SolverResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+    Prec: for<'a> FnMut(
+        usize,
+        ElementView<'a, Space>,
+        ElementViewMut<'a, Space>,
+    ) -> SparseLinAlgResult<()>,
+{
+    gmres_impl(
+        space,
+        op,
+        preconditioner,
+        true,
+        b,
+        x,
+        options,
+        gmres_options,
+    )
+}
+
+// The shared implementation of GMRES and FGMRES. In the flexible case the preconditioned
+// basis vectors are stored, otherwise the preconditioner is applied once more to the
+// update of the solution at the end of each cycle.
+#[allow(clippy::too_many_arguments)]
+fn gmres_impl<Space, Op, Prec>(
+    space: &Space,
+    op: &Op,
+    mut preconditioner: Prec,
+    flexible: bool,
+    b: &Space::E<'_>,
+    x: &mut Space::E<'_>,
+    options: &SolverOptions<RealType<Space>>,
+    gmres_options: &GmresOptions,
+) -> SparseLinAlgResult<SolverResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+    Prec: for<'a> FnMut(
+        usize,
+        ElementView<'a, Space>,
+        ElementViewMut<'a, Space>,
+    ) -> SparseLinAlgResult<()>,
+{
+    let restart = gmres_options.restart.max(1);
+
+    let mut r = space.create_element();
+    let mut z = space.create_element();
+    let mut basis: Vec<_> = (0..=restart).map(|_| space.create_element()).collect();
+    let mut preconditioned_basis: Vec<_> = if flexible {
+        (0..restart).map(|_| space.create_element()).collect()
+    } else {
+        Vec::new()
+    };
+
+    residual(space, op, b, x, &mut r)?;
+    let mut beta = norm(space, &r.view())?;
+
+    let mut monitor = ConvergenceMonitor::new(options, norm(space, &b.view())?);
+    if let Some(reason) = monitor.check(beta) {
+        return Ok(monitor.finish(reason, 0));
+    }
+
+    let mut iterations = 0;
+    while iterations < options.max_iter {
+        space.fill_inplace(&r.view(), &mut basis[0].view_mut())?;
+        space.scale_inplace(inverse(beta), &mut basis[0].view_mut());
+
+        // Column j of the Hessenberg matrix has j + 2 entries.
+        let mut hessenberg: Vec<Vec<Space::F>> = Vec::with_capacity(restart);
+        let mut rotations: Vec<GivensRotation<Space::F>> = Vec::with_capacity(restart);
+        let mut g = vec![Space::F::zero(); restart + 1];
+        g[0] = Space::F::from_real(beta);
+
+        let mut reason = None;
+        for j in 0..restart {
+            if iterations == options.max_iter {
+                break;
+            }
+            iterations += 1;
+
+            // The new direction is A M_j^{-1} v_j.
+            let (head, tail) = basis.split_at_mut(j + 1);
+            let w = &mut tail[0];
+            let zj = if flexible {
+                &mut preconditioned_basis[j]
+            } else {
+                &mut z
+            };
+            preconditioner(iterations, head[j].view(), zj.view_mut())?;
+            op.apply(zj.view(), w.view_mut())?;
+
+            let mut column = vec![Space::F::zero(); j + 2];
+            let passes = if gmres_options.reorthogonalize { 2 } else { 1 };
+            for _ in 0..passes {
+                for (i, v) in head.iter().enumerate() {
+                    let h = space.inner(&w.view(), &v.view())?;
+                    space.axpy_inplace(-h, &v.view(), &mut w.view_mut())?;
+                    column[i] += h;
+                }
+            }
+            let h_next = norm(space, &w.view())?;
+            if h_next > RealType::<Space>::zero() {
+                space.scale_inplace(inverse(h_next), &mut w.view_mut());
+            }
+            column[j + 1] = Space::F::from_real(h_next);
+
+            for (i, rotation) in rotations.iter().enumerate() {
+                rotation.apply(&mut column, i);
+            }
+            let rotation = GivensRotation::new(column[j], column[j + 1]);
+            rotation.apply(&mut column, j);
+            rotation.apply(&mut g, j);
+            rotations.push(rotation);
+            hessenberg.push(column);
+
+            reason = monitor.check(g[j + 1].abs());
+            // A vanishing subdiagonal entry means that the Krylov space is invariant
+            // and the current iterate is the exact solution.
+            if reason.is_some() || h_next == RealType::<Space>::zero() {
+                break;
+            }
+        }
+
+        // Solve the upper triangular system H y = g by back substitution.
+        let k = hessenberg.len();
+        let mut y = g[..k].to_vec();
+        for i in (0..k).rev() {
+            for l in i + 1..k {
+                let update = hessenberg[l][i] * y[l];
+                y[i] -= update;
+            }
+            if hessenberg[i][i] == Space::F::zero() {
+                return Err(breakdown(
+                    "GMRES",
+                    iterations,
+                    "the least squares problem is singular",
+                ));
+            }
+            y[i] /= hessenberg[i][i];
+        }
+
+        if flexible {
+            for (coefficient, zi) in y.iter().zip(preconditioned_basis.iter()) {
+                space.axpy_inplace(*coefficient, &zi.view(), &mut x.view_mut())?;
+            }
+        } else {
+            // Reuse r to accumulate V y before applying the preconditioner.
+            space.scale_inplace(Space::F::zero(), &mut r.view_mut());
+            for (coefficient, vi) in y.iter().zip(basis.iter()) {
+                space.axpy_inplace(*coefficient, &vi.view(), &mut r.view_mut())?;
+            }
+            preconditioner(iterations, r.view(), z.view_mut())?;
+            space.axpy_inplace(Space::F::one(), &z.view(), &mut x.view_mut())?;
+        }
+
+        if let Some(reason) = reason {
+            return Ok(monitor.finish(reason, iterations));
+        }
+
+        residual(space, op, b, x, &mut r)?;
+        beta = norm(space, &r.view())?;
+    }
+
+    Ok(monitor.finish(ConvergenceReason::MaxIterations, iterations))
+}
+
+fn inverse<T: Scalar>(value: T::Real) -> T {
+    T::from_real(T::Real::one() / value)
+}
+
+// A complex Givens rotation [c, s; -conj(s), c] with real c.
+struct GivensRotation<T: Scalar> {
+    c: T::Real,
+    s: T,
+}
+
+impl<T: Scalar> GivensRotation<T> {
+    // The rotation that maps (a, b) to (r, 0).
+    fn new(a: T, b: T) -> Self {
+        let abs_a = a.abs();
+        if abs_a == T::Real::zero() {
+            return Self {
+                c: T::Real::zero(),
+                s: T::one(),
+            };
+        }
+        let t = Float::hypot(abs_a, b.abs());
+        let phase = a / T::from_real(abs_a);
+        Self {
+            c: abs_a / t,
+            s: phase * b.conj() / T::from_real(t),
+        }
+    }
+
+    // Rotate the entries i and i + 1 of the vector.
+    fn apply(&self, vec: &mut [T], i: usize) {
+        let c = T::from_real(self.c);
+        let (a, b) = (vec[i], vec[i + 1]);
+        vec[i] = c * a + self.s * b;
+        vec[i + 1] = c * b - self.s.conj() * a;
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::local::indexable_space::LocalIndexableVectorSpace;
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::test_problems::{
+        convection_diffusion, jacobi, max_residual, overdetermined,
+    };
+    use cauchy::c64;
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::types::SparseLinAlgError;
+    use sparse_traits::{
+        AdjointOperator, BlockOperator, BlockTriangle, BlockTriangularPreconditioner,
+        BoxedOperator, IdentityOperator, LinearSpace, ProductSpace, ScaledOperator,
+    };
+
+    #[test]
+    fn test_gmres_full() {
+        let n = 40;
+        let mat = convection_diffusion(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        let mut x = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+
+        let options = SolverOptions::new(1E-12, 0.0, 100);
+        let result = gmres(
+            &space,
+            &mat,
+            None,
+            &b,
+            &mut x,
+            &options,
+            &GmresOptions::new(n, false),
+        )
+        .unwrap();
+
+        assert!(result.converged());
+        assert!(result.iterations <= n);
+        assert_eq!(result.residual_history.len(), 1 + result.iterations);
+        // The GMRES residual norm is nonincreasing.
+        for pair in result.residual_history.windows(2) {
+            assert!(pair[1] <= pair[0] * (1.0 + 1E-12));
+        }
+
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(&mat, x.data(), b.data()) < 1E-10);
+    }
+
+    #[test]
+    fn test_gmres_restarted() {
+        let n = 100;
+        let mat = convection_diffusion(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = (index as f64).sin();
+        }
+        let options = SolverOptions::new(1E-10, 0.0, 500);
+
+        for reorthogonalize in [false, true] {
+            let mut x = space.create_element();
+            let gmres_options = GmresOptions::new(5, reorthogonalize);
+            let result = gmres(&space, &mat, None, &b, &mut x, &options, &gmres_options).unwrap();
+
+            assert_eq!(result.reason, ConvergenceReason::RelativeTolerance);
+            let x = x.view().view().unwrap();
+            let b = b.view().view().unwrap();
+            assert!(max_residual(&mat, x.data(), b.data()) < 1E-8);
+        }
+    }
+
+    #[test]
+    fn test_gmres_preconditioned() {
+        let n = 100;
+        let mat = convection_diffusion(n);
+        let prec = jacobi(&mat);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-10, 0.0, 500);
+        let gmres_options = GmresOptions::new(10, false);
+
+        let mut x = space.create_element();
+        let plain = gmres(&space, &mat, None, &b, &mut x, &options, &gmres_options).unwrap();
+
+        let mut x = space.create_element();
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+        let preconditioned = gmres(
+            &space,
+            &mat,
+            Some(prec_op),
+            &b,
+            &mut x,
+            &options,
+            &gmres_options,
+        )
+        .unwrap();
+
+        assert!(plain.converged());
+        assert!(preconditioned.converged());
+        assert!(preconditioned.iterations < plain.iterations);
+
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(&mat, x.data(), b.data()) < 1E-8);
+    }
+
+    #[test]
+    fn test_fgmres() {
+        let n = 100;
+        let mat = convection_diffusion(n);
+        let prec = jacobi(&mat);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-10, 0.0, 500);
+        let gmres_options = GmresOptions::new(10, false);
+
+        // With a fixed preconditioner FGMRES and GMRES produce the same iterates.
+        let mut x = space.create_element();
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+        let fixed = gmres(
+            &space,
+            &mat,
+            Some(prec_op),
+            &b,
+            &mut x,
+            &options,
+            &gmres_options,
+        )
+        .unwrap();
+        let mut x = space.create_element();
+        let flexible = fgmres(
+            &space,
+            &mat,
+            |_, v, z| prec.apply(v, z),
+            &b,
+            &mut x,
+            &options,
+            &gmres_options,
+        )
+        .unwrap();
+        assert_eq!(fixed.iterations, flexible.iterations);
+
+        // Alternate between Jacobi and no preconditioning.
+        let mut x = space.create_element();
+        let mut calls = Vec::new();
+        let result = fgmres(
+            &space,
+            &mat,
+            |iteration, v, mut z| {
+                calls.push(iteration);
+                if iteration % 2 == 0 {
+                    prec.apply(v, z)
+                } else {
+                    space.fill_inplace(&v, &mut z)
+                }
+            },
+            &b,
+            &mut x,
+            &options,
+            &gmres_options,
+        )
+        .unwrap();
+
+        assert!(result.converged());
+        assert_eq!(calls, (1..=result.iterations).collect::<Vec<_>>());
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(&mat, x.data(), b.data()) < 1E-8);
+    }
+
+    #[test]
+    fn test_gmres_complex() {
+        let n = 20;
+        let mut rows = Vec::new();
+        let mut cols = Vec::new();
+        let mut data = Vec::new();
+        for index in 0..n {
+            rows.push(index);
+            cols.push(index);
+            data.push(c64::new(3.0, 1.0 + index as f64 / n as f64));
+            if index + 1 < n {
+                rows.push(index);
+                cols.push(index + 1);
+                data.push(c64::new(-1.0, 0.5));
+            }
+            if index > 0 {
+                rows.push(index);
+                cols.push(index - 1);
+                data.push(c64::new(0.0, -1.0));
+            }
+        }
+        let mat = CsrMatrix::from_aij((n, n), &rows, &cols, &data).unwrap();
+        let space = LocalIndexableVectorSpace::<c64>::new(n);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = c64::new(1.0, index as f64);
+        }
+        let mut x = space.create_element();
+
+        let options = SolverOptions::new(1E-12, 0.0, 200);
+        let result = gmres(
+            &space,
+            &mat,
+            None,
+            &b,
+            &mut x,
+            &options,
+            &GmresOptions::new(8, true),
+        )
+        .unwrap();
+        assert!(result.converged());
+
+        let mut res = b.view().view().unwrap().data().to_vec();
+        mat.matmul(
+            c64::new(-1.0, 0.0),
+            x.view().view().unwrap().data(),
+            c64::new(1.0, 0.0),
+            &mut res,
+        );
+        assert!(res.iter().all(|value| value.norm() < 1E-9));
+    }
+
+    #[test]
+    fn test_gmres_max_iterations() {
+        let n = 50;
+        let mat = convection_diffusion(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        let mut x = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+
+        let options = SolverOptions::new(1E-14, 0.0, 7);
+        let result = gmres(
+            &space,
+            &mat,
+            None,
+            &b,
+            &mut x,
+            &options,
+            &GmresOptions::new(3, false),
+        )
+        .unwrap();
+
+        assert_eq!(result.reason, ConvergenceReason::MaxIterations);
+        assert_eq!(result.iterations, 7);
+        assert_eq!(result.residual_history.len(), 8);
+
+        // The estimated residual agrees with the true residual of the final iterate.
+        let x = x.view().view().unwrap();
+        let mut res = vec![1.0; n];
+        mat.matmul(-1.0, x.data(), 1.0, &mut res);
+        let true_norm = res.iter().map(|value| value * value).sum::<f64>().sqrt();
+        assert!((true_norm - result.residual_norm).abs() < 1E-10 * true_norm);
+    }
+
+    #[test]
+    fn test_gmres_singular() {
+        // The Krylov space of diag(1, 0) and b = e_2 is spanned by b, which is mapped to zero.
+        let mat = CsrMatrix::from_aij((2, 2), &[0, 1], &[0, 1], &[1.0, 0.0]).unwrap();
+        let space = LocalIndexableVectorSpace::<f64>::new(2);
+
+        let mut b = space.create_element();
+        let mut x = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut()[1] = 1.0;
+
+        let options = SolverOptions::new(1E-10, 0.0, 10);
+        for flexible in [false, true] {
+            let result = if flexible {
+                fgmres(
+                    &space,
+                    &mat,
+                    |_, r, mut z| space.fill_inplace(&r, &mut z),
+                    &b,
+                    &mut x,
+                    &options,
+                    &GmresOptions::default(),
+                )
+            } else {
+                gmres(
+                    &space,
+                    &mat,
+                    None,
+                    &b,
+                    &mut x,
+                    &options,
+                    &GmresOptions::default(),
+                )
+            };
+            assert!(matches!(
+                result,
+                Err(SparseLinAlgError::SolverBreakdown { iteration: 1, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_gmres_block_triangular() {
+        // The saddle point system [A, B^T; B, 0] with a nonsymmetric A, preconditioned
+        // with the block upper triangular [diag(A), B^T; 0, -I]^{-1}.
+        let (n, m) = (30, 10);
+        let a = convection_diffusion(n);
+        let bt = overdetermined(n, m);
+        let velocity = LocalIndexableVectorSpace::<f64>::new(n);
+        let pressure = LocalIndexableVectorSpace::<f64>::new(m);
+        let space = ProductSpace::new(vec![velocity, pressure.clone()]);
+        let op = BlockOperator::new(
+            space.clone(),
+            space.clone(),
+            vec![
+                vec![
+                    Some(Box::new(&a) as BoxedOperator<_, _>),
+                    Some(Box::new(&bt)),
+                ],
+                vec![Some(Box::new(AdjointOperator::new(&bt))), None],
+            ],
+        )
+        .unwrap();
+        let jacobi_a = jacobi(&a);
+        let prec = BlockTriangularPreconditioner::new(
+            BlockTriangle::Upper,
+            vec![
+                Box::new(&jacobi_a) as BoxedOperator<_, _>,
+                Box::new(ScaledOperator::new(-1.0, IdentityOperator::new(pressure))),
+            ],
+            vec![vec![None, Some(Box::new(&bt))], vec![None, None]],
+        )
+        .unwrap();
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+
+        let mut b = space.create_element();
+        b.view_mut()[0].view_mut().unwrap().data_mut().fill(1.0);
+        for (index, value) in b.view_mut()[1].view_mut().unwrap().iter_mut().enumerate() {
+            *value = index as f64;
+        }
+        let options = SolverOptions::new(1E-10, 0.0, 500);
+        let gmres_options = GmresOptions::new(40, false);
+        let mut x = space.create_element();
+        let result = gmres(
+            &space,
+            &op,
+            Some(prec_op),
+            &b,
+            &mut x,
+            &options,
+            &gmres_options,
+        )
+        .unwrap();
+        assert!(result.converged());
+
+        let mut res = space.create_element();
+        op.apply(x.view(), res.view_mut()).unwrap();
+        space
+            .axpy_inplace(-1.0, &b.view(), &mut res.view_mut())
+            .unwrap();
+        let res_norm = space.inner(&res.view(), &res.view()).unwrap().sqrt();
+        let b_norm = space.inner(&b.view(), &b.view()).unwrap().sqrt();
+        assert!(res_norm < 1E-8 * b_norm);
+    }
+}
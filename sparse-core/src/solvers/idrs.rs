@@ -0,0 +1,359 @@
+//! The induced dimension reduction method IDR(s).
+
+use num::{Float, NumCast, One, Zero};
+use sparse_traits::types::{Scalar, SparseLinAlgError, SparseLinAlgResult};
+use sparse_traits::{AsApply, Element, InnerProductSpace, OperatorBase};
+
+use super::{
+    breakdown, norm, precondition, residual, ConvergenceMonitor, ConvergenceReason, RealType,
+    SolverOptions, SolverResult,
+};
+
+/// Solve `A x = b` with IDR(s) using biorthogonalization.
+///
+/// The `s` vectors in `shadow` span the shadow space. They are orthonormalized
+/// internally and should be chosen at random, independently of the right-hand side.
+/// Larger values of `s` usually need fewer iterations at the cost of `3 s` additional
+/// vectors of storage. IDR(1) is mathematically equivalent to BiCGStab.
+///
+/// The optional `preconditioner` applies an approximation of `A^{-1}` from the right.
+/// On entry `x` contains the initial guess and on exit the approximate solution.
+/// Every iteration requires a single application of the operator and of the preconditioner.
+/// A breakdown of the recurrences is reported as a `SparseLinAlgError::SolverBreakdown`.
+pub fn idrs<Space, Op>(
+    space: &Space,
+    op: &Op,
+    preconditioner: Option<&dyn OperatorBase<Domain = Space, Range = Space>>,
+    shadow: &[Space::E<'_>],
+    b: &Space::E<'_>,
+    x: &mut Space::E<'_>,
+    options: &SolverOptions<RealType<Space>>,
+) -> SparseLinAlgResult<SolverResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+{
+    let s = shadow.len();
+    if s == 0 {
+        return Err(SparseLinAlgError::OperationFailed(
+            "IDR(s) requires at least one shadow vector".to_string(),
+        ));
+    }
+    let zero = Space::F::zero();
+    let one = Space::F::one();
+
+    // Orthonormalize the shadow space with modified Gram-Schmidt.
+    let mut p: Vec<_> = (0..s).map(|_| space.create_element()).collect();
+    for (k, source) in shadow.iter().enumerate() {
+        let (head, tail) = p.split_at_mut(k);
+        let pk = &mut tail[0];
+        space.fill_inplace(&source.view(), &mut pk.view_mut())?;
+        for pi in head.iter() {
+            let h = space.inner(&pk.view(), &pi.view())?;
+            space.axpy_inplace(-h, &pi.view(), &mut pk.view_mut())?;
+        }
+        let pk_norm = norm(space, &pk.view())?;
+        if pk_norm == RealType::<Space>::zero() {
+            return Err(SparseLinAlgError::OperationFailed(
+                "IDR(s) requires linearly independent shadow vectors".to_string(),
+            ));
+        }
+        space.scale_inplace(Space::F::from_real(pk_norm.recip()), &mut pk.view_mut());
+    }
+
+    let mut r = space.create_element();
+    residual(space, op, b, x, &mut r)?;
+
+    let mut monitor = ConvergenceMonitor::new(options, norm(space, &b.view())?);
+    if let Some(reason) = monitor.check(norm(space, &r.view())?) {
+        return Ok(monitor.finish(reason, 0));
+    }
+
+    let mut g: Vec<_> = (0..s).map(|_| space.create_element()).collect();
+    let mut u: Vec<_> = (0..s).map(|_| space.create_element()).collect();
+    let mut v = space.create_element();
+    let mut t = space.create_element();
+
+    // m[i][k] = (g_k, p_i), which is lower triangular after biorthogonalization.
+    let mut m = vec![vec![zero; s]; s];
+    for (i, row) in m.iter_mut().enumerate() {
+        row[i] = one;
+    }
+    let mut f = vec![zero; s];
+    let mut omega = one;
+
+    let mut iteration = 0;
+    while iteration < options.max_iter {
+        for (fi, pi) in f.iter_mut().zip(p.iter()) {
+            *fi = space.inner(&r.view(), &pi.view())?;
+        }
+
+        for k in 0..s {
+            // Solve the lower triangular system m[k.., k..] c = f[k..].
+            let mut c = vec![zero; s - k];
+            for i in k..s {
+                let mut value = f[i];
+                for l in k..i {
+                    value -= m[i][l] * c[l - k];
+                }
+                c[i - k] = value / m[i][i];
+            }
+
+            // v = r - sum_i c_i g_i
+            space.fill_inplace(&r.view(), &mut v.view_mut())?;
+            for (ci, gi) in c.iter().zip(g.iter().skip(k)) {
+                space.axpy_inplace(-*ci, &gi.view(), &mut v.view_mut())?;
+            }
+
+            // u_k = omega M^{-1} v + sum_i c_i u_i
+            precondition(preconditioner, space, &v, &mut t)?;
+            {
+                let (u_head, u_tail) = u.split_at_mut(k + 1);
+                let uk = &mut u_head[k];
+                space.scale_inplace(c[0], &mut uk.view_mut());
+                for (ci, ui) in c.iter().skip(1).zip(u_tail.iter()) {
+                    space.axpy_inplace(*ci, &ui.view(), &mut uk.view_mut())?;
+                }
+                space.axpy_inplace(omega, &t.view(), &mut uk.view_mut())?;
+            }
+            op.apply(u[k].view(), g[k].view_mut())?;
+
+            // Make g_k orthogonal to p_0, ..., p_{k-1}.
+            {
+                let (g_head, g_tail) = g.split_at_mut(k);
+                let (u_head, u_tail) = u.split_at_mut(k);
+                let (gk, uk) = (&mut g_tail[0], &mut u_tail[0]);
+                for i in 0..k {
+                    let alpha = space.inner(&gk.view(), &p[i].view())? / m[i][i];
+                    space.axpy_inplace(-alpha, &g_head[i].view(), &mut gk.view_mut())?;
+                    space.axpy_inplace(-alpha, &u_head[i].view(), &mut uk.view_mut())?;
+                }
+            }
+            for i in k..s {
+                m[i][k] = space.inner(&g[k].view(), &p[i].view())?;
+            }
+
+            iteration += 1;
+            if m[k][k] == zero {
+                return Err(breakdown(
+                    "IDR(s)",
+                    iteration,
+                    "the new direction is orthogonal to the shadow space",
+                ));
+            }
+
+            let beta = f[k] / m[k][k];
+            space.axpy_inplace(-beta, &g[k].view(), &mut r.view_mut())?;
+            space.axpy_inplace(beta, &u[k].view(), &mut x.view_mut())?;
+            if let Some(reason) = monitor.check(norm(space, &r.view())?) {
+                return Ok(monitor.finish(reason, iteration));
+            }
+            if iteration == options.max_iter {
+                return Ok(monitor.finish(ConvergenceReason::MaxIterations, iteration));
+            }
+
+            for i in k + 1..s {
+                f[i] -= beta * m[i][k];
+            }
+        }
+
+        // The dimension reduction step into the next space.
+        precondition(preconditioner, space, &r, &mut v)?;
+        op.apply(v.view(), t.view_mut())?;
+        omega = stabilization(space, &r, &t)?;
+
+        iteration += 1;
+        if omega == zero {
+            return Err(breakdown(
+                "IDR(s)",
+                iteration,
+                "the stabilization parameter vanishes",
+            ));
+        }
+        space.axpy_inplace(-omega, &t.view(), &mut r.view_mut())?;
+        space.axpy_inplace(omega, &v.view(), &mut x.view_mut())?;
+        if let Some(reason) = monitor.check(norm(space, &r.view())?) {
+            return Ok(monitor.finish(reason, iteration));
+        }
+    }
+
+    Ok(monitor.finish(ConvergenceReason::MaxIterations, iteration))
+}
+
+// The parameter omega that minimizes |r - omega t|. If r and t are close to orthogonal
+// omega is increased, which avoids stagnation for operators with complex eigenvalues.
+fn stabilization<Space: InnerProductSpace>(
+    space: &Space,
+    r: &Space::E<'_>,
+    t: &Space::E<'_>,
+) -> SparseLinAlgResult<Space::F> {
+    let angle: RealType<Space> = NumCast::from(0.7).unwrap();
+    let t_norm = norm(space, &t.view())?;
+    let r_norm = norm(space, &r.view())?;
+    if t_norm == RealType::<Space>::zero() || r_norm == RealType::<Space>::zero() {
+        return Ok(Space::F::zero());
+    }
+    let tr = space.inner(&r.view(), &t.view())?;
+    let rho = tr.abs() / (t_norm * r_norm);
+    let mut omega = tr / Space::F::from_real(t_norm * t_norm);
+    if rho < angle {
+        omega *= Space::F::from_real(angle / rho);
+    }
+    Ok(omega)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::local::indexable_space::{
+        LocalIndexableVectorSpace, LocalIndexableVectorSpaceElement,
+    };
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::test_problems::{convection_diffusion, jacobi, max_residual, rotation};
+    use cauchy::c64;
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::LinearSpace;
+
+    // Deterministic vectors with pseudo-random entries in [-1, 1].
+    fn shadow_space<T: Scalar>(
+        space: &LocalIndexableVectorSpace<T>,
+        s: usize,
+    ) -> Vec<LocalIndexableVectorSpaceElement<'_, T>> {
+        let mut state: u64 = 12345;
+        (0..s)
+            .map(|_| {
+                let mut element = space.create_element();
+                for value in element.view_mut().view_mut().unwrap().iter_mut() {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    let random = (state >> 11) as f64 / (1u64 << 53) as f64;
+                    *value = T::from_real(NumCast::from(2.0 * random - 1.0).unwrap());
+                }
+                element
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_idrs() {
+        let n = 100;
+        let mat = convection_diffusion(n);
+        let prec = jacobi(&mat);
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = (index as f64).sin();
+        }
+        let options = SolverOptions::new(1E-10, 0.0, 300);
+
+        for s in [1, 2, 4, 8] {
+            let shadow = shadow_space(&space, s);
+            for preconditioner in [None, Some(prec_op)] {
+                let mut x = space.create_element();
+                let result =
+                    idrs(&space, &mat, preconditioner, &shadow, &b, &mut x, &options).unwrap();
+
+                assert_eq!(result.reason, ConvergenceReason::RelativeTolerance);
+                assert_eq!(result.residual_history.len(), 1 + result.iterations);
+                let x = x.view().view().unwrap();
+                let b = b.view().view().unwrap();
+                assert!(max_residual(&mat, x.data(), b.data()) < 1E-8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_idrs_complex() {
+        let n = 30;
+        let mut rows = Vec::new();
+        let mut cols = Vec::new();
+        let mut data = Vec::new();
+        for index in 0..n {
+            rows.push(index);
+            cols.push(index);
+            data.push(c64::new(4.0, 1.0));
+            if index + 1 < n {
+                rows.push(index);
+                cols.push(index + 1);
+                data.push(c64::new(-1.0, 1.0));
+            }
+            if index > 0 {
+                rows.push(index);
+                cols.push(index - 1);
+                data.push(c64::new(-2.0, 0.0));
+            }
+        }
+        let mat = CsrMatrix::from_aij((n, n), &rows, &cols, &data).unwrap();
+        let space = LocalIndexableVectorSpace::<c64>::new(n);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = c64::new(1.0, -(index as f64));
+        }
+        let shadow = shadow_space(&space, 4);
+        let mut x = space.create_element();
+        let options = SolverOptions::new(1E-12, 0.0, 200);
+        let result = idrs(&space, &mat, None, &shadow, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+
+        let mut res = b.view().view().unwrap().data().to_vec();
+        mat.matmul(
+            c64::new(-1.0, 0.0),
+            x.view().view().unwrap().data(),
+            c64::new(1.0, 0.0),
+            &mut res,
+        );
+        assert!(res.iter().all(|value| value.norm() < 1E-9));
+    }
+
+    #[test]
+    fn test_idrs_breakdown() {
+        let mat = rotation();
+        let space = LocalIndexableVectorSpace::<f64>::new(2);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut()[0] = 1.0;
+        let mut shadow = space.create_element();
+        shadow.view_mut().view_mut().unwrap().data_mut()[0] = 1.0;
+
+        let mut x = space.create_element();
+        let options = SolverOptions::default();
+        let err = idrs(&space, &mat, None, &[shadow], &b, &mut x, &options).unwrap_err();
+        assert!(matches!(
+            err,
+            SparseLinAlgError::SolverBreakdown { iteration: 1, .. }
+        ));
+
+        let err = idrs(&space, &mat, None, &[], &b, &mut x, &options).unwrap_err();
+        assert!(matches!(err, SparseLinAlgError::OperationFailed(_)));
+    }
+
+    #[test]
+    fn test_idrs_max_iterations() {
+        let n = 100;
+        let mat = convection_diffusion(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let shadow = shadow_space(&space, 3);
+        let mut x = space.create_element();
+
+        // Stop in the middle of the second cycle of s + 1 iterations.
+        let options = SolverOptions::new(1E-14, 0.0, 6);
+        let result = idrs(&space, &mat, None, &shadow, &b, &mut x, &options).unwrap();
+        assert_eq!(result.reason, ConvergenceReason::MaxIterations);
+        assert_eq!(result.iterations, 6);
+        assert_eq!(result.residual_history.len(), 7);
+
+        let x = x.view().view().unwrap();
+        let mut res = vec![1.0; n];
+        mat.matmul(-1.0, x.data(), 1.0, &mut res);
+        let true_norm = res.iter().map(|value| value * value).sum::<f64>().sqrt();
+        assert!((true_norm - result.residual_norm).abs() < 1E-8 * true_norm);
+    }
+}
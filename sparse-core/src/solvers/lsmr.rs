@@ -0,0 +1,467 @@
+//! The least-squares solver LSMR.
+
+use num::{Float, One, Zero};
+use sparse_traits::types::{Scalar, SparseLinAlgResult};
+use sparse_traits::{AsApply, AsApplyAdjoint, Element, InnerProductSpace};
+
+use super::{
+    least_squares_reason, norm, sym_ortho, LeastSquaresOptions, LeastSquaresReason,
+    LeastSquaresResult, RealType,
+};
+
+/// Options specific to LSMR.
+#[derive(Debug, Clone, Default)]
+pub struct LsmrOptions {
+    /// Number of previous vectors `v` against which every new `v` is reorthogonalized.
+    /// Zero disables the local reorthogonalization.
+    pub local_size: usize,
+}
+
+impl LsmrOptions {
+    pub fn new(local_size: usize) -> Self {
+        Self { local_size }
+    }
+}
+
+/// Solve `min |A x - b|^2 + damp^2 |x|^2` with LSMR.
+///
+/// LSMR is based on the Golub-Kahan bidiagonalization of `A` and is analytically
+/// equivalent to MINRES on the normal equations `(A^H A + damp^2 I) x = A^H b`. In
+/// contrast to [lsqr](super::lsqr::lsqr) the norm of `A^H r` decreases monotonically,
+/// so it is safer to stop early. Every iteration applies `A` and its adjoint once.
+/// The operator maps elements of `domain` to elements of `range` and may be rectangular.
+///
+/// On entry `x` contains the initial guess and on exit the approximate solution. LSMR
+/// solves for the correction to the initial guess `x0`, so with a nonzero `x0` the damping
+/// regularizes `x - x0` and the method solves `min |A x - b|^2 + damp^2 |x - x0|^2`.
+/// The stopping criteria are described in [LeastSquaresOptions]. The estimate of
+/// `cond(A)` refers to the 2-norm.
+///
+/// In finite precision the vectors `v` of the bidiagonalization lose their orthogonality,
+/// which delays convergence. With [LsmrOptions::local_size] set, every new `v` is
+/// orthogonalized against that many previous vectors at the cost of storing them.
+pub fn lsmr<Domain, Range, Op>(
+    domain: &Domain,
+    range: &Range,
+    op: &Op,
+    b: &Range::E<'_>,
+    x: &mut Domain::E<'_>,
+    options: &LeastSquaresOptions<RealType<Domain>>,
+    lsmr_options: &LsmrOptions,
+) -> SparseLinAlgResult<LeastSquaresResult<RealType<Domain>>>
+where
+    Domain: InnerProductSpace,
+    Range: InnerProductSpace<F = Domain::F>,
+    Op: AsApply<Domain = Domain, Range = Range> + AsApplyAdjoint + ?Sized,
+{
+    let zero = RealType::<Domain>::zero();
+    let one = RealType::<Domain>::one();
+    let real = |value: RealType<Domain>| Domain::F::from_real(value);
+
+    let mut u = range.create_element();
+    let mut range_tmp = range.create_element();
+    let mut v = domain.create_element();
+    let mut h = domain.create_element();
+    let mut hbar = domain.create_element();
+    let mut domain_tmp = domain.create_element();
+
+    // The previous vectors v for the local reorthogonalization, used as a ring buffer.
+    let mut local: Vec<_> = (0..lsmr_options.local_size)
+        .map(|_| domain.create_element())
+        .collect();
+    let mut local_count = 0;
+
+    let b_norm = norm(range, &b.view())?;
+    if b_norm == zero {
+        domain.scale_inplace(Domain::F::zero(), &mut x.view_mut());
+        return Ok(LeastSquaresResult {
+            reason: LeastSquaresReason::ZeroSolution,
+            iterations: 0,
+            residual_norm: zero,
+            normal_residual_norm: zero,
+            operator_norm: zero,
+            condition_number: zero,
+            solution_norm: zero,
+            residual_history: vec![zero],
+        });
+    }
+
+    // Start the bidiagonalization with beta u = b - A x and alpha v = A^H u.
+    op.apply(x.view(), range_tmp.view_mut())?;
+    range.fill_inplace(&b.view(), &mut u.view_mut())?;
+    range.axpy_inplace(-Domain::F::one(), &range_tmp.view(), &mut u.view_mut())?;
+    let mut beta = norm(range, &u.view())?;
+    let mut alpha = zero;
+    if beta > zero {
+        range.scale_inplace(real(beta.recip()), &mut u.view_mut());
+        op.apply_adjoint(u.view(), v.view_mut())?;
+        alpha = norm(domain, &v.view())?;
+    }
+    if alpha > zero {
+        domain.scale_inplace(real(alpha.recip()), &mut v.view_mut());
+    }
+    domain.fill_inplace(&v.view(), &mut h.view_mut())?;
+
+    let mut zetabar = alpha * beta;
+    let mut alphabar = alpha;
+    let mut rho = one;
+    let mut rhobar = one;
+    let mut cbar = one;
+    let mut sbar = zero;
+
+    // Variables for the estimate of |r|.
+    let mut betadd = beta;
+    let mut betad = zero;
+    let mut rhodold = one;
+    let mut tautildeold = zero;
+    let mut thetatilde = zero;
+    let mut zeta = zero;
+    let mut d = zero;
+
+    // Variables for the estimates of |A| and cond(A).
+    let mut operator_norm_sq = alpha * alpha;
+    let mut operator_norm = alpha;
+    let mut condition_number = one;
+    let mut max_rbar = zero;
+    let mut min_rbar = RealType::<Domain>::max_value();
+
+    let mut residual_norm = beta;
+    let mut normal_residual_norm = alpha * beta;
+    let mut x_norm = norm(domain, &x.view())?;
+    let mut residual_history = vec![beta];
+
+    let mut iterations = 0;
+    let mut reason = if beta == zero {
+        Some(LeastSquaresReason::Compatible)
+    } else if alpha == zero {
+        Some(LeastSquaresReason::LeastSquares)
+    } else if options.max_iter == 0 {
+        Some(LeastSquaresReason::MaxIterations)
+    } else {
+        None
+    };
+
+    while reason.is_none() {
+        iterations += 1;
+
+        // Continue the bidiagonalization.
+        op.apply(v.view(), range_tmp.view_mut())?;
+        range.scale_inplace(real(-alpha), &mut u.view_mut());
+        range.axpy_inplace(Domain::F::one(), &range_tmp.view(), &mut u.view_mut())?;
+        beta = norm(range, &u.view())?;
+        if beta > zero {
+            range.scale_inplace(real(beta.recip()), &mut u.view_mut());
+            if !local.is_empty() {
+                let slot = local_count % local.len();
+                domain.fill_inplace(&v.view(), &mut local[slot].view_mut())?;
+                local_count += 1;
+            }
+            op.apply_adjoint(u.view(), domain_tmp.view_mut())?;
+            domain.scale_inplace(real(-beta), &mut v.view_mut());
+            domain.axpy_inplace(Domain::F::one(), &domain_tmp.view(), &mut v.view_mut())?;
+            for previous in local.iter().take(local_count) {
+                let h = domain.inner(&v.view(), &previous.view())?;
+                domain.axpy_inplace(-h, &previous.view(), &mut v.view_mut())?;
+            }
+            alpha = norm(domain, &v.view())?;
+            if alpha > zero {
+                domain.scale_inplace(real(alpha.recip()), &mut v.view_mut());
+            }
+        }
+
+        // Eliminate the damping parameter.
+        let (chat, shat, alphahat) = sym_ortho(alphabar, options.damp);
+
+        // Turn the lower bidiagonal matrix into an upper bidiagonal matrix R.
+        let rho_old = rho;
+        let (c, s, rho_new) = sym_ortho(alphahat, beta);
+        rho = rho_new;
+        let theta_new = s * alpha;
+        alphabar = c * alpha;
+
+        // Turn R^H into the upper bidiagonal matrix Rbar.
+        let rhobar_old = rhobar;
+        let zeta_old = zeta;
+        let thetabar = sbar * rho;
+        let rho_temp = cbar * rho;
+        let (cbar_new, sbar_new, rhobar_new) = sym_ortho(cbar * rho, theta_new);
+        cbar = cbar_new;
+        sbar = sbar_new;
+        rhobar = rhobar_new;
+        zeta = cbar * zetabar;
+        zetabar = -sbar * zetabar;
+
+        // Update the directions h and hbar and the solution x.
+        domain.scale_inplace(
+            real(-thetabar * rho / (rho_old * rhobar_old)),
+            &mut hbar.view_mut(),
+        );
+        domain.axpy_inplace(Domain::F::one(), &h.view(), &mut hbar.view_mut())?;
+        domain.axpy_inplace(real(zeta / (rho * rhobar)), &hbar.view(), &mut x.view_mut())?;
+        domain.scale_inplace(real(-theta_new / rho), &mut h.view_mut());
+        domain.axpy_inplace(Domain::F::one(), &v.view(), &mut h.view_mut())?;
+
+        // Estimate |r|.
+        let betaacute = chat * betadd;
+        let betacheck = -shat * betadd;
+        let betahat = c * betaacute;
+        betadd = -s * betaacute;
+
+        let thetatilde_old = thetatilde;
+        let (ctilde_old, stilde_old, rhotilde_old) = sym_ortho(rhodold, thetabar);
+        thetatilde = stilde_old * rhobar;
+        rhodold = ctilde_old * rhobar;
+        betad = -stilde_old * betad + ctilde_old * betahat;
+
+        tautildeold = (zeta_old - thetatilde_old * tautildeold) / rhotilde_old;
+        let taud = (zeta - thetatilde * tautildeold) / rhodold;
+        d += betacheck * betacheck;
+        residual_norm = Float::sqrt(d + Float::powi(betad - taud, 2) + betadd * betadd);
+
+        // Estimate |A| and cond(A).
+        operator_norm_sq += beta * beta;
+        operator_norm = Float::sqrt(operator_norm_sq);
+        operator_norm_sq += alpha * alpha;
+
+        max_rbar = Float::max(max_rbar, rhobar_old);
+        if iterations > 1 {
+            min_rbar = Float::min(min_rbar, rhobar_old);
+        }
+        condition_number = Float::max(max_rbar, rho_temp) / Float::min(min_rbar, rho_temp);
+
+        normal_residual_norm = Float::abs(zetabar);
+        x_norm = norm(domain, &x.view())?;
+        residual_history.push(residual_norm);
+
+        reason = least_squares_reason(
+            options,
+            iterations,
+            b_norm,
+            residual_norm,
+            normal_residual_norm,
+            operator_norm,
+            condition_number,
+            x_norm,
+        );
+    }
+
+    Ok(LeastSquaresResult {
+        reason: reason.unwrap(),
+        iterations,
+        residual_norm,
+        normal_residual_norm,
+        operator_norm,
+        condition_number,
+        solution_norm: x_norm,
+        residual_history,
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::local::indexable_space::LocalIndexableVectorSpace;
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::lsqr::lsqr;
+    use crate::solvers::test_problems::{max_normal_residual, max_residual, overdetermined};
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::LinearSpace;
+
+    // The norm of A^H (b - A x).
+    fn normal_residual_norm(mat: &CsrMatrix<f64>, x: &[f64], b: &[f64]) -> f64 {
+        let mut res = b.to_vec();
+        mat.matmul(-1.0, x, 1.0, &mut res);
+        let mut normal_res = vec![0.0; x.len()];
+        mat.matmul_adjoint(1.0, &res, 0.0, &mut normal_res);
+        normal_res
+            .iter()
+            .map(|value| value * value)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    #[test]
+    fn test_lsmr_normal_residual_monotone() {
+        let (m, n) = (40, 12);
+        let mat = overdetermined(m, n);
+        let domain = LocalIndexableVectorSpace::<f64>::new(n);
+        let range = LocalIndexableVectorSpace::<f64>::new(m);
+
+        let mut b = range.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = ((5 * index) % 7) as f64 - 3.0;
+        }
+        let b_view = b.view().view().unwrap();
+        let b_data = b_view.data();
+
+        // Restart both solvers with an increasing number of iterations. LSMR minimizes
+        // |A^H r| over the same Krylov space in which LSQR minimizes |r|.
+        let mut options = LeastSquaresOptions::new(0.0, 0.0, 0.0, 0.0, 0);
+        let mut previous = f64::INFINITY;
+        for iterations in 0..n {
+            options.max_iter = iterations;
+            let mut x = domain.create_element();
+            let result = lsmr(
+                &domain,
+                &range,
+                &mat,
+                &b,
+                &mut x,
+                &options,
+                &LsmrOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(result.iterations, iterations);
+            let lsmr_norm = normal_residual_norm(&mat, x.view().view().unwrap().data(), b_data);
+            assert!((result.normal_residual_norm - lsmr_norm).abs() < 1E-10);
+            assert!(lsmr_norm <= previous);
+            previous = lsmr_norm;
+
+            let mut x = domain.create_element();
+            lsqr(&domain, &range, &mat, &b, &mut x, &options).unwrap();
+            let lsqr_norm = normal_residual_norm(&mat, x.view().view().unwrap().data(), b_data);
+            assert!(lsmr_norm <= lsqr_norm * (1.0 + 1E-12));
+        }
+    }
+
+    #[test]
+    fn test_lsmr_local_reorthogonalization() {
+        // A diagonal matrix with condition number 1E4 and distinct singular values.
+        let n = 40;
+        let diag: Vec<usize> = (0..n).collect();
+        let data: Vec<f64> = (0..n)
+            .map(|index| f64::powf(1E4, index as f64 / (n - 1) as f64))
+            .collect();
+        let mat = CsrMatrix::from_aij((n, n), &diag, &diag, &data).unwrap();
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+
+        let options = LeastSquaresOptions::new(0.0, 1E-12, 1E-12, 0.0, 1000);
+        let solve = |local_size| {
+            let mut x = space.create_element();
+            let result = lsmr(
+                &space,
+                &space,
+                &mat,
+                &b,
+                &mut x,
+                &options,
+                &LsmrOptions::new(local_size),
+            )
+            .unwrap();
+            assert!(result.converged());
+            let error = max_residual(&mat, x.view().view().unwrap().data(), &vec![1.0; n]);
+            (result.iterations, error)
+        };
+
+        // Without reorthogonalization the loss of orthogonality delays convergence well
+        // beyond n iterations. Reorthogonalizing against all previous vectors restores
+        // the termination after n iterations of exact arithmetic.
+        let (plain_iterations, plain_error) = solve(0);
+        assert!(plain_iterations > 2 * n);
+        assert!(plain_error < 1E-6);
+        let (full_iterations, full_error) = solve(n);
+        assert!(full_iterations <= n);
+        assert!(full_error < 1E-12);
+
+        // A shorter history, which wraps around, still converges.
+        let (local_iterations, local_error) = solve(20);
+        assert!(local_iterations < plain_iterations);
+        assert!(local_error < 1E-6);
+    }
+
+    #[test]
+    fn test_lsmr_damped_initial_guess() {
+        let (m, n) = (25, 8);
+        let mat = overdetermined(m, n);
+        let domain = LocalIndexableVectorSpace::<f64>::new(n);
+        let range = LocalIndexableVectorSpace::<f64>::new(m);
+
+        let mut b = range.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = 1.0 + (index % 3) as f64;
+        }
+
+        let damp = 2.0;
+        let x0: Vec<f64> = (0..n).map(|index| index as f64 - 3.0).collect();
+        let mut x = domain.create_element();
+        x.view_mut()
+            .view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&x0);
+        let options = LeastSquaresOptions::new(damp, 1E-12, 1E-12, 1E8, 100);
+        let result = lsmr(
+            &domain,
+            &range,
+            &mat,
+            &b,
+            &mut x,
+            &options,
+            &LsmrOptions::default(),
+        )
+        .unwrap();
+        assert!(result.converged());
+
+        // The correction d = x - x0 solves the damped problem for b - A x0.
+        let x_view = x.view().view().unwrap();
+        let correction: Vec<f64> = x_view
+            .iter()
+            .zip(x0.iter())
+            .map(|(value, initial)| value - initial)
+            .collect();
+        let mut shifted = b.view().view().unwrap().data().to_vec();
+        mat.matmul(-1.0, &x0, 1.0, &mut shifted);
+        assert!(max_normal_residual(&mat, &correction, &shifted, damp) < 1E-9);
+        assert!(
+            max_normal_residual(&mat, x_view.data(), b.view().view().unwrap().data(), damp) > 1E-3
+        );
+    }
+
+    #[test]
+    fn test_lsmr_agrees_with_lsqr() {
+        let (m, n) = (50, 15);
+        let mat = overdetermined(m, n);
+        let domain = LocalIndexableVectorSpace::<f64>::new(n);
+        let range = LocalIndexableVectorSpace::<f64>::new(m);
+
+        let mut b = range.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = ((3 * index) % 11) as f64 - 5.0;
+        }
+
+        let options = LeastSquaresOptions::new(0.5, 1E-12, 1E-12, 1E8, 200);
+        let mut x_lsmr = domain.create_element();
+        let lsmr_result = lsmr(
+            &domain,
+            &range,
+            &mat,
+            &b,
+            &mut x_lsmr,
+            &options,
+            &LsmrOptions::default(),
+        )
+        .unwrap();
+        let mut x_lsqr = domain.create_element();
+        let lsqr_result = lsqr(&domain, &range, &mat, &b, &mut x_lsqr, &options).unwrap();
+
+        assert!(lsmr_result.converged());
+        assert!(lsqr_result.converged());
+        assert!(
+            (lsmr_result.residual_norm - lsqr_result.residual_norm).abs()
+                < 1E-8 * lsqr_result.residual_norm
+        );
+        for (lsmr_value, lsqr_value) in x_lsmr
+            .view()
+            .view()
+            .unwrap()
+            .iter()
+            .zip(x_lsqr.view().view().unwrap().iter())
+        {
+            assert!((lsmr_value - lsqr_value).abs() < 1E-8);
+        }
+    }
+}
@@ -0,0 +1,410 @@
+//! The least-squares solver LSQR.
+
+use num::{Float, One, Zero};
+use sparse_traits::types::{Scalar, SparseLinAlgResult};
+use sparse_traits::{AsApply, AsApplyAdjoint, Element, InnerProductSpace};
+
+use super::{
+    least_squares_reason, norm, sym_ortho, LeastSquaresOptions, LeastSquaresReason,
+    LeastSquaresResult, RealType,
+};
+
+/// Solve `min |A x - b|^2 + damp^2 |x|^2` with LSQR.
+///
+/// LSQR is based on the Golub-Kahan bidiagonalization of `A` and is analytically
+/// equivalent to CG on the normal equations `(A^H A + damp^2 I) x = A^H b`, but has
+/// better numerical properties. Every iteration applies `A` and its adjoint once.
+/// The operator maps elements of `domain` to elements of `range` and may be rectangular.
+///
+/// On entry `x` contains the initial guess and on exit the approximate solution. As in
+/// [lsmr](super::lsmr::lsmr), a nonzero initial guess `x0` makes the damping regularize
+/// `x - x0`. The stopping criteria are described in [LeastSquaresOptions].
+pub fn lsqr<Domain, Range, Op>(
+    domain: &Domain,
+    range: &Range,
+    op: &Op,
+    b: &Range::E<'_>,
+    x: &mut Domain::E<'_>,
+    options: &LeastSquaresOptions<RealType<Domain>>,
+) -> SparseLinAlgResult<LeastSquaresResult<RealType<Domain>>>
+where
+    Domain: InnerProductSpace,
+    Range: InnerProductSpace<F = Domain::F>,
+    Op: AsApply<Domain = Domain, Range = Range> + AsApplyAdjoint + ?Sized,
+{
+    let zero = RealType::<Domain>::zero();
+    let one = RealType::<Domain>::one();
+    let real = |value: RealType<Domain>| Domain::F::from_real(value);
+
+    let mut u = range.create_element();
+    let mut range_tmp = range.create_element();
+    let mut v = domain.create_element();
+    let mut w = domain.create_element();
+    let mut domain_tmp = domain.create_element();
+
+    let b_norm = norm(range, &b.view())?;
+    if b_norm == zero {
+        domain.scale_inplace(Domain::F::zero(), &mut x.view_mut());
+        return Ok(LeastSquaresResult {
+            reason: LeastSquaresReason::ZeroSolution,
+            iterations: 0,
+            residual_norm: zero,
+            normal_residual_norm: zero,
+            operator_norm: zero,
+            condition_number: zero,
+            solution_norm: zero,
+            residual_history: vec![zero],
+        });
+    }
+
+    // Start the bidiagonalization with beta u = b - A x and alpha v = A^H u.
+    op.apply(x.view(), range_tmp.view_mut())?;
+    range.fill_inplace(&b.view(), &mut u.view_mut())?;
+    range.axpy_inplace(-Domain::F::one(), &range_tmp.view(), &mut u.view_mut())?;
+    let mut beta = norm(range, &u.view())?;
+    let mut alpha = zero;
+    if beta > zero {
+        range.scale_inplace(real(beta.recip()), &mut u.view_mut());
+        op.apply_adjoint(u.view(), v.view_mut())?;
+        alpha = norm(domain, &v.view())?;
+    }
+    if alpha > zero {
+        domain.scale_inplace(real(alpha.recip()), &mut v.view_mut());
+    }
+    domain.fill_inplace(&v.view(), &mut w.view_mut())?;
+
+    let damp_sq = options.damp * options.damp;
+    let mut rhobar = alpha;
+    let mut phibar = beta;
+    let mut residual_norm = beta;
+    let mut normal_residual_norm = alpha * beta;
+    let mut operator_norm = zero;
+    let mut condition_number = zero;
+    let mut dd_norm = zero;
+    let mut res2 = zero;
+    let mut x_norm = zero;
+    let mut xx_norm = zero;
+    let mut z = zero;
+    let mut cs2 = -one;
+    let mut sn2 = zero;
+    let mut residual_history = vec![beta];
+
+    let mut iterations = 0;
+    let mut reason = if beta == zero {
+        Some(LeastSquaresReason::Compatible)
+    } else if alpha == zero {
+        Some(LeastSquaresReason::LeastSquares)
+    } else if options.max_iter == 0 {
+        Some(LeastSquaresReason::MaxIterations)
+    } else {
+        None
+    };
+
+    while reason.is_none() {
+        iterations += 1;
+
+        // Continue the bidiagonalization.
+        op.apply(v.view(), range_tmp.view_mut())?;
+        range.scale_inplace(real(-alpha), &mut u.view_mut());
+        range.axpy_inplace(Domain::F::one(), &range_tmp.view(), &mut u.view_mut())?;
+        beta = norm(range, &u.view())?;
+        if beta > zero {
+            range.scale_inplace(real(beta.recip()), &mut u.view_mut());
+            operator_norm =
+                Float::sqrt(operator_norm * operator_norm + alpha * alpha + beta * beta + damp_sq);
+            op.apply_adjoint(u.view(), domain_tmp.view_mut())?;
+            domain.scale_inplace(real(-beta), &mut v.view_mut());
+            domain.axpy_inplace(Domain::F::one(), &domain_tmp.view(), &mut v.view_mut())?;
+            alpha = norm(domain, &v.view())?;
+            if alpha > zero {
+                domain.scale_inplace(real(alpha.recip()), &mut v.view_mut());
+            }
+        }
+
+        // Eliminate the damping parameter.
+        let (rhobar1, psi) = if options.damp > zero {
+            let rhobar1 = Float::hypot(rhobar, options.damp);
+            let cs1 = rhobar / rhobar1;
+            let sn1 = options.damp / rhobar1;
+            let psi = sn1 * phibar;
+            phibar = cs1 * phibar;
+            (rhobar1, psi)
+        } else {
+            (rhobar, zero)
+        };
+
+        // Eliminate the subdiagonal element beta of the lower bidiagonal matrix.
+        let (cs, sn, rho) = sym_ortho(rhobar1, beta);
+        let theta = sn * alpha;
+        rhobar = -cs * alpha;
+        let phi = cs * phibar;
+        phibar = sn * phibar;
+        let tau = sn * phi;
+
+        // Update x and the search direction w.
+        dd_norm += Float::powi(norm(domain, &w.view())? / rho, 2);
+        domain.axpy_inplace(real(phi / rho), &w.view(), &mut x.view_mut())?;
+        domain.scale_inplace(real(-theta / rho), &mut w.view_mut());
+        domain.axpy_inplace(Domain::F::one(), &v.view(), &mut w.view_mut())?;
+
+        // Estimate the norm of x with a rotation on the upper bidiagonal matrix.
+        let delta = sn2 * rho;
+        let gambar = -cs2 * rho;
+        let rhs = phi - delta * z;
+        let zbar = rhs / gambar;
+        x_norm = Float::sqrt(xx_norm + zbar * zbar);
+        let gamma = Float::hypot(gambar, theta);
+        cs2 = gambar / gamma;
+        sn2 = theta / gamma;
+        z = rhs / gamma;
+        xx_norm += z * z;
+
+        condition_number = operator_norm * Float::sqrt(dd_norm);
+        res2 += psi * psi;
+        residual_norm = Float::sqrt(phibar * phibar + res2);
+        normal_residual_norm = alpha * Float::abs(tau);
+        residual_history.push(residual_norm);
+
+        reason = least_squares_reason(
+            options,
+            iterations,
+            b_norm,
+            residual_norm,
+            normal_residual_norm,
+            operator_norm,
+            condition_number,
+            x_norm,
+        );
+    }
+
+    Ok(LeastSquaresResult {
+        reason: reason.unwrap(),
+        iterations,
+        residual_norm,
+        normal_residual_norm,
+        operator_norm,
+        condition_number,
+        solution_norm: x_norm,
+        residual_history,
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::local::indexable_space::LocalIndexableVectorSpace;
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::test_problems::{max_normal_residual, max_residual, overdetermined};
+    use cauchy::c64;
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::LinearSpace;
+
+    #[test]
+    fn test_lsqr_compatible() {
+        let (m, n) = (30, 10);
+        let mat = overdetermined(m, n);
+        let domain = LocalIndexableVectorSpace::<f64>::new(n);
+        let range = LocalIndexableVectorSpace::<f64>::new(m);
+
+        let expected: Vec<f64> = (0..n).map(|index| 1.0 + index as f64).collect();
+        let mut b = range.create_element();
+        mat.matmul(
+            1.0,
+            &expected,
+            0.0,
+            b.view_mut().view_mut().unwrap().data_mut(),
+        );
+
+        let mut x = domain.create_element();
+        let options = LeastSquaresOptions::new(0.0, 1E-12, 1E-12, 1E8, 100);
+        let result = lsqr(&domain, &range, &mat, &b, &mut x, &options).unwrap();
+
+        assert_eq!(result.reason, LeastSquaresReason::Compatible);
+        assert!(result.converged());
+        assert_eq!(result.residual_history.len(), 1 + result.iterations);
+        for (actual, expected) in x.view().view().unwrap().iter().zip(expected.iter()) {
+            assert!((actual - expected).abs() < 1E-8 * expected);
+        }
+    }
+
+    #[test]
+    fn test_lsqr_least_squares() {
+        let (m, n) = (40, 12);
+        let mat = overdetermined(m, n);
+        let domain = LocalIndexableVectorSpace::<f64>::new(n);
+        let range = LocalIndexableVectorSpace::<f64>::new(m);
+
+        let mut b = range.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = ((5 * index) % 7) as f64 - 3.0;
+        }
+
+        let mut x = domain.create_element();
+        let options = LeastSquaresOptions::new(0.0, 1E-10, 1E-10, 1E8, 100);
+        let result = lsqr(&domain, &range, &mat, &b, &mut x, &options).unwrap();
+
+        assert_eq!(result.reason, LeastSquaresReason::LeastSquares);
+        let x_view = x.view().view().unwrap();
+        let x_data = x_view.data();
+        let b_view = b.view().view().unwrap();
+        let b_data = b_view.data();
+
+        // The system is not compatible and the residual estimate is accurate.
+        let mut res = b_data.to_vec();
+        mat.matmul(-1.0, x_data, 1.0, &mut res);
+        let res_norm = res.iter().map(|value| value * value).sum::<f64>().sqrt();
+        assert!(res_norm > 1.0);
+        assert!((result.residual_norm - res_norm).abs() < 1E-8 * res_norm);
+
+        // The norm of A is estimated from below by the Frobenius norm.
+        let frobenius = mat
+            .data()
+            .iter()
+            .map(|value| value * value)
+            .sum::<f64>()
+            .sqrt();
+        assert!(result.operator_norm <= frobenius * (1.0 + 1E-12));
+        assert!(result.operator_norm > 0.5 * frobenius);
+
+        // A solution that is already optimal stops immediately.
+        let result = lsqr(&domain, &range, &mat, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+        assert!(result.iterations <= 1);
+    }
+
+    #[test]
+    fn test_lsqr_damped() {
+        let (m, n) = (25, 8);
+        let mat = overdetermined(m, n);
+        let domain = LocalIndexableVectorSpace::<f64>::new(n);
+        let range = LocalIndexableVectorSpace::<f64>::new(m);
+
+        let mut b = range.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = 1.0 + (index % 3) as f64;
+        }
+
+        let damp = 2.0;
+        let mut x = domain.create_element();
+        let options = LeastSquaresOptions::new(damp, 1E-12, 1E-12, 1E8, 100);
+        let result = lsqr(&domain, &range, &mat, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+
+        let x_view = x.view().view().unwrap();
+        let x_data = x_view.data();
+        let b_view = b.view().view().unwrap();
+        let b_data = b_view.data();
+        assert!(max_normal_residual(&mat, x_data, b_data, damp) < 1E-9);
+
+        // The residual norm includes the damping term.
+        let mut res = b_data.to_vec();
+        mat.matmul(-1.0, x_data, 1.0, &mut res);
+        let damped_norm = (res.iter().map(|value| value * value).sum::<f64>()
+            + damp * damp * x_data.iter().map(|value| value * value).sum::<f64>())
+        .sqrt();
+        assert!((result.residual_norm - damped_norm).abs() < 1E-8 * damped_norm);
+    }
+
+    #[test]
+    fn test_lsqr_condition_limit() {
+        // A diagonal matrix with condition number 1000.
+        let n = 20;
+        let diag: Vec<usize> = (0..n).collect();
+        let data: Vec<f64> = (0..n)
+            .map(|index| f64::powf(1E3, index as f64 / (n - 1) as f64))
+            .collect();
+        let mat = CsrMatrix::from_aij((n, n), &diag, &diag, &data).unwrap();
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+
+        let mut x = space.create_element();
+        let options = LeastSquaresOptions::new(0.0, 1E-14, 1E-14, 10.0, 100);
+        let result = lsqr(&space, &space, &mat, &b, &mut x, &options).unwrap();
+        assert_eq!(result.reason, LeastSquaresReason::ConditionLimit);
+        assert!(!result.converged());
+        assert!(result.condition_number >= 10.0);
+
+        let mut x = space.create_element();
+        let options = LeastSquaresOptions::new(0.0, 1E-14, 1E-14, 0.0, 200);
+        let result = lsqr(&space, &space, &mat, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+        assert!(max_residual(&mat, x.view().view().unwrap().data(), &vec![1.0; n]) < 1E-10);
+        assert!(result.condition_number > 100.0);
+    }
+
+    #[test]
+    fn test_lsqr_complex() {
+        // The matrix [[1, i], [2, 0], [0, 1 - i]].
+        let data = vec![
+            c64::new(1.0, 0.0),
+            c64::new(0.0, 1.0),
+            c64::new(2.0, 0.0),
+            c64::new(1.0, -1.0),
+        ];
+        let mat = CsrMatrix::from_aij((3, 2), &[0, 0, 1, 2], &[0, 1, 0, 1], &data).unwrap();
+        let domain = LocalIndexableVectorSpace::<c64>::new(2);
+        let range = LocalIndexableVectorSpace::<c64>::new(3);
+
+        let mut b = range.create_element();
+        b.view_mut()
+            .view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&[c64::new(1.0, 1.0), c64::new(0.0, -2.0), c64::new(3.0, 0.0)]);
+
+        let mut x = domain.create_element();
+        let options = LeastSquaresOptions::new(0.0, 1E-12, 1E-12, 1E8, 10);
+        let result = lsqr(&domain, &range, &mat, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+        assert!(result.iterations <= 3);
+
+        let mut res = b.view().view().unwrap().data().to_vec();
+        mat.matmul(
+            c64::new(-1.0, 0.0),
+            x.view().view().unwrap().data(),
+            c64::new(1.0, 0.0),
+            &mut res,
+        );
+        let mut normal_res = vec![c64::new(0.0, 0.0); 2];
+        mat.matmul_adjoint(
+            c64::new(1.0, 0.0),
+            &res,
+            c64::new(0.0, 0.0),
+            &mut normal_res,
+        );
+        assert!(normal_res.iter().all(|value| value.norm() < 1E-10));
+    }
+
+    #[test]
+    fn test_lsqr_zero_rhs_and_max_iterations() {
+        let (m, n) = (30, 10);
+        let mat = overdetermined(m, n);
+        let domain = LocalIndexableVectorSpace::<f64>::new(n);
+        let range = LocalIndexableVectorSpace::<f64>::new(m);
+
+        // A zero right-hand side resets the initial guess.
+        let b = range.create_element();
+        let mut x = domain.create_element();
+        x.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = LeastSquaresOptions::default();
+        let result = lsqr(&domain, &range, &mat, &b, &mut x, &options).unwrap();
+        assert_eq!(result.reason, LeastSquaresReason::ZeroSolution);
+        assert!(result.converged());
+        assert_eq!(result.iterations, 0);
+        assert!(x.view().view().unwrap().iter().all(|value| *value == 0.0));
+
+        let mut b = range.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = (index % 4) as f64;
+        }
+        let options = LeastSquaresOptions::new(0.0, 1E-14, 1E-14, 0.0, 2);
+        let result = lsqr(&domain, &range, &mat, &b, &mut x, &options).unwrap();
+        assert_eq!(result.reason, LeastSquaresReason::MaxIterations);
+        assert!(!result.converged());
+        assert_eq!(result.iterations, 2);
+        assert_eq!(result.residual_history.len(), 3);
+    }
+}
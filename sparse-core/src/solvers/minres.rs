@@ -0,0 +1,378 @@
+//! The minimal residual method MINRES for Hermitian indefinite systems.
+
+use num::{Float, One, Zero};
+use sparse_traits::types::{Scalar, SparseLinAlgResult};
+use sparse_traits::{AsApply, Element, InnerProductSpace, OperatorBase};
+
+use super::{
+    breakdown, precondition, residual, ConvergenceMonitor, ConvergenceReason, RealType,
+    SolverOptions, SolverResult,
+};
+
+/// Solve `A x = b` with the preconditioned minimal residual method MINRES.
+///
+/// The operator `A` must be Hermitian but may be indefinite. The optional `preconditioner`
+/// applies an approximation of `A^{-1}` and must be Hermitian positive definite. With a
+/// preconditioner `M^{-1}` the residual norms, including the norm of `b` for the relative
+/// tolerance, are measured in the norm `|r|_M = sqrt((M^{-1} r, r))` that MINRES minimizes.
+/// On entry `x` contains the initial guess and on exit the approximate solution.
+///
+/// A preconditioner that is not positive definite and a singular tridiagonal Lanczos
+/// matrix, which can only occur if the operator is singular, are reported as a
+/// `SparseLinAlgError::SolverBreakdown`.
+pub fn minres<Space, Op>(
+    space: &Space,
+    op: &Op,
+    preconditioner: Option<&dyn OperatorBase<Domain = Space, Range = Space>>,
+    b: &Space::E<'_>,
+    x: &mut Space::E<'_>,
+    options: &SolverOptions<RealType<Space>>,
+) -> SparseLinAlgResult<SolverResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+{
+    let zero = RealType::<Space>::zero();
+    let real = |value: RealType<Space>| Space::F::from_real(value);
+
+    let mut r1 = space.create_element();
+    let mut r2 = space.create_element();
+    let mut y = space.create_element();
+    let mut v = space.create_element();
+    let mut w = space.create_element();
+    let mut w1 = space.create_element();
+    let mut w2 = space.create_element();
+
+    // The preconditioned norm of b, which is the reference for the relative tolerance.
+    precondition(preconditioner, space, b, &mut y)?;
+    let b_norm = preconditioned_norm(space, b, &y, 0)?;
+
+    residual(space, op, b, x, &mut r1)?;
+    precondition(preconditioner, space, &r1, &mut y)?;
+    let beta1 = preconditioned_norm(space, &r1, &y, 0)?;
+
+    let mut monitor = ConvergenceMonitor::new(options, b_norm);
+    if let Some(reason) = monitor.check(beta1) {
+        return Ok(monitor.finish(reason, 0));
+    }
+    space.fill_inplace(&r1.view(), &mut r2.view_mut())?;
+
+    let mut old_beta = zero;
+    let mut beta = beta1;
+    let mut dbar = zero;
+    let mut epsilon = zero;
+    let mut phibar = beta1;
+    let mut cs = -RealType::<Space>::one();
+    let mut sn = zero;
+
+    for iteration in 1..=options.max_iter {
+        // The Lanczos step for the preconditioned operator.
+        space.fill_inplace(&y.view(), &mut v.view_mut())?;
+        space.scale_inplace(real(beta.recip()), &mut v.view_mut());
+        op.apply(v.view(), y.view_mut())?;
+        if iteration >= 2 {
+            space.axpy_inplace(real(-beta / old_beta), &r1.view(), &mut y.view_mut())?;
+        }
+        let alpha = space.inner(&y.view(), &v.view())?.re();
+        space.axpy_inplace(real(-alpha / beta), &r2.view(), &mut y.view_mut())?;
+        // r1 = r2, r2 = y, and y is overwritten by the preconditioned r2.
+        std::mem::swap(&mut r1, &mut r2);
+        std::mem::swap(&mut r2, &mut y);
+        precondition(preconditioner, space, &r2, &mut y)?;
+        old_beta = beta;
+        beta = preconditioned_norm(space, &r2, &y, iteration)?;
+
+        // Apply the previous rotation and compute the next one.
+        let old_epsilon = epsilon;
+        let delta = cs * dbar + sn * alpha;
+        let gbar = sn * dbar - cs * alpha;
+        epsilon = sn * beta;
+        dbar = -cs * beta;
+        let gamma = Float::hypot(gbar, beta);
+        if gamma == zero {
+            return Err(breakdown(
+                "MINRES",
+                iteration,
+                "the Lanczos matrix is singular",
+            ));
+        }
+        cs = gbar / gamma;
+        sn = beta / gamma;
+        let phi = cs * phibar;
+        phibar = sn * phibar;
+
+        // w = (v - old_epsilon * w1 - delta * w2) / gamma with the previous directions.
+        std::mem::swap(&mut w1, &mut w2);
+        std::mem::swap(&mut w2, &mut w);
+        space.fill_inplace(&v.view(), &mut w.view_mut())?;
+        space.axpy_inplace(real(-old_epsilon), &w1.view(), &mut w.view_mut())?;
+        space.axpy_inplace(real(-delta), &w2.view(), &mut w.view_mut())?;
+        space.scale_inplace(real(gamma.recip()), &mut w.view_mut());
+        space.axpy_inplace(real(phi), &w.view(), &mut x.view_mut())?;
+
+        if let Some(reason) = monitor.check(phibar) {
+            return Ok(monitor.finish(reason, iteration));
+        }
+    }
+
+    Ok(monitor.finish(ConvergenceReason::MaxIterations, options.max_iter))
+}
+
+// The norm sqrt((M^{-1} r, r)) from r and z = M^{-1} r.
+fn preconditioned_norm<Space: InnerProductSpace>(
+    space: &Space,
+    r: &Space::E<'_>,
+    z: &Space::E<'_>,
+    iteration: usize,
+) -> SparseLinAlgResult<RealType<Space>> {
+    let value = space.inner(&z.view(), &r.view())?.re();
+    if value < RealType::<Space>::zero() {
+        return Err(breakdown(
+            "MINRES",
+            iteration,
+            "the preconditioner is not positive definite",
+        ));
+    }
+    Ok(Float::sqrt(value))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::local::indexable_space::LocalIndexableVectorSpace;
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::cg::cg;
+    use crate::solvers::test_problems::{
+        jacobi, laplace_1d, max_residual, overdetermined, tridiagonal,
+    };
+    use cauchy::c64;
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::types::SparseLinAlgError;
+    use sparse_traits::{
+        AdjointOperator, BlockOperator, BoxedOperator, IdentityOperator, LinearSpace, ProductSpace,
+    };
+
+    #[test]
+    fn test_minres_against_cg() {
+        let n = 50;
+        let mat = laplace_1d(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = (index as f64).sin();
+        }
+        let options = SolverOptions::new(1E-12, 0.0, 200);
+
+        let mut x_cg = space.create_element();
+        let cg_result = cg(&space, &mat, None, &b, &mut x_cg, &options).unwrap();
+        let mut x = space.create_element();
+        let result = minres(&space, &mat, None, &b, &mut x, &options).unwrap();
+
+        assert_eq!(result.reason, ConvergenceReason::RelativeTolerance);
+        assert_eq!(result.residual_history.len(), 1 + result.iterations);
+        // Both methods terminate after at most n steps in exact arithmetic.
+        assert!(result.iterations <= n && cg_result.iterations <= n);
+        for (actual, expected) in x
+            .view()
+            .view()
+            .unwrap()
+            .iter()
+            .zip(x_cg.view().view().unwrap().iter())
+        {
+            assert!((actual - expected).abs() < 1E-8 * expected.abs().max(1.0));
+        }
+    }
+
+    #[test]
+    fn test_minres_indefinite() {
+        // The shifted Laplacian L - 1.5 I has eigenvalues on both sides of zero.
+        let n = 50;
+        let mat = tridiagonal(&vec![0.5; n]);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let mut x = space.create_element();
+
+        let options = SolverOptions::new(1E-10, 0.0, 500);
+        let result = minres(&space, &mat, None, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+        // The MINRES residual norm is nonincreasing.
+        for pair in result.residual_history.windows(2) {
+            assert!(pair[1] <= pair[0] * (1.0 + 1E-12));
+        }
+
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(&mat, x.data(), b.data()) < 1E-8);
+    }
+
+    #[test]
+    fn test_minres_preconditioned() {
+        // An indefinite matrix with a strongly varying diagonal, preconditioned with the
+        // inverse of the absolute value of the diagonal.
+        let n = 100;
+        let diagonal: Vec<f64> = (0..n)
+            .map(|index| {
+                let value = 1.0 + (index % 10) as f64 * 10.0;
+                if index % 3 == 0 {
+                    -value
+                } else {
+                    value
+                }
+            })
+            .collect();
+        let mat = tridiagonal(&diagonal);
+        let rows: Vec<usize> = (0..n).collect();
+        let inv_abs_diag: Vec<f64> = diagonal.iter().map(|value| 1.0 / value.abs()).collect();
+        let prec = CsrMatrix::from_aij((n, n), &rows, &rows, &inv_abs_diag).unwrap();
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let options = SolverOptions::new(1E-10, 0.0, 500);
+
+        let mut x = space.create_element();
+        let plain = minres(&space, &mat, None, &b, &mut x, &options).unwrap();
+        let mut x = space.create_element();
+        let preconditioned = minres(&space, &mat, Some(prec_op), &b, &mut x, &options).unwrap();
+
+        assert!(plain.converged());
+        assert!(preconditioned.converged());
+        assert!(preconditioned.iterations < plain.iterations);
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(&mat, x.data(), b.data()) < 1E-7);
+    }
+
+    #[test]
+    fn test_minres_complex_hermitian() {
+        // The Hermitian indefinite matrix [[1, 2i, 0], [-2i, -1, 1], [0, 1, 2]].
+        let mat = CsrMatrix::from_aij(
+            (3, 3),
+            &[0, 0, 1, 1, 1, 2, 2],
+            &[0, 1, 0, 1, 2, 1, 2],
+            &[
+                c64::new(1.0, 0.0),
+                c64::new(0.0, 2.0),
+                c64::new(0.0, -2.0),
+                c64::new(-1.0, 0.0),
+                c64::new(1.0, 0.0),
+                c64::new(1.0, 0.0),
+                c64::new(2.0, 0.0),
+            ],
+        )
+        .unwrap();
+        let space = LocalIndexableVectorSpace::<c64>::new(3);
+
+        let mut b = space.create_element();
+        b.view_mut()
+            .view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&[c64::new(1.0, 0.0), c64::new(0.0, 1.0), c64::new(2.0, -1.0)]);
+        let mut x = space.create_element();
+
+        let options = SolverOptions::new(1E-12, 0.0, 10);
+        let result = minres(&space, &mat, None, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+        assert!(result.iterations <= 4);
+
+        let mut res = b.view().view().unwrap().data().to_vec();
+        mat.matmul(
+            c64::new(-1.0, 0.0),
+            x.view().view().unwrap().data(),
+            c64::new(1.0, 0.0),
+            &mut res,
+        );
+        assert!(res.iter().all(|value| value.norm() < 1E-10));
+    }
+
+    #[test]
+    fn test_minres_indefinite_preconditioner() {
+        let n = 10;
+        let mat = laplace_1d(n);
+        let rows: Vec<usize> = (0..n).collect();
+        let prec = CsrMatrix::from_aij((n, n), &rows, &rows, &vec![-1.0; n]).unwrap();
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let mut x = space.create_element();
+
+        let options = SolverOptions::default();
+        let err = minres(&space, &mat, Some(prec_op), &b, &mut x, &options).unwrap_err();
+        assert!(matches!(err, SparseLinAlgError::SolverBreakdown { .. }));
+    }
+
+    #[test]
+    fn test_minres_singular() {
+        // The Krylov space of diag(1, 0) and b = e_2 is spanned by b, which is mapped to zero.
+        let mat = CsrMatrix::from_aij((2, 2), &[0, 1], &[0, 1], &[1.0, 0.0]).unwrap();
+        let space = LocalIndexableVectorSpace::<f64>::new(2);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut()[1] = 1.0;
+        let mut x = space.create_element();
+
+        let options = SolverOptions::default();
+        let err = minres(&space, &mat, None, &b, &mut x, &options).unwrap_err();
+        assert!(matches!(
+            err,
+            SparseLinAlgError::SolverBreakdown { iteration: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_minres_saddle_point() {
+        // The saddle point system [A, B^T; B, 0] with the 1D Laplacian A and a B of full
+        // rank, preconditioned with the block diagonal diag(diag(A)^{-1}, I).
+        let (n, m) = (30, 10);
+        let a = laplace_1d(n);
+        let bt = overdetermined(n, m);
+        let velocity = LocalIndexableVectorSpace::<f64>::new(n);
+        let pressure = LocalIndexableVectorSpace::<f64>::new(m);
+        let space = ProductSpace::new(vec![velocity, pressure.clone()]);
+        let op = BlockOperator::new(
+            space.clone(),
+            space.clone(),
+            vec![
+                vec![
+                    Some(Box::new(&a) as BoxedOperator<_, _>),
+                    Some(Box::new(&bt)),
+                ],
+                vec![Some(Box::new(AdjointOperator::new(&bt))), None],
+            ],
+        )
+        .unwrap();
+        let jacobi_a = jacobi(&a);
+        let prec = BlockOperator::block_diagonal(vec![
+            Box::new(&jacobi_a) as BoxedOperator<_, _>,
+            Box::new(IdentityOperator::new(pressure)),
+        ]);
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+
+        let mut b = space.create_element();
+        b.view_mut()[0].view_mut().unwrap().data_mut().fill(1.0);
+        for (index, value) in b.view_mut()[1].view_mut().unwrap().iter_mut().enumerate() {
+            *value = index as f64;
+        }
+        let options = SolverOptions::new(1E-10, 0.0, 200);
+        let mut x = space.create_element();
+        let result = minres(&space, &op, Some(prec_op), &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+
+        let mut res = space.create_element();
+        op.apply(x.view(), res.view_mut()).unwrap();
+        space
+            .axpy_inplace(-1.0, &b.view(), &mut res.view_mut())
+            .unwrap();
+        let res_norm = space.inner(&res.view(), &res.view()).unwrap().sqrt();
+        let b_norm = space.inner(&b.view(), &b.view()).unwrap().sqrt();
+        assert!(res_norm < 1E-8 * b_norm);
+    }
+}
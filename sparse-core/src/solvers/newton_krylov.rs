@@ -0,0 +1,652 @@
+//! The Jacobian-free Newton-Krylov method for nonlinear systems `F(x) = 0`.
+//!
+//! Each Newton step solves `J(x) s = -F(x)` with GMRES, where the Jacobian is never
+//! assembled. Its action is approximated by the finite difference
+//! `J(x) v = (F(x + h v) - F(x)) / h` of [FiniteDifferenceJacobian], so that only the
+//! residual function has to be provided.
+
+use std::cell::Cell;
+use std::fmt::Debug;
+
+use num::{Float, NumCast, One, Zero};
+use sparse_traits::types::{Scalar, SparseLinAlgResult};
+use sparse_traits::{
+    AsApply, Element, ElementView, ElementViewMut, InnerProductSpace, LinearSpace, OperatorBase,
+};
+
+use super::gmres::{gmres, GmresOptions};
+use super::{breakdown, norm, ConvergenceMonitor, ConvergenceReason, RealType, SolverOptions};
+
+/// The choice of the relative tolerances `eta_k` of the linear solves.
+#[derive(Debug, Clone, Copy)]
+pub enum ForcingTerm<R: Float> {
+    /// Solve every Newton step to the same relative tolerance.
+    Constant(R),
+    /// Choice 2 of Eisenstat and Walker, `eta_k = gamma (|F(x_k)| / |F(x_{k-1})|)^alpha`.
+    ///
+    /// The tolerances are safeguarded against decreasing too fast and bounded by `max`.
+    /// Near the solution they are kept large enough to not solve beyond the nonlinear
+    /// tolerance. Loose linear solves far from the solution and tight ones close to it
+    /// retain the local convergence of Newton's method at a fraction of the cost.
+    EisenstatWalker {
+        /// The tolerance of the first step.
+        initial: R,
+        /// The upper bound of the tolerances.
+        max: R,
+        gamma: R,
+        alpha: R,
+    },
+}
+
+impl<R: Float> ForcingTerm<R> {
+    /// The Eisenstat-Walker forcing terms with `initial = 0.5`, `max = 0.9`, `gamma = 0.9`
+    /// and `alpha = 2`.
+    pub fn eisenstat_walker() -> Self {
+        ForcingTerm::EisenstatWalker {
+            initial: R::from(0.5).unwrap(),
+            max: R::from(0.9).unwrap(),
+            gamma: R::from(0.9).unwrap(),
+            alpha: R::from(2.0).unwrap(),
+        }
+    }
+}
+
+/// The globalization of the Newton steps.
+#[derive(Debug, Clone, Copy)]
+pub enum LineSearch<R: Float> {
+    /// Always take the full Newton step.
+    Full,
+    /// Backtrack along the Newton direction until `|F(x + lambda s)| <= (1 - c lambda) |F(x)|`
+    /// with `c = sufficient_decrease`.
+    ///
+    /// The step length `lambda` is the minimizer of a parabola that models
+    /// `|F(x + lambda s)|^2`, restricted to between a tenth and a half of the previous
+    /// step length.
+    Backtracking {
+        sufficient_decrease: R,
+        /// The maximum number of reductions of the step length.
+        max_steps: usize,
+    },
+}
+
+/// Options of the Newton-Krylov method.
+///
+/// The method stops if `|F(x)| <= max(rel_tol * |F(x_0)|, abs_tol)`.
+#[derive(Debug, Clone)]
+pub struct NewtonKrylovOptions<R: Float> {
+    /// Tolerance relative to the norm of the initial residual.
+    pub rel_tol: R,
+    /// Absolute tolerance for the residual norm.
+    pub abs_tol: R,
+    /// Maximum number of Newton steps.
+    pub max_iter: usize,
+    pub forcing: ForcingTerm<R>,
+    pub line_search: LineSearch<R>,
+    /// Maximum number of GMRES iterations per Newton step.
+    pub max_linear_iter: usize,
+    pub gmres_options: GmresOptions,
+    /// The relative size of the finite difference step, see [FiniteDifferenceJacobian].
+    pub difference_step: R,
+}
+
+impl<R: Float> NewtonKrylovOptions<R> {
+    /// Create options with Eisenstat-Walker forcing terms, a backtracking line search with
+    /// `c = 1E-4` and at most 20 reductions, at most 100 GMRES iterations per step with
+    /// the default [GmresOptions] and a difference step of the square root of the machine
+    /// precision.
+    pub fn new(rel_tol: R, abs_tol: R, max_iter: usize) -> Self {
+        Self {
+            rel_tol,
+            abs_tol,
+            max_iter,
+            forcing: ForcingTerm::eisenstat_walker(),
+            line_search: LineSearch::Backtracking {
+                sufficient_decrease: R::from(1E-4).unwrap(),
+                max_steps: 20,
+            },
+            max_linear_iter: 100,
+            gmres_options: GmresOptions::default(),
+            difference_step: Float::sqrt(R::epsilon()),
+        }
+    }
+}
+
+impl<R: Float> Default for NewtonKrylovOptions<R> {
+    /// A relative tolerance of `1E-8`, no absolute tolerance and at most 50 Newton steps.
+    fn default() -> Self {
+        Self::new(R::from(1E-8).unwrap(), R::zero(), 50)
+    }
+}
+
+/// Summary of a run of the Newton-Krylov method.
+#[derive(Debug, Clone)]
+pub struct NewtonKrylovResult<R: Float> {
+    pub reason: ConvergenceReason,
+    /// Number of Newton steps performed.
+    pub iterations: usize,
+    /// Total number of GMRES iterations.
+    pub linear_iterations: usize,
+    /// Total number of evaluations of the residual function.
+    pub function_evaluations: usize,
+    /// Norm of the final residual `F(x)`.
+    pub residual_norm: R,
+    /// Norm of `F(x_0)` followed by the norm of `F(x)` after each Newton step.
+    pub residual_history: Vec<R>,
+}
+
+impl<R: Float> NewtonKrylovResult<R> {
+    /// Return true if one of the tolerances was reached.
+    pub fn converged(&self) -> bool {
+        self.reason != ConvergenceReason::MaxIterations
+    }
+}
+
+/// The finite difference approximation of the Jacobian of `F` at a point `x`.
+///
+/// The operator applies `J v = (F(x + h v) - F(x)) / h` with the step
+/// `h = epsilon sqrt(1 + |x|) / |v|` of Pernice and Walker, which scales with the size
+/// of `x` and keeps the perturbation `h v` at the relative size `epsilon` independently
+/// of the length of `v`. For `epsilon` the square root of the machine precision balances
+/// the truncation and the rounding error if `F` is evaluated to full precision.
+/// Like all operators that are only available through their action it does not support
+/// the transpose or the adjoint.
+pub struct FiniteDifferenceJacobian<'s, Space: LinearSpace, Fun> {
+    space: &'s Space,
+    function: &'s Fun,
+    x: Space::E<'s>,
+    fx: Space::E<'s>,
+    x_norm: RealType<Space>,
+    epsilon: RealType<Space>,
+    evaluations: Cell<usize>,
+}
+
+impl<'s, Space, Fun> FiniteDifferenceJacobian<'s, Space, Fun>
+where
+    Space: InnerProductSpace,
+    Fun: Fn(ElementView<Space>, ElementViewMut<Space>) -> SparseLinAlgResult<()>,
+{
+    /// Create the Jacobian at `x`, where `fx` is `F(x)`. Both are copied.
+    pub fn new(
+        space: &'s Space,
+        function: &'s Fun,
+        x: &Space::E<'_>,
+        fx: &Space::E<'_>,
+        epsilon: RealType<Space>,
+    ) -> SparseLinAlgResult<Self> {
+        let mut x_copy = space.create_element();
+        space.fill_inplace(&x.view(), &mut x_copy.view_mut())?;
+        let mut fx_copy = space.create_element();
+        space.fill_inplace(&fx.view(), &mut fx_copy.view_mut())?;
+        let x_norm = norm(space, &x.view())?;
+        Ok(Self {
+            space,
+            function,
+            x: x_copy,
+            fx: fx_copy,
+            x_norm,
+            epsilon,
+            evaluations: Cell::new(0),
+        })
+    }
+
+    /// The number of evaluations of `F` by applications of the operator.
+    pub fn evaluations(&self) -> usize {
+        self.evaluations.get()
+    }
+}
+
+impl<'s, Space: LinearSpace, Fun> Debug for FiniteDifferenceJacobian<'s, Space, Fun> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FiniteDifferenceJacobian")
+            .field("epsilon", &self.epsilon)
+            .field("evaluations", &self.evaluations.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'s, Space, Fun> OperatorBase for FiniteDifferenceJacobian<'s, Space, Fun>
+where
+    Space: InnerProductSpace,
+    Fun: Fn(ElementView<Space>, ElementViewMut<Space>) -> SparseLinAlgResult<()>,
+{
+    type Domain = Space;
+    type Range = Space;
+
+    fn domain(&self) -> &Self::Domain {
+        self.space
+    }
+
+    fn range(&self) -> &Self::Range {
+        self.space
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<'s, Space, Fun> AsApply for FiniteDifferenceJacobian<'s, Space, Fun>
+where
+    Space: InnerProductSpace,
+    Fun: Fn(ElementView<Space>, ElementViewMut<Space>) -> SparseLinAlgResult<()>,
+{
+    fn apply(&self, v: ElementView<Space>, mut y: ElementViewMut<Space>) -> SparseLinAlgResult<()> {
+        let space = self.space;
+        let v_norm = norm(space, &Space::reborrow_view(&v))?;
+        if v_norm == RealType::<Space>::zero() {
+            let zero = space.create_element();
+            space.fill_inplace(&zero.view(), &mut Space::reborrow_view_mut(&mut y))?;
+            return Ok(());
+        }
+        let h = self.epsilon * Float::sqrt(RealType::<Space>::one() + self.x_norm) / v_norm;
+
+        let mut perturbed = space.create_element();
+        space.fill_inplace(&self.x.view(), &mut perturbed.view_mut())?;
+        space.axpy_inplace(
+            Space::F::from_real(h),
+            &Space::reborrow_view(&v),
+            &mut perturbed.view_mut(),
+        )?;
+        (self.function)(perturbed.view(), Space::reborrow_view_mut(&mut y))?;
+        self.evaluations.set(1 + self.evaluations.get());
+
+        space.axpy_inplace(
+            -Space::F::one(),
+            &self.fx.view(),
+            &mut Space::reborrow_view_mut(&mut y),
+        )?;
+        space.scale_inplace(Space::F::from_real(h.recip()), &mut y);
+        Ok(())
+    }
+}
+
+/// Solve `F(x) = 0` with the Jacobian-free Newton-Krylov method.
+///
+/// `function(x, y)` stores `F(x)` in `y`. Every Newton step solves `J(x) s = -F(x)` with
+/// GMRES to the relative tolerance given by the forcing term, using the finite difference
+/// Jacobian. The optional `preconditioner` approximates `J^{-1}` and is applied from the
+/// right. The step is then globalized with the line search of the options.
+/// On entry `x` contains the initial guess and on exit the approximate solution.
+///
+/// If the line search cannot reduce the residual or GMRES breaks down on a singular
+/// Jacobian, a [SolverBreakdown](sparse_traits::types::SparseLinAlgError::SolverBreakdown)
+/// error is returned and `x` contains the last accepted iterate.
+pub fn newton_krylov<Space, Fun>(
+    space: &Space,
+    function: Fun,
+    preconditioner: Option<&dyn OperatorBase<Domain = Space, Range = Space>>,
+    x: &mut Space::E<'_>,
+    options: &NewtonKrylovOptions<RealType<Space>>,
+) -> SparseLinAlgResult<NewtonKrylovResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Fun: Fn(ElementView<Space>, ElementViewMut<Space>) -> SparseLinAlgResult<()>,
+{
+    let zero = RealType::<Space>::zero();
+    let one = RealType::<Space>::one();
+    let half: RealType<Space> = NumCast::from(0.5).unwrap();
+    let tenth: RealType<Space> = NumCast::from(0.1).unwrap();
+
+    let mut fx = space.create_element();
+    function(x.view(), fx.view_mut())?;
+    let mut function_evaluations = 1;
+    let mut linear_iterations = 0;
+
+    let mut residual_norm = norm(space, &fx.view())?;
+    let mut monitor = ConvergenceMonitor::new(
+        &SolverOptions::new(options.rel_tol, options.abs_tol, options.max_iter),
+        residual_norm,
+    );
+    let target = Float::max(options.rel_tol * residual_norm, options.abs_tol);
+    let finish = |monitor: ConvergenceMonitor<_>,
+                  reason,
+                  iterations,
+                  linear_iterations,
+                  function_evaluations| {
+        let result = monitor.finish(reason, iterations);
+        Ok(NewtonKrylovResult {
+            reason: result.reason,
+            iterations: result.iterations,
+            linear_iterations,
+            function_evaluations,
+            residual_norm: result.residual_norm,
+            residual_history: result.residual_history,
+        })
+    };
+    if let Some(reason) = monitor.check(residual_norm) {
+        return finish(monitor, reason, 0, linear_iterations, function_evaluations);
+    }
+
+    let mut eta = match options.forcing {
+        ForcingTerm::Constant(eta) => eta,
+        ForcingTerm::EisenstatWalker { initial, .. } => initial,
+    };
+    let mut rhs = space.create_element();
+    let mut trial = space.create_element();
+    let mut f_trial = space.create_element();
+
+    for iteration in 1..=options.max_iter {
+        // Solve J s = -F(x) inexactly.
+        let jacobian =
+            FiniteDifferenceJacobian::new(space, &function, x, &fx, options.difference_step)?;
+        space.fill_inplace(&fx.view(), &mut rhs.view_mut())?;
+        space.scale_inplace(-Space::F::one(), &mut rhs.view_mut());
+        let mut step = space.create_element();
+        let linear = gmres(
+            space,
+            &jacobian,
+            preconditioner,
+            &rhs,
+            &mut step,
+            &SolverOptions::new(eta, zero, options.max_linear_iter),
+            &options.gmres_options,
+        )?;
+        linear_iterations += linear.iterations;
+        function_evaluations += jacobian.evaluations();
+
+        // Find the length of the step.
+        let mut lambda = one;
+        let mut reductions = 0;
+        let trial_norm = loop {
+            space.fill_inplace(&x.view(), &mut trial.view_mut())?;
+            space.axpy_inplace(
+                Space::F::from_real(lambda),
+                &step.view(),
+                &mut trial.view_mut(),
+            )?;
+            function(trial.view(), f_trial.view_mut())?;
+            function_evaluations += 1;
+            let trial_norm = norm(space, &f_trial.view())?;
+
+            match options.line_search {
+                LineSearch::Full => break trial_norm,
+                LineSearch::Backtracking {
+                    sufficient_decrease,
+                    max_steps,
+                } => {
+                    if trial_norm <= (one - sufficient_decrease * lambda) * residual_norm {
+                        break trial_norm;
+                    }
+                    if reductions == max_steps {
+                        return Err(breakdown(
+                            "Newton-Krylov",
+                            iteration,
+                            "the line search did not reduce the residual",
+                        ));
+                    }
+                    reductions += 1;
+
+                    // The parabola through |F(x)|^2 with the slope -2 |F(x)|^2 of an exact
+                    // Newton step and through |F(x + lambda s)|^2.
+                    let phi0 = residual_norm * residual_norm;
+                    let curvature = trial_norm * trial_norm - phi0 + (one + one) * phi0 * lambda;
+                    let next = if curvature > zero {
+                        phi0 * lambda * lambda / curvature
+                    } else {
+                        half * lambda
+                    };
+                    lambda = Float::min(Float::max(next, tenth * lambda), half * lambda);
+                }
+            }
+        };
+
+        space.fill_inplace(&trial.view(), &mut x.view_mut())?;
+        std::mem::swap(&mut fx, &mut f_trial);
+        let previous_norm = residual_norm;
+        residual_norm = trial_norm;
+        if let Some(reason) = monitor.check(residual_norm) {
+            return finish(
+                monitor,
+                reason,
+                iteration,
+                linear_iterations,
+                function_evaluations,
+            );
+        }
+
+        if let ForcingTerm::EisenstatWalker {
+            max, gamma, alpha, ..
+        } = options.forcing
+        {
+            let mut next = gamma * Float::powf(residual_norm / previous_norm, alpha);
+            // Do not let the tolerance drop much faster than it did before.
+            let safeguard = gamma * Float::powf(eta, alpha);
+            if safeguard > tenth {
+                next = Float::max(next, safeguard);
+            }
+            // Do not solve more accurately than the nonlinear tolerance requires.
+            next = Float::max(next, half * target / residual_norm);
+            eta = Float::min(next, max);
+        }
+    }
+    finish(
+        monitor,
+        ConvergenceReason::MaxIterations,
+        options.max_iter,
+        linear_iterations,
+        function_evaluations,
+    )
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::local::indexable_space::LocalIndexableVectorSpace;
+    use crate::local::indexable_vector::LocalIndexableVector;
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::test_problems::{jacobi, laplace_1d};
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::types::SparseLinAlgError;
+
+    /// The residual `F(u) = A u + u^3 - 1` of a discretized semilinear equation.
+    fn semilinear(
+        mat: &CsrMatrix<f64>,
+    ) -> impl Fn(&LocalIndexableVector<f64>, &mut LocalIndexableVector<f64>) -> SparseLinAlgResult<()> + '_
+    {
+        move |x, y| {
+            let x = x.view().unwrap();
+            let x = x.data();
+            let mut y = y.view_mut().unwrap();
+            let y = y.data_mut();
+            mat.matmul(1.0, x, 0.0, y);
+            for (out, value) in y.iter_mut().zip(x) {
+                *out += value * value * value - 1.0;
+            }
+            Ok(())
+        }
+    }
+
+    /// Evaluate the residual function and return the largest entry.
+    fn max_function_value<Fun>(
+        function: Fun,
+        space: &LocalIndexableVectorSpace<f64>,
+        x: &LocalIndexableVector<f64>,
+    ) -> f64
+    where
+        Fun: Fn(
+            &LocalIndexableVector<f64>,
+            &mut LocalIndexableVector<f64>,
+        ) -> SparseLinAlgResult<()>,
+    {
+        let mut fx = space.create_element();
+        function(x, fx.view_mut()).unwrap();
+        let fx = fx.view().view().unwrap();
+        fx.data()
+            .iter()
+            .fold(0.0, |acc, value| f64::max(acc, value.abs()))
+    }
+
+    #[test]
+    fn test_finite_difference_jacobian() {
+        let n = 20;
+        let mat = laplace_1d(n);
+        let function = semilinear(&mat);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut x = space.create_element();
+        for (index, value) in x.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = (index as f64).cos();
+        }
+        let mut fx = space.create_element();
+        function(x.view(), fx.view_mut()).unwrap();
+        let jacobian = FiniteDifferenceJacobian::new(&space, &function, &x, &fx, 1E-7).unwrap();
+        assert!(jacobian.has_apply());
+        assert!(!jacobian.has_apply_transpose());
+
+        // Compare with the exact Jacobian A + 3 diag(u^2), also for a long vector.
+        for scale in [1.0, 1E6] {
+            let mut v = space.create_element();
+            for (index, value) in v.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+                *value = scale * (index as f64).sin();
+            }
+            let mut jv = space.create_element();
+            jacobian.apply(v.view(), jv.view_mut()).unwrap();
+
+            let x_view = x.view().view().unwrap();
+            let v_view = v.view().view().unwrap();
+            let mut expected = vec![0.0; n];
+            mat.matmul(1.0, v_view.data(), 0.0, &mut expected);
+            for ((out, x), v) in expected.iter_mut().zip(x_view.data()).zip(v_view.data()) {
+                *out += 3.0 * x * x * v;
+            }
+            let jv_view = jv.view().view().unwrap();
+            for (actual, expected) in jv_view.data().iter().zip(&expected) {
+                assert!((actual - expected).abs() < 1E-5 * scale);
+            }
+        }
+
+        // The zero vector is mapped to zero without evaluating F.
+        let mut jv = space.create_element();
+        jv.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        jacobian
+            .apply(space.create_element().view(), jv.view_mut())
+            .unwrap();
+        assert!(jv
+            .view()
+            .view()
+            .unwrap()
+            .data()
+            .iter()
+            .all(|&value| value == 0.0));
+        assert_eq!(jacobian.evaluations(), 2);
+    }
+
+    #[test]
+    fn test_newton_krylov_forcing_terms() {
+        let n = 50;
+        let mat = laplace_1d(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut options = NewtonKrylovOptions::new(1E-10, 0.0, 30);
+        let mut x = space.create_element();
+        let adaptive = newton_krylov(&space, semilinear(&mat), None, &mut x, &options).unwrap();
+        assert!(adaptive.converged());
+        assert_eq!(adaptive.residual_history.len(), 1 + adaptive.iterations);
+        assert!(max_function_value(semilinear(&mat), &space, x.view()) < 1E-9);
+
+        options.forcing = ForcingTerm::Constant(1E-12);
+        let mut x = space.create_element();
+        let exact = newton_krylov(&space, semilinear(&mat), None, &mut x, &options).unwrap();
+        assert!(exact.converged());
+
+        // Accurate linear solves take fewer Newton steps but more GMRES iterations.
+        assert!(exact.iterations <= adaptive.iterations);
+        assert!(adaptive.linear_iterations < exact.linear_iterations);
+        assert!(adaptive.function_evaluations > adaptive.linear_iterations);
+    }
+
+    #[test]
+    fn test_newton_krylov_preconditioned() {
+        let n = 100;
+        let mat = laplace_1d(n);
+        let prec = jacobi(&mat);
+        let prec_op = &prec as &dyn OperatorBase<Domain = _, Range = _>;
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let options = NewtonKrylovOptions::new(1E-10, 0.0, 30);
+        let mut x = space.create_element();
+        let result =
+            newton_krylov(&space, semilinear(&mat), Some(prec_op), &mut x, &options).unwrap();
+        assert!(result.converged());
+        assert!(max_function_value(semilinear(&mat), &space, x.view()) < 1E-8);
+    }
+
+    #[test]
+    fn test_newton_krylov_line_search() {
+        // Newton's method for arctan(x) = 0 diverges from x_0 = 10 without a line search.
+        let arctan = |x: &LocalIndexableVector<f64>, y: &mut LocalIndexableVector<f64>| {
+            let x = x.view().unwrap();
+            for (out, value) in y.view_mut().unwrap().iter_mut().zip(x.iter()) {
+                *out = value.atan();
+            }
+            Ok(())
+        };
+        let space = LocalIndexableVectorSpace::<f64>::new(3);
+        let mut options = NewtonKrylovOptions::new(1E-10, 0.0, 20);
+
+        let mut x = space.create_element();
+        x.view_mut().view_mut().unwrap().data_mut().fill(10.0);
+        let result = newton_krylov(&space, arctan, None, &mut x, &options).unwrap();
+        assert!(result.converged());
+        assert!(x
+            .view()
+            .view()
+            .unwrap()
+            .data()
+            .iter()
+            .all(|value| value.abs() < 1E-9));
+
+        options.line_search = LineSearch::Full;
+        let mut x = space.create_element();
+        x.view_mut().view_mut().unwrap().data_mut().fill(10.0);
+        // The iterates grow until the finite difference Jacobian vanishes.
+        let result = newton_krylov(&space, arctan, None, &mut x, &options);
+        assert!(matches!(
+            result,
+            Err(SparseLinAlgError::SolverBreakdown { ref method, .. }) if method == "GMRES"
+        ));
+    }
+
+    #[test]
+    fn test_newton_krylov_line_search_failure() {
+        // F(x) = x^2 + 1 has no root, and its norm has a minimum at x = 0 where the
+        // Jacobian is singular. From x = 1e-3 no step length reduces the residual enough.
+        let shifted_square = |x: &LocalIndexableVector<f64>, y: &mut LocalIndexableVector<f64>| {
+            let x = x.view().unwrap();
+            for (out, value) in y.view_mut().unwrap().iter_mut().zip(x.iter()) {
+                *out = value * value + 1.0;
+            }
+            Ok(())
+        };
+        let space = LocalIndexableVectorSpace::<f64>::new(1);
+        let mut options = NewtonKrylovOptions::new(1E-10, 0.0, 20);
+        options.line_search = LineSearch::Backtracking {
+            sufficient_decrease: 1E-4,
+            max_steps: 5,
+        };
+
+        let mut x = space.create_element();
+        x.view_mut().view_mut().unwrap().data_mut().fill(1E-3);
+        let err = newton_krylov(&space, shifted_square, None, &mut x, &options).unwrap_err();
+        assert!(matches!(err, SparseLinAlgError::SolverBreakdown { .. }));
+        assert_eq!(x.view().view().unwrap().data()[0], 1E-3);
+    }
+
+    #[test]
+    fn test_newton_krylov_initial_guess() {
+        let n = 10;
+        let mat = laplace_1d(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+        // The second run starts below the absolute tolerance.
+        let options = NewtonKrylovOptions::new(1E-8, 1E-6, 50);
+
+        let mut x = space.create_element();
+        newton_krylov(&space, semilinear(&mat), None, &mut x, &options).unwrap();
+        let result = newton_krylov(&space, semilinear(&mat), None, &mut x, &options).unwrap();
+        assert_eq!(result.reason, ConvergenceReason::AbsoluteTolerance);
+        assert_eq!(result.iterations, 0);
+        assert_eq!(result.function_evaluations, 1);
+        assert_eq!(result.residual_history.len(), 1);
+    }
+}
@@ -0,0 +1,287 @@
+//! The method SYMMLQ for Hermitian indefinite systems.
+
+use num::{Float, One, Zero};
+use sparse_traits::types::{Scalar, SparseLinAlgResult};
+use sparse_traits::{AsApply, Element, InnerProductSpace};
+
+use super::{
+    breakdown, norm, residual, ConvergenceMonitor, ConvergenceReason, RealType, SolverOptions,
+    SolverResult,
+};
+
+/// Solve `A x = b` with SYMMLQ.
+///
+/// The operator `A` must be Hermitian but may be indefinite. SYMMLQ computes the
+/// iterates of an LQ factorization of the Lanczos matrix, which always exist, and
+/// the monitored residual is that of the corresponding CG iterate. On exit `x` is
+/// the CG iterate, which coincides with the iterate of CG for positive definite `A`,
+/// or the LQ iterate in the rare case that the CG iterate does not exist.
+/// On entry `x` contains the initial guess.
+///
+/// An exactly singular Lanczos matrix with an invariant Krylov space is reported as a
+/// `SparseLinAlgError::SolverBreakdown`.
+pub fn symmlq<Space, Op>(
+    space: &Space,
+    op: &Op,
+    b: &Space::E<'_>,
+    x: &mut Space::E<'_>,
+    options: &SolverOptions<RealType<Space>>,
+) -> SparseLinAlgResult<SolverResult<RealType<Space>>>
+where
+    Space: InnerProductSpace,
+    Op: AsApply<Domain = Space, Range = Space> + ?Sized,
+{
+    let zero = RealType::<Space>::zero();
+    let one = RealType::<Space>::one();
+    let real = |value: RealType<Space>| Space::F::from_real(value);
+
+    let mut v_old = space.create_element();
+    let mut v = space.create_element();
+    let mut v_next = space.create_element();
+    let mut w = space.create_element();
+    let mut wbar = space.create_element();
+
+    residual(space, op, b, x, &mut v)?;
+    let beta1 = norm(space, &v.view())?;
+
+    let mut monitor = ConvergenceMonitor::new(options, norm(space, &b.view())?);
+    if let Some(reason) = monitor.check(beta1) {
+        return Ok(monitor.finish(reason, 0));
+    }
+    space.scale_inplace(real(beta1.recip()), &mut v.view_mut());
+    space.fill_inplace(&v.view(), &mut wbar.view_mut())?;
+
+    // The rotations k - 2 and k - 1 and the solution components z_{k-2} and z_{k-1}
+    // of the LQ factorization. The initial values make the first steps consistent.
+    let (mut c_old, mut s_old) = (-one, zero);
+    let (mut c, mut s) = (-one, zero);
+    let (mut z_old, mut z) = (zero, zero);
+    let mut beta = zero;
+
+    for iteration in 1..=options.max_iter {
+        // The Lanczos step A v_k = beta_k v_{k-1} + alpha_k v_k + beta_{k+1} v_{k+1}.
+        op.apply(v.view(), v_next.view_mut())?;
+        space.axpy_inplace(real(-beta), &v_old.view(), &mut v_next.view_mut())?;
+        let alpha = space.inner(&v_next.view(), &v.view())?.re();
+        space.axpy_inplace(real(-alpha), &v.view(), &mut v_next.view_mut())?;
+        let beta_next = norm(space, &v_next.view())?;
+        if beta_next > zero {
+            space.scale_inplace(real(beta_next.recip()), &mut v_next.view_mut());
+        }
+
+        // Row k of the lower triangular factor, with the last diagonal entry gbar.
+        let epsilon = s_old * beta;
+        let dbar = -c_old * beta;
+        let delta = c * dbar + s * alpha;
+        let gbar = s * dbar - c * alpha;
+        let rhs = if iteration == 1 { beta1 } else { zero } - epsilon * z_old - delta * z;
+
+        if gbar != zero {
+            // The CG iterate is the LQ iterate of the previous step plus zbar * wbar.
+            let zbar = rhs / gbar;
+            let cg_norm = beta_next * Float::abs(s * z - c * zbar);
+            let reason = monitor.check(cg_norm);
+            if reason.is_some() || iteration == options.max_iter {
+                space.axpy_inplace(real(zbar), &wbar.view(), &mut x.view_mut())?;
+                let reason = reason.unwrap_or(ConvergenceReason::MaxIterations);
+                return Ok(monitor.finish(reason, iteration));
+            }
+        }
+
+        let gamma = Float::hypot(gbar, beta_next);
+        if gamma == zero {
+            return Err(breakdown(
+                "SYMMLQ",
+                iteration,
+                "the Lanczos matrix is singular",
+            ));
+        }
+        let (c_new, s_new) = (gbar / gamma, beta_next / gamma);
+        let z_new = rhs / gamma;
+
+        // w_k = c wbar_k + s v_{k+1} and wbar_{k+1} = s wbar_k - c v_{k+1}.
+        space.fill_inplace(&wbar.view(), &mut w.view_mut())?;
+        space.scale_inplace(real(c_new), &mut w.view_mut());
+        space.axpy_inplace(real(s_new), &v_next.view(), &mut w.view_mut())?;
+        space.scale_inplace(real(s_new), &mut wbar.view_mut());
+        space.axpy_inplace(real(-c_new), &v_next.view(), &mut wbar.view_mut())?;
+        space.axpy_inplace(real(z_new), &w.view(), &mut x.view_mut())?;
+
+        (c_old, s_old, c, s) = (c, s, c_new, s_new);
+        (z_old, z) = (z, z_new);
+
+        if gbar == zero {
+            // The CG iterate does not exist, so measure the residual of the LQ iterate.
+            residual(space, op, b, x, &mut w)?;
+            let reason = monitor.check(norm(space, &w.view())?);
+            if reason.is_some() || iteration == options.max_iter {
+                let reason = reason.unwrap_or(ConvergenceReason::MaxIterations);
+                return Ok(monitor.finish(reason, iteration));
+            }
+        }
+
+        // Shift the Lanczos vectors, v_old = v, v = v_next.
+        std::mem::swap(&mut v_old, &mut v);
+        std::mem::swap(&mut v, &mut v_next);
+        beta = beta_next;
+    }
+
+    Ok(monitor.finish(ConvergenceReason::MaxIterations, options.max_iter))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::local::indexable_space::LocalIndexableVectorSpace;
+    use crate::local::sparse::csr_mat::CsrMatrix;
+    use crate::solvers::cg::cg;
+    use crate::solvers::test_problems::{laplace_1d, max_residual, tridiagonal};
+    use cauchy::c64;
+    use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+    use sparse_traits::LinearSpace;
+
+    #[test]
+    fn test_symmlq_against_cg() {
+        let n = 50;
+        let mat = laplace_1d(n);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        for (index, value) in b.view_mut().view_mut().unwrap().iter_mut().enumerate() {
+            *value = (index as f64).sin();
+        }
+        let options = SolverOptions::new(1E-10, 0.0, 200);
+
+        let mut x_cg = space.create_element();
+        let cg_result = cg(&space, &mat, None, &b, &mut x_cg, &options).unwrap();
+        let mut x = space.create_element();
+        let result = symmlq(&space, &mat, &b, &mut x, &options).unwrap();
+
+        // SYMMLQ monitors the residual of the CG iterates.
+        assert_eq!(result.reason, ConvergenceReason::RelativeTolerance);
+        assert_eq!(result.iterations, cg_result.iterations);
+        assert_eq!(result.residual_history.len(), 1 + result.iterations);
+        for (actual, expected) in result
+            .residual_history
+            .iter()
+            .zip(cg_result.residual_history.iter())
+        {
+            assert!((actual - expected).abs() < 1E-8 * cg_result.residual_history[0]);
+        }
+
+        for (actual, expected) in x
+            .view()
+            .view()
+            .unwrap()
+            .iter()
+            .zip(x_cg.view().view().unwrap().iter())
+        {
+            assert!((actual - expected).abs() < 1E-8 * expected.abs().max(1.0));
+        }
+    }
+
+    #[test]
+    fn test_symmlq_indefinite() {
+        let n = 50;
+        let mat = tridiagonal(&vec![0.5; n]);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let mut x = space.create_element();
+
+        let options = SolverOptions::new(1E-10, 0.0, 500);
+        let result = symmlq(&space, &mat, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+
+        let x = x.view().view().unwrap();
+        let b = b.view().view().unwrap();
+        assert!(max_residual(&mat, x.data(), b.data()) < 1E-8);
+    }
+
+    #[test]
+    fn test_symmlq_singular_lanczos_matrix() {
+        // For [[0, 1], [1, 0]] and b = e_1 the first Lanczos matrix is zero, so the first
+        // CG iterate does not exist but the LQ iterate is the exact solution.
+        let mat = CsrMatrix::from_aij((2, 2), &[0, 1], &[1, 0], &[1.0, 1.0]).unwrap();
+        let space = LocalIndexableVectorSpace::<f64>::new(2);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut()[0] = 1.0;
+        let mut x = space.create_element();
+
+        let options = SolverOptions::new(1E-12, 0.0, 10);
+        let result = symmlq(&space, &mat, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+        assert_eq!(result.iterations, 1);
+
+        let x = x.view().view().unwrap();
+        assert!((x.data()[0]).abs() < 1E-12);
+        assert!((x.data()[1] - 1.0).abs() < 1E-12);
+    }
+
+    #[test]
+    fn test_symmlq_complex_hermitian() {
+        let mat = CsrMatrix::from_aij(
+            (3, 3),
+            &[0, 0, 1, 1, 1, 2, 2],
+            &[0, 1, 0, 1, 2, 1, 2],
+            &[
+                c64::new(1.0, 0.0),
+                c64::new(0.0, 2.0),
+                c64::new(0.0, -2.0),
+                c64::new(-1.0, 0.0),
+                c64::new(1.0, 0.0),
+                c64::new(1.0, 0.0),
+                c64::new(2.0, 0.0),
+            ],
+        )
+        .unwrap();
+        let space = LocalIndexableVectorSpace::<c64>::new(3);
+
+        let mut b = space.create_element();
+        b.view_mut()
+            .view_mut()
+            .unwrap()
+            .data_mut()
+            .copy_from_slice(&[c64::new(1.0, 0.0), c64::new(0.0, 1.0), c64::new(2.0, -1.0)]);
+        let mut x = space.create_element();
+
+        let options = SolverOptions::new(1E-12, 0.0, 10);
+        let result = symmlq(&space, &mat, &b, &mut x, &options).unwrap();
+        assert!(result.converged());
+
+        let mut res = b.view().view().unwrap().data().to_vec();
+        mat.matmul(
+            c64::new(-1.0, 0.0),
+            x.view().view().unwrap().data(),
+            c64::new(1.0, 0.0),
+            &mut res,
+        );
+        assert!(res.iter().all(|value| value.norm() < 1E-10));
+    }
+
+    #[test]
+    fn test_symmlq_max_iterations() {
+        let n = 50;
+        let mat = tridiagonal(&vec![0.5; n]);
+        let space = LocalIndexableVectorSpace::<f64>::new(n);
+
+        let mut b = space.create_element();
+        b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+        let mut x = space.create_element();
+
+        let options = SolverOptions::new(1E-14, 0.0, 5);
+        let result = symmlq(&space, &mat, &b, &mut x, &options).unwrap();
+        assert_eq!(result.reason, ConvergenceReason::MaxIterations);
+        assert_eq!(result.iterations, 5);
+
+        // The reported residual norm is that of the returned iterate.
+        let x = x.view().view().unwrap();
+        let mut res = vec![1.0; n];
+        mat.matmul(-1.0, x.data(), 1.0, &mut res);
+        let true_norm = res.iter().map(|value| value * value).sum::<f64>().sqrt();
+        assert!((true_norm - result.residual_norm).abs() < 1E-10 * true_norm);
+    }
+}
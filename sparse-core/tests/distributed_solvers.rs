@@ -0,0 +1,145 @@
+//! Krylov solvers and preconditioners on distributed vector spaces.
+//!
+//! MPI can only be initialized once per process, so all checks that need it share
+//! a single test.
+
+use mpi::topology::SimpleCommunicator;
+use sparse_core::distributed::index_layout::DistributedIndexLayout;
+use sparse_core::distributed::indexable_space::DistributedIndexableVectorSpace;
+use sparse_core::local::sparse::csr_mat::CsrMatrix;
+use sparse_core::preconditioners::{BlockJacobi, Jacobi};
+use sparse_core::solvers::cg::cg;
+use sparse_core::solvers::{ConvergenceReason, SolverOptions};
+use sparse_traits::linalg::{IndexableVector, IndexableVectorView, IndexableVectorViewMut};
+use sparse_traits::types::SparseLinAlgResult;
+use sparse_traits::{
+    AsApply, AsApplyInverse, Element, ElementView, ElementViewMut, IndexLayout, LinearSpace,
+    OperatorBase,
+};
+use std::fmt::Debug;
+
+type Space<'comm> = DistributedIndexableVectorSpace<'comm, f64, SimpleCommunicator>;
+
+/// The 1D Laplacian `tridiag(-1, 2, -1)` of dimension `n`.
+fn laplace_1d(n: usize) -> CsrMatrix<f64> {
+    let mut rows = Vec::new();
+    let mut cols = Vec::new();
+    let mut data = Vec::new();
+    for row in 0..n {
+        for (col, value) in [(row.wrapping_sub(1), -1.0), (row, 2.0), (row + 1, -1.0)] {
+            if col < n {
+                rows.push(row);
+                cols.push(col);
+                data.push(value);
+            }
+        }
+    }
+    CsrMatrix::from_aij((n, n), &rows, &cols, &data).unwrap()
+}
+
+/// The rows of the block Laplacian owned by a rank whose local range starts at `first`,
+/// with global column indices.
+fn local_rows(local: &CsrMatrix<f64>, first: usize, n: usize) -> CsrMatrix<f64> {
+    let indices = local.indices().iter().map(|col| first + col).collect();
+    CsrMatrix::try_new(
+        (local.shape().0, n),
+        indices,
+        local.indptr().to_vec(),
+        local.data().to_vec(),
+    )
+    .unwrap()
+}
+
+/// Each rank applies the Laplacian to its own entries, so that the operator is block
+/// diagonal and needs no communication. On a single rank it is the Laplacian.
+struct BlockLaplace<'s, 'comm> {
+    space: &'s Space<'comm>,
+    local: CsrMatrix<f64>,
+}
+
+impl Debug for BlockLaplace<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockLaplace")
+            .field("local", &self.local)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'s, 'comm> OperatorBase for BlockLaplace<'s, 'comm> {
+    type Domain = Space<'comm>;
+    type Range = Space<'comm>;
+
+    fn domain(&self) -> &Self::Domain {
+        self.space
+    }
+
+    fn range(&self) -> &Self::Range {
+        self.space
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<'s, 'comm> AsApply for BlockLaplace<'s, 'comm> {
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        let x = x.view().unwrap();
+        let mut y = y.view_mut().unwrap();
+        self.local.matmul(1.0, x.data(), 0.0, y.data_mut());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_distributed_cg() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+
+    let n = 60;
+    let layout = DistributedIndexLayout::new(n, &world);
+    let space = Space::new(&layout);
+
+    let local_dim = layout.number_of_local_indices();
+    let op = BlockLaplace {
+        space: &space,
+        local: laplace_1d(local_dim),
+    };
+
+    let mut b = space.create_element();
+    let mut x = space.create_element();
+    b.view_mut().view_mut().unwrap().data_mut().fill(1.0);
+
+    let options = SolverOptions::new(1E-10, 0.0, 100);
+    let result = cg(&space, &op, None, &b, &mut x, &options).unwrap();
+
+    assert_eq!(result.reason, ConvergenceReason::RelativeTolerance);
+
+    // The exact solution of each block is x_i = (i + 1)(m - i) / 2.
+    let x_view = x.view().view().unwrap();
+    for (index, value) in x_view.iter().enumerate() {
+        let expected = ((index + 1) * (local_dim - index)) as f64 / 2.0;
+        assert!((value - expected).abs() < 1E-6 * expected);
+    }
+
+    let rows = local_rows(&op.local, layout.local_range().0, n);
+
+    // The diagonal of the Laplacian is 2.
+    let jacobi = Jacobi::distributed(&layout, &rows).unwrap();
+    let mut y = space.create_element();
+    jacobi.apply_inverse(b.view(), y.view_mut()).unwrap();
+    let y_view = y.view().view().unwrap();
+    assert!(y_view.iter().all(|&value| value == 0.5));
+
+    // ILU(0) of a tridiagonal block is exact, so CG converges in a single iteration.
+    let block_jacobi = BlockJacobi::distributed(&layout, &rows).unwrap();
+    assert_eq!(block_jacobi.number_of_blocks(), 1);
+    let mut x = space.create_element();
+    let result = cg(&space, &op, Some(&block_jacobi), &b, &mut x, &options).unwrap();
+    assert_eq!(result.reason, ConvergenceReason::RelativeTolerance);
+    assert!(result.iterations <= 1);
+}
@@ -0,0 +1,218 @@
+//! Derive macros for the traits of `sparse_traits`.
+//!
+//! `#[derive(Operator)]` implements `OperatorBase` for a struct. The container attribute
+//! `#[operator(domain = D, range = R, ...)]` sets the associated space types and lists the
+//! capabilities of the operator. Each of `apply`, `transpose`, `adjoint` and `inverse`
+//! makes the matching discovery method (`as_apply`, `as_apply_transpose`,
+//! `as_apply_adjoint`, `as_apply_inverse`) return `Some(self)`, so the struct must
+//! implement the corresponding trait. All other discovery methods keep their default
+//! `None`.
+//!
+//! The spaces are taken from the fields named `domain` and `range`. A different field can
+//! be marked with `#[operator(domain)]` or `#[operator(range)]`, and one field can serve
+//! as both.
+//!
+//! ```ignore
+//! #[derive(Debug, Operator)]
+//! #[operator(domain = LocalIndexableVectorSpace<f64>, range = LocalIndexableVectorSpace<f64>, apply, adjoint)]
+//! struct Stencil {
+//!     #[operator(domain, range)]
+//!     space: LocalIndexableVectorSpace<f64>,
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Member, Type};
+
+/// Implement `OperatorBase` with the capability discovery methods wired up.
+#[proc_macro_derive(Operator, attributes(operator))]
+pub fn derive_operator(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_operator(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The options of the container attribute.
+#[derive(Default)]
+struct OperatorOptions {
+    domain: Option<Type>,
+    range: Option<Type>,
+    apply: bool,
+    transpose: bool,
+    adjoint: bool,
+    inverse: bool,
+}
+
+fn parse_options(input: &DeriveInput) -> syn::Result<OperatorOptions> {
+    let mut options = OperatorOptions::default();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("operator"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("domain") {
+                options.domain = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("range") {
+                options.range = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("apply") {
+                options.apply = true;
+            } else if meta.path.is_ident("transpose") {
+                options.transpose = true;
+            } else if meta.path.is_ident("adjoint") {
+                options.adjoint = true;
+            } else if meta.path.is_ident("inverse") {
+                options.inverse = true;
+            } else {
+                return Err(meta.error(
+                    "expected `domain = ...`, `range = ...`, `apply`, `transpose`, `adjoint` or `inverse`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// Find the fields that hold the domain and the range.
+fn space_fields(input: &DeriveInput) -> syn::Result<(Member, Member)> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "`Operator` can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut domain = None;
+    let mut range = None;
+    let mut domain_by_name = None;
+    let mut range_by_name = None;
+
+    for (index, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("operator"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("domain") {
+                    domain = Some(member.clone());
+                } else if meta.path.is_ident("range") {
+                    range = Some(member.clone());
+                } else {
+                    return Err(meta.error("expected `domain` or `range`"));
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(ident) = &field.ident {
+            if ident == "domain" {
+                domain_by_name = Some(member.clone());
+            } else if ident == "range" {
+                range_by_name = Some(member.clone());
+            }
+        }
+    }
+
+    let missing = |space: &str| {
+        syn::Error::new_spanned(
+            &input.ident,
+            format!("no field named `{space}` and no field marked with `#[operator({space})]`"),
+        )
+    };
+
+    let domain = domain.or(domain_by_name).ok_or_else(|| missing("domain"))?;
+    let range = range.or(range_by_name).ok_or_else(|| missing("range"))?;
+    Ok((domain, range))
+}
+
+fn expand_operator(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let options = parse_options(input)?;
+    let domain_type = options
+        .domain
+        .as_ref()
+        .ok_or_else(|| syn::Error::new_spanned(input, "missing `#[operator(domain = ...)]`"))?;
+    let range_type = options
+        .range
+        .as_ref()
+        .ok_or_else(|| syn::Error::new_spanned(input, "missing `#[operator(range = ...)]`"))?;
+    let (domain_field, range_field) = space_fields(input)?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let as_apply = options.apply.then(|| {
+        quote! {
+            fn as_apply(
+                &self,
+            ) -> ::std::option::Option<
+                &dyn ::sparse_traits::AsApply<Domain = Self::Domain, Range = Self::Range>,
+            > {
+                ::std::option::Option::Some(self)
+            }
+        }
+    });
+    let as_apply_transpose = options.transpose.then(|| {
+        quote! {
+            fn as_apply_transpose(
+                &self,
+            ) -> ::std::option::Option<
+                &dyn ::sparse_traits::AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>,
+            > {
+                ::std::option::Option::Some(self)
+            }
+        }
+    });
+    let as_apply_adjoint = options.adjoint.then(|| {
+        quote! {
+            fn as_apply_adjoint(
+                &self,
+            ) -> ::std::option::Option<
+                &dyn ::sparse_traits::AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>,
+            > {
+                ::std::option::Option::Some(self)
+            }
+        }
+    });
+    let as_apply_inverse = options.inverse.then(|| {
+        quote! {
+            fn as_apply_inverse(
+                &self,
+            ) -> ::std::option::Option<
+                &dyn ::sparse_traits::AsApplyInverse<Domain = Self::Domain, Range = Self::Range>,
+            > {
+                ::std::option::Option::Some(self)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::sparse_traits::OperatorBase for #name #ty_generics #where_clause {
+            type Domain = #domain_type;
+            type Range = #range_type;
+
+            fn domain(&self) -> &Self::Domain {
+                &self.#domain_field
+            }
+
+            fn range(&self) -> &Self::Range {
+                &self.#range_field
+            }
+
+            #as_apply
+            #as_apply_transpose
+            #as_apply_adjoint
+            #as_apply_inverse
+        }
+    })
+}
@@ -3,17 +3,56 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 
 // We create two structs. One will have a matvec,
-// the other one not.
-#[derive(Debug)]
-struct OpWithMatVec {}
+// the other one not. For OpWithMatvec the derive macro
+// implements `OperatorBase` and makes `as_apply` return
+// a reference to self, so the user needs not write this
+// boilerplate.
+#[derive(Debug, Operator)]
+#[operator(domain = SimpleSpace, range = SimpleSpace, apply)]
+struct OpWithMatVec {
+    #[operator(domain, range)]
+    space: SimpleSpace,
+}
 #[derive(Debug)]
 struct OpWithoutMatVec {}
 
+#[derive(Debug)]
 struct SimpleSpace;
 
 impl LinearSpace for SimpleSpace {
     type F = f64;
     type E<'a> = Vec;
+    type View<'a> = View<'a>;
+    type ViewMut<'a> = View<'a>;
+
+    fn reborrow_view<'b, 'a: 'b>(_x: &'b View<'a>) -> View<'b> {
+        View::new()
+    }
+
+    fn reborrow_view_mut<'b, 'a: 'b>(_y: &'b mut View<'a>) -> View<'b> {
+        View::new()
+    }
+
+    // The mock vectors hold no data, so there is nothing to update.
+    fn axpy_inplace<'a>(&self, _alpha: f64, _x: &View<'a>, _y: &mut View<'a>) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        Ok(())
+    }
+
+    fn fill_inplace<'a>(&self, _x: &View<'a>, _y: &mut View<'a>) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        Ok(())
+    }
+
+    fn scale_inplace<'a>(&self, _alpha: f64, _y: &mut View<'a>)
+    where
+        Self: 'a,
+    {
+    }
 }
 
 struct View<'a> {
@@ -34,31 +73,16 @@ impl<'a> View<'a> {
 
 impl Element for Vec {
     type Space = SimpleSpace;
-    type View<'a> = View<'a> where Self: 'a;
-    type ViewMut<'a> = View<'a> where Self: 'a;
 
-    fn view<'a>(&'a self) -> Self::View<'a> {
+    fn view<'a>(&'a self) -> View<'a> {
         View::new()
     }
 
-    fn view_mut<'a>(&'a mut self) -> Self::View<'a> {
+    fn view_mut<'a>(&'a mut self) -> View<'a> {
         View::new()
     }
 }
 
-// For OpWithMatvec we have to implement
-// `as_matvec` to return a reference to self.
-// This is just boilerplate and could be handled
-// by a simple derive macro or similar so the user
-// needs not write this boilerplate.
-impl OperatorBase for OpWithMatVec {
-    type Domain = SimpleSpace;
-    type Range = SimpleSpace;
-    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
-        Some(self)
-    }
-}
-
 // The actual matvec is now implemented. It is just
 // a stub that prints a message.
 impl AsApply for OpWithMatVec {
@@ -73,11 +97,17 @@ impl AsApply for OpWithMatVec {
 impl OperatorBase for OpWithoutMatVec {
     type Domain = SimpleSpace;
     type Range = SimpleSpace;
+    fn domain(&self) -> &Self::Domain {
+        &SimpleSpace
+    }
+    fn range(&self) -> &Self::Range {
+        &SimpleSpace
+    }
 }
 
 fn main() {
     // We create two structs. One witho matvec and one without
-    let op_with_matvec = OpWithMatVec {};
+    let op_with_matvec = OpWithMatVec { space: SimpleSpace };
     //let op_matvec_ref = &op_with_matvec as &dyn OperatorBase;
 
     let op_without_matvec = OpWithoutMatVec {};
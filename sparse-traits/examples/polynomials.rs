@@ -5,6 +5,58 @@ pub struct PolynomialSpace;
 impl LinearSpace for PolynomialSpace {
     type F = f64;
     type E<'a> = Polynomial;
+    type View<'a> = PolynomialView<'a>;
+    type ViewMut<'a> = PolynomialViewMut<'a>;
+
+    fn reborrow_view<'b, 'a: 'b>(x: &'b PolynomialView<'a>) -> PolynomialView<'b> {
+        PolynomialView {
+            monomial_coeffs: x.monomial_coeffs,
+        }
+    }
+
+    fn reborrow_view_mut<'b, 'a: 'b>(y: &'b mut PolynomialViewMut<'a>) -> PolynomialViewMut<'b> {
+        PolynomialViewMut {
+            monomial_coeffs: y.monomial_coeffs,
+        }
+    }
+
+    fn axpy_inplace<'a>(&self, alpha: f64, x: &PolynomialView<'a>, y: &mut PolynomialViewMut<'a>) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        check_degree(x.monomial_coeffs.len(), y.monomial_coeffs.len())?;
+        for (out, value) in y.monomial_coeffs.iter_mut().zip(x.monomial_coeffs) {
+            *out += alpha * value;
+        }
+        Ok(())
+    }
+
+    fn fill_inplace<'a>(&self, x: &PolynomialView<'a>, y: &mut PolynomialViewMut<'a>) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        check_degree(x.monomial_coeffs.len(), y.monomial_coeffs.len())?;
+        y.monomial_coeffs.copy_from_slice(x.monomial_coeffs);
+        Ok(())
+    }
+
+    fn scale_inplace<'a>(&self, alpha: f64, y: &mut PolynomialViewMut<'a>)
+    where
+        Self: 'a,
+    {
+        for value in y.monomial_coeffs.iter_mut() {
+            *value *= alpha;
+        }
+    }
+}
+
+// The views have a fixed number of coefficients, so only polynomials of the same
+// degree can be combined.
+fn check_degree(expected: usize, actual: usize) -> SparseLinAlgResult<()> {
+    if expected != actual {
+        return Err(SparseLinAlgError::SingleDimensionError { expected, actual });
+    }
+    Ok(())
 }
 
 #[derive(Debug)]
@@ -36,10 +88,8 @@ pub struct PolynomialViewMut<'a> {
 
 impl Element for Polynomial {
     type Space = PolynomialSpace;
-    type View<'b> = PolynomialView<'b> where Self: 'b ;
-    type ViewMut<'b> = PolynomialViewMut<'b> where Self: 'b;
 
-    fn view<'b>(&'b self) -> Self::View<'b> {
+    fn view<'b>(&'b self) -> PolynomialView<'b> {
         PolynomialView {
             monomial_coeffs: &self.monomial_coeffs,
         }
@@ -55,6 +105,44 @@ pub struct PointwiseEvaluatorSpace;
 impl LinearSpace for PointwiseEvaluatorSpace {
     type F = f64;
     type E<'a> = PointwiseEvaluate;
+    type View<'a> = &'a PointwiseEvaluate;
+    type ViewMut<'a> = &'a mut PointwiseEvaluate;
+
+    fn reborrow_view<'b, 'a: 'b>(x: &'b &'a PointwiseEvaluate) -> &'b PointwiseEvaluate {
+        x
+    }
+
+    fn reborrow_view_mut<'b, 'a: 'b>(y: &'b mut &'a mut PointwiseEvaluate) -> &'b mut PointwiseEvaluate {
+        y
+    }
+
+    // Only evaluations at the same point can be added.
+    fn axpy_inplace<'a>(&self, alpha: f64, x: &&'a PointwiseEvaluate, y: &mut &'a mut PointwiseEvaluate) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        if x.x != y.x {
+            return Err(SparseLinAlgError::NotImplemented("sum of evaluations at different points".to_string()));
+        }
+        y.scale += alpha * x.scale;
+        Ok(())
+    }
+
+    fn fill_inplace<'a>(&self, x: &&'a PointwiseEvaluate, y: &mut &'a mut PointwiseEvaluate) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        y.x = x.x;
+        y.scale = x.scale;
+        Ok(())
+    }
+
+    fn scale_inplace<'a>(&self, alpha: f64, y: &mut &'a mut PointwiseEvaluate)
+    where
+        Self: 'a,
+    {
+        y.scale *= alpha;
+    }
 }
 impl DualSpace for PointwiseEvaluatorSpace {
     type Space = PolynomialSpace;
@@ -76,12 +164,10 @@ impl PointwiseEvaluate {
 
 impl Element for PointwiseEvaluate {
     type Space = PointwiseEvaluatorSpace;
-    type View<'a> = &'a PointwiseEvaluate where Self: 'a;
-    type ViewMut<'a> = &'a mut PointwiseEvaluate where Self: 'a;
-    fn view<'a>(&'a self) -> Self::View<'a> {
+    fn view(&self) -> &PointwiseEvaluate {
         &self
     }
-    fn view_mut<'a>(&'a mut self) -> Self::ViewMut<'a> {
+    fn view_mut(&mut self) -> &mut PointwiseEvaluate {
         self
     }
 }
@@ -91,6 +177,12 @@ struct Derivative;
 impl OperatorBase for Derivative {
     type Domain = PolynomialSpace;
     type Range = PolynomialSpace;
+    fn domain(&self) -> &Self::Domain {
+        &PolynomialSpace
+    }
+    fn range(&self) -> &Self::Range {
+        &PolynomialSpace
+    }
     fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
         Some(self)
     }
@@ -1,5 +1,8 @@
 //! This is a sandbox to test traits for sparse (and more general) operators.
 
+// The derive macros refer to `::sparse_traits`, which also has to resolve inside this crate.
+extern crate self as sparse_traits;
+
 pub mod index_layout;
 pub mod operator;
 pub mod spaces;
@@ -10,3 +13,5 @@ pub use index_layout::*;
 pub use operator::*;
 pub use spaces::*;
 pub use types::*;
+
+pub use sparse_derive::Operator;
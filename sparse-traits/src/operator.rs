@@ -1,5 +1,15 @@
 //! General linear operator.
 
+pub mod algebra;
+pub mod block;
+pub mod fn_operator;
+#[cfg(test)]
+pub(crate) mod test_spaces;
+
+pub use algebra::*;
+pub use block::*;
+pub use fn_operator::*;
+
 use crate::LinearSpace;
 use std::fmt::Debug;
 
@@ -10,6 +20,12 @@ pub trait OperatorBase: Debug {
     type Domain: LinearSpace;
     type Range: LinearSpace;
 
+    /// The space the operator acts on.
+    fn domain(&self) -> &Self::Domain;
+
+    /// The space the operator maps into.
+    fn range(&self) -> &Self::Range;
+
     /// Returns a reference to trait object that supports application of the operator.
     ///
     /// By default it returns an `Err`. But for concrete types
@@ -22,6 +38,41 @@ pub trait OperatorBase: Debug {
     fn has_apply(&self) -> bool {
         self.as_apply().is_some()
     }
+
+    /// Returns a reference to trait object that supports application of the transpose.
+    ///
+    /// Operators that are only available through their action, such as finite
+    /// difference approximations of a Jacobian, keep the default `None`.
+    fn as_apply_transpose(&self) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        None
+    }
+
+    fn has_apply_transpose(&self) -> bool {
+        self.as_apply_transpose().is_some()
+    }
+
+    /// Returns a reference to trait object that supports application of the adjoint.
+    ///
+    /// By default it returns `None`, like [OperatorBase::as_apply_transpose].
+    fn as_apply_adjoint(&self) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        None
+    }
+
+    fn has_apply_adjoint(&self) -> bool {
+        self.as_apply_adjoint().is_some()
+    }
+
+    /// Returns a reference to trait object that applies an approximate inverse.
+    ///
+    /// Preconditioners such as Jacobi or incomplete factorizations are operators `M` that
+    /// approximate `A` and support this capability. By default it returns `None`.
+    fn as_apply_inverse(&self) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        None
+    }
+
+    fn has_apply_inverse(&self) -> bool {
+        self.as_apply_inverse().is_some()
+    }
 }
 
 /// Apply an operator.
@@ -29,7 +80,48 @@ pub trait AsApply: OperatorBase {
     fn apply(&self, x: ElementView<Self::Domain>, y: ElementViewMut<Self::Range>) -> SparseLinAlgResult<()>;
 }
 
-impl<In: LinearSpace, Out: LinearSpace> AsApply for dyn OperatorBase<Domain = In, Range = Out> {
+/// Apply the transpose of an operator.
+///
+/// The transpose maps the range into the domain. In contrast to the adjoint no
+/// complex conjugation is involved, so for a matrix this is `y = A^T x`.
+pub trait AsApplyTranspose: OperatorBase {
+    fn apply_transpose(&self, x: ElementView<Self::Range>, y: ElementViewMut<Self::Domain>) -> SparseLinAlgResult<()>;
+}
+
+/// Apply the adjoint of an operator.
+///
+/// The adjoint maps the range into the domain and satisfies `(A x, y) = (x, A^H y)`
+/// with respect to the inner products of the two spaces.
+pub trait AsApplyAdjoint: OperatorBase {
+    fn apply_adjoint(&self, x: ElementView<Self::Range>, y: ElementViewMut<Self::Domain>) -> SparseLinAlgResult<()>;
+}
+
+/// Apply an approximate inverse of an operator.
+///
+/// The inverse maps the range into the domain, `y = M^{-1} x`. It need not be exact,
+/// so applying the operator and then its inverse only approximately gives the identity.
+pub trait AsApplyInverse: OperatorBase {
+    fn apply_inverse(&self, x: ElementView<Self::Range>, y: ElementViewMut<Self::Domain>) -> SparseLinAlgResult<()>;
+}
+
+/// Compute `y = M^{-1} x` for an operator `M` that is used as a preconditioner.
+///
+/// If `M` supports [OperatorBase::as_apply_inverse] its inverse is applied. Otherwise `M`
+/// is taken to be the approximate inverse itself and is applied directly, so that any
+/// operator that applies an approximation of `A^{-1}` can be passed as a preconditioner.
+pub fn apply_preconditioner<Space, Op>(prec: &Op, x: ElementView<Space>, y: ElementViewMut<Space>) -> SparseLinAlgResult<()>
+where
+    Space: LinearSpace,
+    Op: OperatorBase<Domain = Space, Range = Space> + ?Sized,
+{
+    match (prec.as_apply_inverse(), prec.as_apply()) {
+        (Some(inverse), _) => inverse.apply_inverse(x, y),
+        (None, Some(op)) => op.apply(x, y),
+        (None, None) => Err(SparseLinAlgError::NotImplemented("ApplyInverse".to_string())),
+    }
+}
+
+impl<'a, In: LinearSpace, Out: LinearSpace> AsApply for dyn OperatorBase<Domain = In, Range = Out> + 'a {
     fn apply(&self, x: ElementView<Self::Domain>, y: ElementViewMut<Self::Range>) -> SparseLinAlgResult<()> {
         if let Some(op) = self.as_apply() {
             op.apply(x, y)
@@ -39,6 +131,96 @@ impl<In: LinearSpace, Out: LinearSpace> AsApply for dyn OperatorBase<Domain = In
     }
 }
 
+impl<'a, In: LinearSpace, Out: LinearSpace> AsApplyTranspose for dyn OperatorBase<Domain = In, Range = Out> + 'a {
+    fn apply_transpose(&self, x: ElementView<Self::Range>, y: ElementViewMut<Self::Domain>) -> SparseLinAlgResult<()> {
+        if let Some(op) = self.as_apply_transpose() {
+            op.apply_transpose(x, y)
+        } else {
+            Err(SparseLinAlgError::NotImplemented("ApplyTranspose".to_string()))
+        }
+    }
+}
+
+impl<'a, In: LinearSpace, Out: LinearSpace> AsApplyAdjoint for dyn OperatorBase<Domain = In, Range = Out> + 'a {
+    fn apply_adjoint(&self, x: ElementView<Self::Range>, y: ElementViewMut<Self::Domain>) -> SparseLinAlgResult<()> {
+        if let Some(op) = self.as_apply_adjoint() {
+            op.apply_adjoint(x, y)
+        } else {
+            Err(SparseLinAlgError::NotImplemented("ApplyAdjoint".to_string()))
+        }
+    }
+}
+
+impl<'a, In: LinearSpace, Out: LinearSpace> AsApplyInverse for dyn OperatorBase<Domain = In, Range = Out> + 'a {
+    fn apply_inverse(&self, x: ElementView<Self::Range>, y: ElementViewMut<Self::Domain>) -> SparseLinAlgResult<()> {
+        if let Some(op) = self.as_apply_inverse() {
+            op.apply_inverse(x, y)
+        } else {
+            Err(SparseLinAlgError::NotImplemented("ApplyInverse".to_string()))
+        }
+    }
+}
+
+// Boxed operators and references to operators are operators themselves, so that
+// combinators can own their parts or borrow them.
+impl<Op: OperatorBase + ?Sized> OperatorBase for Box<Op> {
+    type Domain = Op::Domain;
+    type Range = Op::Range;
+
+    fn domain(&self) -> &Self::Domain {
+        (**self).domain()
+    }
+
+    fn range(&self) -> &Self::Range {
+        (**self).range()
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        (**self).as_apply()
+    }
+
+    fn as_apply_transpose(&self) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        (**self).as_apply_transpose()
+    }
+
+    fn as_apply_adjoint(&self) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        (**self).as_apply_adjoint()
+    }
+
+    fn as_apply_inverse(&self) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        (**self).as_apply_inverse()
+    }
+}
+
+impl<Op: OperatorBase + ?Sized> OperatorBase for &Op {
+    type Domain = Op::Domain;
+    type Range = Op::Range;
+
+    fn domain(&self) -> &Self::Domain {
+        (**self).domain()
+    }
+
+    fn range(&self) -> &Self::Range {
+        (**self).range()
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        (**self).as_apply()
+    }
+
+    fn as_apply_transpose(&self) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        (**self).as_apply_transpose()
+    }
+
+    fn as_apply_adjoint(&self) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        (**self).as_apply_adjoint()
+    }
+
+    fn as_apply_inverse(&self) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        (**self).as_apply_inverse()
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -51,6 +233,41 @@ mod tests {
     impl LinearSpace for SimpleSpace {
         type F = f64;
         type E<'a> = SimpleVector;
+        type View<'a> = View<'a>;
+        type ViewMut<'a> = View<'a>;
+
+        fn reborrow_view<'b, 'a: 'b>(_x: &'b View<'a>) -> View<'b> {
+            View::new()
+        }
+
+        fn reborrow_view_mut<'b, 'a: 'b>(_y: &'b mut View<'a>) -> View<'b> {
+            View::new()
+        }
+
+        fn axpy_inplace<'a>(
+            &self,
+            _alpha: f64,
+            _x: &View<'a>,
+            _y: &mut View<'a>,
+        ) -> SparseLinAlgResult<()>
+        where
+            Self: 'a,
+        {
+            Ok(())
+        }
+
+        fn fill_inplace<'a>(&self, _x: &View<'a>, _y: &mut View<'a>) -> SparseLinAlgResult<()>
+        where
+            Self: 'a,
+        {
+            Ok(())
+        }
+
+        fn scale_inplace<'a>(&self, _alpha: f64, _y: &mut View<'a>)
+        where
+            Self: 'a,
+        {
+        }
     }
 
     #[derive(Debug)]
@@ -71,14 +288,12 @@ mod tests {
 
     impl Element for SimpleVector {
         type Space = SimpleSpace;
-        type View<'b> = View<'b> where Self: 'b;
-        type ViewMut<'b> = View<'b> where Self: 'b;
 
-        fn view<'b>(&'b self) -> Self::View<'b> {
+        fn view<'b>(&'b self) -> View<'b> {
             View::new()
         }
 
-        fn view_mut<'b>(&'b mut self) -> Self::View<'b> {
+        fn view_mut<'b>(&'b mut self) -> View<'b> {
             View::new()
         }
     }
@@ -88,15 +303,26 @@ mod tests {
     impl OperatorBase for SparseMatrix {
         type Domain = SimpleSpace;
         type Range = SimpleSpace;
+        fn domain(&self) -> &Self::Domain {
+            &SimpleSpace
+        }
+        fn range(&self) -> &Self::Range {
+            &SimpleSpace
+        }
 
         fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
             Some(self)
         }
-        // fn as_matvec_h(
-        //     &self,
-        // ) -> Option<&dyn AsHermitianMatVec<Domain = Self::Domain, Range = Self::Range>> {
-        //     Some(self)
-        // }
+        fn as_apply_transpose(
+            &self,
+        ) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+            Some(self)
+        }
+        fn as_apply_adjoint(
+            &self,
+        ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+            Some(self)
+        }
     }
     impl AsApply for SparseMatrix {
         fn apply(
@@ -108,6 +334,26 @@ mod tests {
             Ok(())
         }
     }
+    impl AsApplyTranspose for SparseMatrix {
+        fn apply_transpose(
+            &self,
+            _x: ElementView<Self::Range>,
+            _y: ElementViewMut<Self::Domain>,
+        ) -> SparseLinAlgResult<()> {
+            println!("{self:?} matvec_t");
+            Ok(())
+        }
+    }
+    impl AsApplyAdjoint for SparseMatrix {
+        fn apply_adjoint(
+            &self,
+            _x: ElementView<Self::Range>,
+            _y: ElementViewMut<Self::Domain>,
+        ) -> SparseLinAlgResult<()> {
+            println!("{self:?} matvec_h");
+            Ok(())
+        }
+    }
 
     // Finite difference matrices use the following formula where f is a
     // nonlinear function and x is a vector that we linearize around. It is not
@@ -120,6 +366,12 @@ mod tests {
     impl OperatorBase for FiniteDifference {
         type Domain = SimpleSpace;
         type Range = SimpleSpace;
+        fn domain(&self) -> &Self::Domain {
+            &SimpleSpace
+        }
+        fn range(&self) -> &Self::Range {
+            &SimpleSpace
+        }
         fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
             Some(self)
         }
@@ -141,6 +393,12 @@ mod tests {
     impl OperatorBase for SketchyMatrix {
         type Domain = SimpleSpace;
         type Range = SimpleSpace;
+        fn domain(&self) -> &Self::Domain {
+            &SimpleSpace
+        }
+        fn range(&self) -> &Self::Range {
+            &SimpleSpace
+        }
         fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
             Some(self)
         }
@@ -155,6 +413,116 @@ mod tests {
             Err(SparseLinAlgError::OperationFailed("Apply".to_string()))
         }
     }
+    // The derive macro generates the discovery methods for the listed capabilities.
+    #[derive(Debug, Operator)]
+    #[operator(domain = SimpleSpace, range = SimpleSpace, apply, adjoint)]
+    struct DerivedMatrix {
+        #[operator(domain, range)]
+        space: SimpleSpace,
+    }
+    impl AsApply for DerivedMatrix {
+        fn apply(
+            &self,
+            _x: ElementView<Self::Domain>,
+            _y: ElementViewMut<Self::Range>,
+        ) -> SparseLinAlgResult<()> {
+            Ok(())
+        }
+    }
+    impl AsApplyAdjoint for DerivedMatrix {
+        fn apply_adjoint(
+            &self,
+            _x: ElementView<Self::Range>,
+            _y: ElementViewMut<Self::Domain>,
+        ) -> SparseLinAlgResult<()> {
+            Err(SparseLinAlgError::OperationFailed("ApplyAdjoint".to_string()))
+        }
+    }
+
+    #[derive(Debug, Operator)]
+    #[operator(domain = SimpleSpace, range = SimpleSpace, transpose, inverse)]
+    struct DerivedGeneric<T: Debug> {
+        domain: SimpleSpace,
+        range: SimpleSpace,
+        marker: PhantomData<T>,
+    }
+    impl<T: Debug> AsApplyTranspose for DerivedGeneric<T> {
+        fn apply_transpose(
+            &self,
+            _x: ElementView<Self::Range>,
+            _y: ElementViewMut<Self::Domain>,
+        ) -> SparseLinAlgResult<()> {
+            Ok(())
+        }
+    }
+    impl<T: Debug> AsApplyInverse for DerivedGeneric<T> {
+        fn apply_inverse(
+            &self,
+            _x: ElementView<Self::Range>,
+            _y: ElementViewMut<Self::Domain>,
+        ) -> SparseLinAlgResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_derive() -> SparseLinAlgResult<()> {
+        let x = SimpleVector {};
+        let mut y = SimpleVector {};
+
+        let derived = DerivedMatrix { space: SimpleSpace };
+        let op = &derived as &dyn OperatorBase<Domain = SimpleSpace, Range = SimpleSpace>;
+        assert!(op.has_apply());
+        assert!(!op.has_apply_transpose());
+        assert!(op.has_apply_adjoint());
+        op.apply(x.view(), y.view_mut())?;
+        assert!(matches!(
+            op.apply_adjoint(x.view(), y.view_mut()),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+
+        let generic = DerivedGeneric::<f64> {
+            domain: SimpleSpace,
+            range: SimpleSpace,
+            marker: PhantomData,
+        };
+        let op = &generic as &dyn OperatorBase<Domain = SimpleSpace, Range = SimpleSpace>;
+        assert!(!op.has_apply());
+        assert!(op.has_apply_transpose());
+        assert!(!op.has_apply_adjoint());
+        assert!(op.has_apply_inverse());
+        op.apply_transpose(x.view(), y.view_mut())?;
+        op.apply_inverse(x.view(), y.view_mut())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_preconditioner() -> SparseLinAlgResult<()> {
+        let x = SimpleVector {};
+        let mut y = SimpleVector {};
+
+        // An operator with an inverse is applied through it, even without `as_apply`.
+        let generic = DerivedGeneric::<f64> {
+            domain: SimpleSpace,
+            range: SimpleSpace,
+            marker: PhantomData,
+        };
+        apply_preconditioner(&generic, x.view(), y.view_mut())?;
+
+        // Other operators are approximate inverses themselves.
+        let sketchy = &SketchyMatrix as &dyn OperatorBase<Domain = SimpleSpace, Range = SimpleSpace>;
+        assert!(!sketchy.has_apply_inverse());
+        assert!(matches!(
+            apply_preconditioner(sketchy, x.view(), y.view_mut()),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+        assert!(matches!(
+            sketchy.apply_inverse(x.view(), y.view_mut()),
+            Err(SparseLinAlgError::NotImplemented(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_mult_dyn() -> SparseLinAlgResult<()> {
         let x = SimpleVector {};
@@ -167,6 +535,34 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_mult_adjoint_dyn() -> SparseLinAlgResult<()> {
+        let x = SimpleVector {};
+        let mut y = SimpleVector {};
+        let sparse: Box<dyn OperatorBase<Domain = SimpleSpace, Range = SimpleSpace>> =
+            Box::new(SparseMatrix);
+        assert!(sparse.has_apply_transpose());
+        assert!(sparse.has_apply_adjoint());
+        sparse.apply_transpose(x.view(), y.view_mut())?;
+        sparse.apply_adjoint(x.view(), y.view_mut())?;
+
+        // Finite difference operators only support the forward application.
+        let fd: Box<dyn OperatorBase<Domain = SimpleSpace, Range = SimpleSpace>> =
+            Box::new(FiniteDifference);
+        assert!(fd.has_apply());
+        assert!(!fd.has_apply_transpose());
+        assert!(!fd.has_apply_adjoint());
+        assert!(matches!(
+            fd.apply_adjoint(x.view(), y.view_mut()),
+            Err(SparseLinAlgError::NotImplemented(_))
+        ));
+        assert!(matches!(
+            fd.apply_transpose(x.view(), y.view_mut()),
+            Err(SparseLinAlgError::NotImplemented(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_mult() -> SparseLinAlgResult<()> {
         let x = SimpleVector {};
@@ -0,0 +1,1029 @@
+//! Lazy combinations of operators.
+//!
+//! The combinators build operators like `alpha * A * B + C` from existing operators
+//! without assembling them. On construction they check that the spaces of their parts
+//! fit together, and they only advertise a capability such as `as_apply` if all of
+//! their parts support it. Temporary elements are created in the spaces of the parts
+//! on every application.
+//!
+//! The parts can be owned operators, references to operators or boxed trait objects.
+//! For the latter the operators of `std::ops` are overloaded, so that
+//! `2.0 * a * b + c` works for `a`, `b` and `c` of type [BoxedOperator].
+
+use std::fmt::Debug;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use cauchy::{c32, c64};
+use num::One;
+
+use crate::*;
+
+/// A boxed operator trait object.
+pub type BoxedOperator<'a, Domain, Range> =
+    Box<dyn OperatorBase<Domain = Domain, Range = Range> + 'a>;
+
+pub(crate) fn not_implemented(method: &str) -> SparseLinAlgError {
+    SparseLinAlgError::NotImplemented(method.to_string())
+}
+
+pub(crate) fn check_compatible<Space: LinearSpace>(
+    expected: &Space,
+    actual: &Space,
+    message: &str,
+) -> SparseLinAlgResult<()> {
+    if expected.is_compatible(actual) {
+        Ok(())
+    } else {
+        Err(SparseLinAlgError::IncompatibleSpaces(message.to_string()))
+    }
+}
+
+/// The sum `A + B` of two operators with the same domain and range.
+#[derive(Debug)]
+pub struct SumOperator<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> SumOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Domain = A::Domain, Range = A::Range>,
+{
+    /// Create the sum. Returns an error if the domains or the ranges are not compatible.
+    pub fn new(a: A, b: B) -> SparseLinAlgResult<Self> {
+        check_compatible(a.domain(), b.domain(), "the domains of the summands differ")?;
+        check_compatible(a.range(), b.range(), "the ranges of the summands differ")?;
+        Ok(Self { a, b })
+    }
+}
+
+impl<A, B> OperatorBase for SumOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Domain = A::Domain, Range = A::Range>,
+{
+    type Domain = A::Domain;
+    type Range = A::Range;
+
+    fn domain(&self) -> &Self::Domain {
+        self.a.domain()
+    }
+
+    fn range(&self) -> &Self::Range {
+        self.a.range()
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply() && self.b.has_apply() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_transpose(
+        &self,
+    ) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply_transpose() && self.b.has_apply_transpose() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply_adjoint() && self.b.has_apply_adjoint() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A, B> AsApply for SumOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Domain = A::Domain, Range = A::Range>,
+{
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        mut y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        let a = self.a.as_apply().ok_or_else(|| not_implemented("Apply"))?;
+        let b = self.b.as_apply().ok_or_else(|| not_implemented("Apply"))?;
+
+        let mut tmp = self.range().create_element();
+        b.apply(
+            <Self::Domain as LinearSpace>::reborrow_view(&x),
+            tmp.view_mut(),
+        )?;
+        a.apply(x, <Self::Range as LinearSpace>::reborrow_view_mut(&mut y))?;
+        self.range().axpy_inplace(
+            <Self::Range as LinearSpace>::F::one(),
+            &tmp.view(),
+            &mut <Self::Range as LinearSpace>::reborrow_view_mut(&mut y),
+        )?;
+        Ok(())
+    }
+}
+
+impl<A, B> AsApplyTranspose for SumOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Domain = A::Domain, Range = A::Range>,
+{
+    fn apply_transpose(
+        &self,
+        x: ElementView<Self::Range>,
+        mut y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let a = self
+            .a
+            .as_apply_transpose()
+            .ok_or_else(|| not_implemented("ApplyTranspose"))?;
+        let b = self
+            .b
+            .as_apply_transpose()
+            .ok_or_else(|| not_implemented("ApplyTranspose"))?;
+
+        let mut tmp = self.domain().create_element();
+        b.apply_transpose(
+            <Self::Range as LinearSpace>::reborrow_view(&x),
+            tmp.view_mut(),
+        )?;
+        a.apply_transpose(x, <Self::Domain as LinearSpace>::reborrow_view_mut(&mut y))?;
+        self.domain().axpy_inplace(
+            <Self::Domain as LinearSpace>::F::one(),
+            &tmp.view(),
+            &mut <Self::Domain as LinearSpace>::reborrow_view_mut(&mut y),
+        )?;
+        Ok(())
+    }
+}
+
+impl<A, B> AsApplyAdjoint for SumOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Domain = A::Domain, Range = A::Range>,
+{
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Self::Range>,
+        mut y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let a = self
+            .a
+            .as_apply_adjoint()
+            .ok_or_else(|| not_implemented("ApplyAdjoint"))?;
+        let b = self
+            .b
+            .as_apply_adjoint()
+            .ok_or_else(|| not_implemented("ApplyAdjoint"))?;
+
+        let mut tmp = self.domain().create_element();
+        b.apply_adjoint(
+            <Self::Range as LinearSpace>::reborrow_view(&x),
+            tmp.view_mut(),
+        )?;
+        a.apply_adjoint(x, <Self::Domain as LinearSpace>::reborrow_view_mut(&mut y))?;
+        self.domain().axpy_inplace(
+            <Self::Domain as LinearSpace>::F::one(),
+            &tmp.view(),
+            &mut <Self::Domain as LinearSpace>::reborrow_view_mut(&mut y),
+        )?;
+        Ok(())
+    }
+}
+
+/// The composition `A B` of two operators, where `B` is applied first.
+#[derive(Debug)]
+pub struct ProductOperator<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> ProductOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Range = A::Domain>,
+{
+    /// Create the product. Returns an error if the range of `B` is not compatible with
+    /// the domain of `A`.
+    pub fn new(a: A, b: B) -> SparseLinAlgResult<Self> {
+        check_compatible(
+            a.domain(),
+            b.range(),
+            "the range of the right factor is not the domain of the left factor",
+        )?;
+        Ok(Self { a, b })
+    }
+}
+
+impl<A, B> OperatorBase for ProductOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Range = A::Domain>,
+{
+    type Domain = B::Domain;
+    type Range = A::Range;
+
+    fn domain(&self) -> &Self::Domain {
+        self.b.domain()
+    }
+
+    fn range(&self) -> &Self::Range {
+        self.a.range()
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply() && self.b.has_apply() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_transpose(
+        &self,
+    ) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply_transpose() && self.b.has_apply_transpose() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply_adjoint() && self.b.has_apply_adjoint() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A, B> AsApply for ProductOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Range = A::Domain>,
+{
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        let a = self.a.as_apply().ok_or_else(|| not_implemented("Apply"))?;
+        let b = self.b.as_apply().ok_or_else(|| not_implemented("Apply"))?;
+
+        let mut tmp = self.b.range().create_element();
+        b.apply(x, tmp.view_mut())?;
+        a.apply(tmp.view(), y)?;
+        Ok(())
+    }
+}
+
+impl<A, B> AsApplyTranspose for ProductOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Range = A::Domain>,
+{
+    fn apply_transpose(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let a = self
+            .a
+            .as_apply_transpose()
+            .ok_or_else(|| not_implemented("ApplyTranspose"))?;
+        let b = self
+            .b
+            .as_apply_transpose()
+            .ok_or_else(|| not_implemented("ApplyTranspose"))?;
+
+        // (A B)^T = B^T A^T
+        let mut tmp = self.a.domain().create_element();
+        a.apply_transpose(x, tmp.view_mut())?;
+        b.apply_transpose(tmp.view(), y)?;
+        Ok(())
+    }
+}
+
+impl<A, B> AsApplyAdjoint for ProductOperator<A, B>
+where
+    A: OperatorBase,
+    B: OperatorBase<Range = A::Domain>,
+{
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let a = self
+            .a
+            .as_apply_adjoint()
+            .ok_or_else(|| not_implemented("ApplyAdjoint"))?;
+        let b = self
+            .b
+            .as_apply_adjoint()
+            .ok_or_else(|| not_implemented("ApplyAdjoint"))?;
+
+        // (A B)^H = B^H A^H
+        let mut tmp = self.a.domain().create_element();
+        a.apply_adjoint(x, tmp.view_mut())?;
+        b.apply_adjoint(tmp.view(), y)?;
+        Ok(())
+    }
+}
+
+/// The multiple `alpha A` of an operator.
+pub struct ScaledOperator<A: OperatorBase> {
+    alpha: <A::Range as LinearSpace>::F,
+    a: A,
+}
+
+impl<A: OperatorBase> Debug for ScaledOperator<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScaledOperator")
+            .field("alpha", &self.alpha)
+            .field("a", &self.a)
+            .finish()
+    }
+}
+
+impl<A> ScaledOperator<A>
+where
+    A: OperatorBase,
+    A::Domain: LinearSpace<F = <A::Range as LinearSpace>::F>,
+{
+    pub fn new(alpha: <A::Range as LinearSpace>::F, a: A) -> Self {
+        Self { alpha, a }
+    }
+}
+
+impl<A> OperatorBase for ScaledOperator<A>
+where
+    A: OperatorBase,
+    A::Domain: LinearSpace<F = <A::Range as LinearSpace>::F>,
+{
+    type Domain = A::Domain;
+    type Range = A::Range;
+
+    fn domain(&self) -> &Self::Domain {
+        self.a.domain()
+    }
+
+    fn range(&self) -> &Self::Range {
+        self.a.range()
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_transpose(
+        &self,
+    ) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply_transpose() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply_adjoint() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A> AsApply for ScaledOperator<A>
+where
+    A: OperatorBase,
+    A::Domain: LinearSpace<F = <A::Range as LinearSpace>::F>,
+{
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        mut y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        let a = self.a.as_apply().ok_or_else(|| not_implemented("Apply"))?;
+        a.apply(x, <Self::Range as LinearSpace>::reborrow_view_mut(&mut y))?;
+        self.range().scale_inplace(self.alpha, &mut y);
+        Ok(())
+    }
+}
+
+impl<A> AsApplyTranspose for ScaledOperator<A>
+where
+    A: OperatorBase,
+    A::Domain: LinearSpace<F = <A::Range as LinearSpace>::F>,
+{
+    fn apply_transpose(
+        &self,
+        x: ElementView<Self::Range>,
+        mut y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let a = self
+            .a
+            .as_apply_transpose()
+            .ok_or_else(|| not_implemented("ApplyTranspose"))?;
+        a.apply_transpose(x, <Self::Domain as LinearSpace>::reborrow_view_mut(&mut y))?;
+        self.domain().scale_inplace(self.alpha, &mut y);
+        Ok(())
+    }
+}
+
+impl<A> AsApplyAdjoint for ScaledOperator<A>
+where
+    A: OperatorBase,
+    A::Domain: LinearSpace<F = <A::Range as LinearSpace>::F>,
+{
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Self::Range>,
+        mut y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        let a = self
+            .a
+            .as_apply_adjoint()
+            .ok_or_else(|| not_implemented("ApplyAdjoint"))?;
+        a.apply_adjoint(x, <Self::Domain as LinearSpace>::reborrow_view_mut(&mut y))?;
+        self.domain().scale_inplace(self.alpha.conj(), &mut y);
+        Ok(())
+    }
+}
+
+/// The adjoint `A^H` of an operator, which maps the range of `A` into its domain.
+///
+/// It can be applied if `A` supports `as_apply_adjoint`, and its adjoint is `A` itself.
+/// The transpose of `A^H` is the complex conjugate of `A` and is not available.
+#[derive(Debug)]
+pub struct AdjointOperator<A> {
+    a: A,
+}
+
+impl<A: OperatorBase> AdjointOperator<A> {
+    pub fn new(a: A) -> Self {
+        Self { a }
+    }
+}
+
+impl<A: OperatorBase> OperatorBase for AdjointOperator<A> {
+    type Domain = A::Range;
+    type Range = A::Domain;
+
+    fn domain(&self) -> &Self::Domain {
+        self.a.range()
+    }
+
+    fn range(&self) -> &Self::Range {
+        self.a.domain()
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply_adjoint() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        if self.a.has_apply() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A: OperatorBase> AsApply for AdjointOperator<A> {
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        self.a
+            .as_apply_adjoint()
+            .ok_or_else(|| not_implemented("Apply"))?
+            .apply_adjoint(x, y)
+    }
+}
+
+impl<A: OperatorBase> AsApplyAdjoint for AdjointOperator<A> {
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        self.a
+            .as_apply()
+            .ok_or_else(|| not_implemented("ApplyAdjoint"))?
+            .apply(x, y)
+    }
+}
+
+/// The identity operator on a space.
+pub struct IdentityOperator<Space: LinearSpace> {
+    space: Space,
+}
+
+impl<Space: LinearSpace> IdentityOperator<Space> {
+    pub fn new(space: Space) -> Self {
+        Self { space }
+    }
+
+    fn copy(&self, x: ElementView<Space>, mut y: ElementViewMut<Space>) -> SparseLinAlgResult<()> {
+        self.space.fill_inplace(
+            &Space::reborrow_view(&x),
+            &mut Space::reborrow_view_mut(&mut y),
+        )
+    }
+}
+
+impl<Space: LinearSpace> Debug for IdentityOperator<Space> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdentityOperator").finish_non_exhaustive()
+    }
+}
+
+impl<Space: LinearSpace> OperatorBase for IdentityOperator<Space> {
+    type Domain = Space;
+    type Range = Space;
+
+    fn domain(&self) -> &Self::Domain {
+        &self.space
+    }
+
+    fn range(&self) -> &Self::Range {
+        &self.space
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_transpose(
+        &self,
+    ) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_inverse(
+        &self,
+    ) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<Space: LinearSpace> AsApply for IdentityOperator<Space> {
+    fn apply(&self, x: ElementView<Space>, y: ElementViewMut<Space>) -> SparseLinAlgResult<()> {
+        self.copy(x, y)
+    }
+}
+
+impl<Space: LinearSpace> AsApplyTranspose for IdentityOperator<Space> {
+    fn apply_transpose(
+        &self,
+        x: ElementView<Space>,
+        y: ElementViewMut<Space>,
+    ) -> SparseLinAlgResult<()> {
+        self.copy(x, y)
+    }
+}
+
+impl<Space: LinearSpace> AsApplyAdjoint for IdentityOperator<Space> {
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Space>,
+        y: ElementViewMut<Space>,
+    ) -> SparseLinAlgResult<()> {
+        self.copy(x, y)
+    }
+}
+
+impl<Space: LinearSpace> AsApplyInverse for IdentityOperator<Space> {
+    fn apply_inverse(
+        &self,
+        x: ElementView<Space>,
+        y: ElementViewMut<Space>,
+    ) -> SparseLinAlgResult<()> {
+        self.copy(x, y)
+    }
+}
+
+/// The zero operator between two spaces.
+pub struct ZeroOperator<Domain: LinearSpace, Range: LinearSpace> {
+    domain: Domain,
+    range: Range,
+}
+
+impl<Domain: LinearSpace, Range: LinearSpace> ZeroOperator<Domain, Range> {
+    pub fn new(domain: Domain, range: Range) -> Self {
+        Self { domain, range }
+    }
+}
+
+/// Set `y` to zero by copying a newly created element of `space`.
+pub(crate) fn set_zero<Space: LinearSpace>(
+    space: &Space,
+    mut y: ElementViewMut<Space>,
+) -> SparseLinAlgResult<()> {
+    let zero = space.create_element();
+    space.fill_inplace(&zero.view(), &mut Space::reborrow_view_mut(&mut y))?;
+    Ok(())
+}
+
+impl<Domain: LinearSpace, Range: LinearSpace> Debug for ZeroOperator<Domain, Range> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZeroOperator").finish_non_exhaustive()
+    }
+}
+
+impl<Domain: LinearSpace, Range: LinearSpace> OperatorBase for ZeroOperator<Domain, Range> {
+    type Domain = Domain;
+    type Range = Range;
+
+    fn domain(&self) -> &Self::Domain {
+        &self.domain
+    }
+
+    fn range(&self) -> &Self::Range {
+        &self.range
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_transpose(
+        &self,
+    ) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+}
+
+impl<Domain: LinearSpace, Range: LinearSpace> AsApply for ZeroOperator<Domain, Range> {
+    fn apply(&self, _x: ElementView<Domain>, y: ElementViewMut<Range>) -> SparseLinAlgResult<()> {
+        set_zero(&self.range, y)
+    }
+}
+
+impl<Domain: LinearSpace, Range: LinearSpace> AsApplyTranspose for ZeroOperator<Domain, Range> {
+    fn apply_transpose(
+        &self,
+        _x: ElementView<Range>,
+        y: ElementViewMut<Domain>,
+    ) -> SparseLinAlgResult<()> {
+        set_zero(&self.domain, y)
+    }
+}
+
+impl<Domain: LinearSpace, Range: LinearSpace> AsApplyAdjoint for ZeroOperator<Domain, Range> {
+    fn apply_adjoint(
+        &self,
+        _x: ElementView<Range>,
+        y: ElementViewMut<Domain>,
+    ) -> SparseLinAlgResult<()> {
+        set_zero(&self.domain, y)
+    }
+}
+
+// The operator overloads panic on incompatible spaces since they cannot return
+// an error. Use the constructors of the combinators for a fallible version.
+
+impl<'a, Domain, Range> Add for BoxedOperator<'a, Domain, Range>
+where
+    Domain: LinearSpace + 'a,
+    Range: LinearSpace + 'a,
+{
+    type Output = BoxedOperator<'a, Domain, Range>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Box::new(SumOperator::new(self, rhs).unwrap_or_else(|err| panic!("{err}")))
+    }
+}
+
+impl<'a, Domain, Range> Sub for BoxedOperator<'a, Domain, Range>
+where
+    Domain: LinearSpace<F = Range::F> + 'a,
+    Range: LinearSpace + 'a,
+{
+    type Output = BoxedOperator<'a, Domain, Range>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + (-rhs)
+    }
+}
+
+impl<'a, Domain, Range> Neg for BoxedOperator<'a, Domain, Range>
+where
+    Domain: LinearSpace<F = Range::F> + 'a,
+    Range: LinearSpace + 'a,
+{
+    type Output = BoxedOperator<'a, Domain, Range>;
+
+    fn neg(self) -> Self::Output {
+        Box::new(ScaledOperator::new(-Range::F::one(), self))
+    }
+}
+
+impl<'a, Domain, Middle, Range> Mul<BoxedOperator<'a, Domain, Middle>>
+    for BoxedOperator<'a, Middle, Range>
+where
+    Domain: LinearSpace + 'a,
+    Middle: LinearSpace + 'a,
+    Range: LinearSpace + 'a,
+{
+    type Output = BoxedOperator<'a, Domain, Range>;
+
+    fn mul(self, rhs: BoxedOperator<'a, Domain, Middle>) -> Self::Output {
+        Box::new(ProductOperator::new(self, rhs).unwrap_or_else(|err| panic!("{err}")))
+    }
+}
+
+macro_rules! scalar_times_operator {
+    ($scalar:ty) => {
+        impl<'a, Domain, Range> Mul<BoxedOperator<'a, Domain, Range>> for $scalar
+        where
+            Domain: LinearSpace<F = $scalar> + 'a,
+            Range: LinearSpace<F = $scalar> + 'a,
+        {
+            type Output = BoxedOperator<'a, Domain, Range>;
+
+            fn mul(self, rhs: BoxedOperator<'a, Domain, Range>) -> Self::Output {
+                Box::new(ScaledOperator::new(self, rhs))
+            }
+        }
+    };
+}
+
+scalar_times_operator!(f32);
+scalar_times_operator!(f64);
+scalar_times_operator!(c32);
+scalar_times_operator!(c64);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::operator::test_spaces::VectorSpace;
+
+    // A dense real matrix in row-major order. The adjoint can be disabled to model
+    // operators that only provide their action.
+    #[derive(Debug)]
+    struct Matrix {
+        domain: VectorSpace,
+        range: VectorSpace,
+        data: Vec<f64>,
+        has_adjoint: bool,
+    }
+
+    impl Matrix {
+        fn new(rows: usize, cols: usize, data: &[f64]) -> Self {
+            Self {
+                domain: VectorSpace(cols),
+                range: VectorSpace(rows),
+                data: data.to_vec(),
+                has_adjoint: true,
+            }
+        }
+
+        fn without_adjoint(mut self) -> Self {
+            self.has_adjoint = false;
+            self
+        }
+
+        fn boxed<'a>(self) -> BoxedOperator<'a, VectorSpace, VectorSpace> {
+            Box::new(self)
+        }
+    }
+
+    impl OperatorBase for Matrix {
+        type Domain = VectorSpace;
+        type Range = VectorSpace;
+
+        fn domain(&self) -> &VectorSpace {
+            &self.domain
+        }
+
+        fn range(&self) -> &VectorSpace {
+            &self.range
+        }
+
+        fn as_apply(&self) -> Option<&dyn AsApply<Domain = VectorSpace, Range = VectorSpace>> {
+            Some(self)
+        }
+
+        fn as_apply_transpose(
+            &self,
+        ) -> Option<&dyn AsApplyTranspose<Domain = VectorSpace, Range = VectorSpace>> {
+            if self.has_adjoint {
+                Some(self)
+            } else {
+                None
+            }
+        }
+
+        fn as_apply_adjoint(
+            &self,
+        ) -> Option<&dyn AsApplyAdjoint<Domain = VectorSpace, Range = VectorSpace>> {
+            if self.has_adjoint {
+                Some(self)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl AsApply for Matrix {
+        fn apply(&self, x: &[f64], y: &mut [f64]) -> SparseLinAlgResult<()> {
+            for (row, out) in y.iter_mut().enumerate() {
+                *out = (0..self.domain.0)
+                    .map(|col| self.data[row * self.domain.0 + col] * x[col])
+                    .sum();
+            }
+            Ok(())
+        }
+    }
+
+    impl AsApplyTranspose for Matrix {
+        fn apply_transpose(&self, x: &[f64], y: &mut [f64]) -> SparseLinAlgResult<()> {
+            for (col, out) in y.iter_mut().enumerate() {
+                *out = (0..self.range.0)
+                    .map(|row| self.data[row * self.domain.0 + col] * x[row])
+                    .sum();
+            }
+            Ok(())
+        }
+    }
+
+    impl AsApplyAdjoint for Matrix {
+        fn apply_adjoint(&self, x: &[f64], y: &mut [f64]) -> SparseLinAlgResult<()> {
+            self.apply_transpose(x, y)
+        }
+    }
+
+    fn a() -> Matrix {
+        Matrix::new(2, 3, &[1.0, 2.0, 0.0, -1.0, 0.0, 3.0])
+    }
+
+    fn b() -> Matrix {
+        Matrix::new(3, 2, &[1.0, 1.0, 0.0, 2.0, -2.0, 1.0])
+    }
+
+    fn c() -> Matrix {
+        Matrix::new(2, 2, &[4.0, 0.0, 1.0, -1.0])
+    }
+
+    #[test]
+    fn test_linear_combination() -> SparseLinAlgResult<()> {
+        // 2 A B + C = [[6, 10], [-13, 3]]
+        let op = 2.0 * a().boxed() * b().boxed() + c().boxed();
+        assert!(op.has_apply());
+        assert!(op.has_apply_transpose());
+        assert!(op.has_apply_adjoint());
+
+        let mut y = [0.0; 2];
+        op.apply(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [26.0, -7.0]);
+
+        op.apply_adjoint(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [-20.0, 16.0]);
+        op.apply_transpose(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [-20.0, 16.0]);
+
+        // A - A and -A + A vanish.
+        let op = a().boxed() - a().boxed();
+        let mut y = [1.0; 2];
+        op.apply(&[1.0, 2.0, 3.0], &mut y)?;
+        assert_eq!(y, [0.0, 0.0]);
+        let op = -a().boxed() + a().boxed();
+        op.apply(&[1.0, 2.0, 3.0], &mut y)?;
+        assert_eq!(y, [0.0, 0.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_borrowed_parts() -> SparseLinAlgResult<()> {
+        let (a, b) = (a(), b());
+        let product = ProductOperator::new(&a, &b)?;
+        let scaled = ScaledOperator::new(0.5, &product);
+        let adjoint = AdjointOperator::new(&a);
+
+        let mut y = [0.0; 2];
+        scaled.apply(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [5.5, -1.5]);
+
+        let mut y = [0.0; 3];
+        adjoint.apply(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [-1.0, 2.0, 6.0]);
+        assert_eq!(adjoint.domain().0, 2);
+        assert_eq!(adjoint.range().0, 3);
+
+        // The adjoint of the adjoint is the operator itself.
+        let mut y = [0.0; 2];
+        adjoint.apply_adjoint(&[1.0, 0.0, 1.0], &mut y)?;
+        assert_eq!(y, [1.0, 2.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_incompatible_spaces() {
+        assert!(matches!(
+            SumOperator::new(a(), c()),
+            Err(SparseLinAlgError::IncompatibleSpaces(_))
+        ));
+        assert!(matches!(
+            ProductOperator::new(a(), a()),
+            Err(SparseLinAlgError::IncompatibleSpaces(_))
+        ));
+        assert!(ProductOperator::new(b(), a()).is_ok());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_incompatible_sum_panics() {
+        let _ = a().boxed() + c().boxed();
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let sum = SumOperator::new(c(), c().without_adjoint()).unwrap();
+        assert!(sum.has_apply());
+        assert!(!sum.has_apply_transpose());
+        assert!(!sum.has_apply_adjoint());
+        assert!(matches!(
+            sum.apply_adjoint(&[1.0, 1.0], &mut [0.0; 2]),
+            Err(SparseLinAlgError::NotImplemented(_))
+        ));
+
+        let adjoint = AdjointOperator::new(sum);
+        assert!(!adjoint.has_apply());
+        assert!(adjoint.has_apply_adjoint());
+        assert!(!adjoint.has_apply_transpose());
+
+        let op: BoxedOperator<_, _> = Box::new(adjoint);
+        assert!(matches!(
+            op.apply(&[1.0, 1.0], &mut [0.0; 2]),
+            Err(SparseLinAlgError::NotImplemented(_))
+        ));
+    }
+
+    #[test]
+    fn test_identity_and_zero() -> SparseLinAlgResult<()> {
+        let identity: BoxedOperator<_, _> = Box::new(IdentityOperator::new(VectorSpace(2)));
+        let zero: BoxedOperator<_, _> = Box::new(ZeroOperator::new(VectorSpace(2), VectorSpace(2)));
+        let op = identity + 3.0 * zero - c().boxed();
+
+        let mut y = [5.0; 2];
+        op.apply(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [-3.0, 3.0]);
+        op.apply_adjoint(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [-5.0, 4.0]);
+        assert!(!op.has_apply_inverse());
+
+        let identity = IdentityOperator::new(VectorSpace(2));
+        identity
+            .as_apply_inverse()
+            .unwrap()
+            .apply_inverse(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [1.0, 2.0]);
+
+        let zero = ZeroOperator::new(VectorSpace(3), VectorSpace(2));
+        let mut y = [5.0; 3];
+        zero.apply_transpose(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [0.0; 3]);
+        Ok(())
+    }
+}
@@ -0,0 +1,756 @@
+//! Block operators on product spaces.
+//!
+//! A [BlockOperator] maps a [ProductSpace] into another one and applies an operator per
+//! block, as in the saddle point system `[A, B^T; B, 0]` of a Stokes problem. Zero blocks
+//! are `None` and cost nothing. With preconditioners for the diagonal blocks,
+//! [BlockOperator::block_diagonal] and [BlockTriangularPreconditioner] give the usual
+//! block preconditioners for such systems.
+
+use std::fmt::Debug;
+
+use num::One;
+
+use super::algebra::{check_compatible, not_implemented, set_zero};
+use crate::*;
+
+/// The blocks of a block operator, stored by rows. Zero blocks are `None`.
+pub type Blocks<'a, Domain, Range> = Vec<Vec<Option<BoxedOperator<'a, Domain, Range>>>>;
+
+/// Compute `y = sum_k term_k` if `apply(k, z)` writes the k-th of `count` terms into `z`.
+///
+/// The first term is written into `y` directly and `y` is zero if there are no terms.
+fn accumulate<Space, F>(
+    space: &Space,
+    count: usize,
+    mut y: ElementViewMut<Space>,
+    mut apply: F,
+) -> SparseLinAlgResult<()>
+where
+    Space: LinearSpace,
+    F: FnMut(usize, ElementViewMut<Space>) -> SparseLinAlgResult<()>,
+{
+    if count == 0 {
+        return set_zero(space, y);
+    }
+    apply(0, Space::reborrow_view_mut(&mut y))?;
+    if count > 1 {
+        let mut tmp = space.create_element();
+        for k in 1..count {
+            apply(k, tmp.view_mut())?;
+            space.axpy_inplace(
+                Space::F::one(),
+                &tmp.view(),
+                &mut Space::reborrow_view_mut(&mut y),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Check that `blocks` has the shape `rows x cols`.
+fn check_shape<T>(blocks: &[Vec<T>], rows: usize, cols: usize) -> SparseLinAlgResult<()> {
+    if blocks.len() != rows {
+        return Err(SparseLinAlgError::SingleDimensionError {
+            expected: rows,
+            actual: blocks.len(),
+        });
+    }
+    for row in blocks {
+        if row.len() != cols {
+            return Err(SparseLinAlgError::SingleDimensionError {
+                expected: cols,
+                actual: row.len(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check that the block `(row, col)` maps `domain` into `range`.
+fn check_block<Domain: LinearSpace, Range: LinearSpace>(
+    block: &dyn OperatorBase<Domain = Domain, Range = Range>,
+    domain: &Domain,
+    range: &Range,
+    row: usize,
+    col: usize,
+) -> SparseLinAlgResult<()> {
+    check_compatible(
+        block.domain(),
+        domain,
+        &format!("the domain of block ({row}, {col}) is not component {col} of the domain"),
+    )?;
+    check_compatible(
+        block.range(),
+        range,
+        &format!("the range of block ({row}, {col}) is not component {row} of the range"),
+    )
+}
+
+/// An operator between product spaces that is given by its blocks.
+///
+/// The block `(i, j)` maps component `j` of the domain into component `i` of the range,
+/// and `y_i = sum_j A_ij x_j`. The operator supports a capability if all of its nonzero
+/// blocks support it. The transpose and the adjoint are applied blockwise with the
+/// transposed layout.
+pub struct BlockOperator<'a, Domain: LinearSpace, Range: LinearSpace> {
+    domain: ProductSpace<Domain>,
+    range: ProductSpace<Range>,
+    blocks: Blocks<'a, Domain, Range>,
+}
+
+impl<'a, Domain: LinearSpace, Range: LinearSpace> BlockOperator<'a, Domain, Range> {
+    /// Create a block operator from its blocks, stored by rows.
+    ///
+    /// Returns an error if the layout of the blocks does not match the number of
+    /// components of `range` and `domain`, or if a block does not fit its components.
+    pub fn new(
+        domain: ProductSpace<Domain>,
+        range: ProductSpace<Range>,
+        blocks: Blocks<'a, Domain, Range>,
+    ) -> SparseLinAlgResult<Self> {
+        check_shape(&blocks, range.num_components(), domain.num_components())?;
+        for (row, blocks) in blocks.iter().enumerate() {
+            for (col, block) in blocks.iter().enumerate() {
+                if let Some(block) = block {
+                    check_block(
+                        block.as_ref(),
+                        domain.component(col),
+                        range.component(row),
+                        row,
+                        col,
+                    )?;
+                }
+            }
+        }
+        Ok(Self {
+            domain,
+            range,
+            blocks,
+        })
+    }
+
+    /// Create the block diagonal operator `diag(A_0, ..., A_{n-1})`.
+    ///
+    /// The spaces are the products of the spaces of the blocks. Passing approximate
+    /// inverses of the diagonal blocks of a system gives a block Jacobi preconditioner.
+    /// If all blocks support `as_apply_inverse`, so does the block diagonal operator,
+    /// and it can be passed as a preconditioner as well.
+    pub fn block_diagonal(diagonal: Vec<BoxedOperator<'a, Domain, Range>>) -> Self
+    where
+        Domain: Clone,
+        Range: Clone,
+    {
+        let domain = ProductSpace::new(diagonal.iter().map(|op| op.domain().clone()).collect());
+        let range = ProductSpace::new(diagonal.iter().map(|op| op.range().clone()).collect());
+        let n = diagonal.len();
+        let blocks = diagonal
+            .into_iter()
+            .enumerate()
+            .map(|(row, op)| {
+                let mut blocks: Vec<_> = (0..n).map(|_| None).collect();
+                blocks[row] = Some(op);
+                blocks
+            })
+            .collect();
+        Self {
+            domain,
+            range,
+            blocks,
+        }
+    }
+
+    /// The block `(row, col)`, or `None` for a zero block.
+    pub fn block(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> Option<&(dyn OperatorBase<Domain = Domain, Range = Range> + 'a)> {
+        self.blocks[row][col].as_deref()
+    }
+
+    /// Iterate over the nonzero blocks.
+    fn nonzero_blocks(
+        &self,
+    ) -> impl Iterator<Item = &(dyn OperatorBase<Domain = Domain, Range = Range> + 'a)> {
+        self.blocks
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|block| block.as_ref())
+    }
+
+    /// Return true if all blocks off the diagonal are zero.
+    fn is_block_diagonal(&self) -> bool {
+        self.blocks.iter().enumerate().all(|(row, blocks)| {
+            blocks
+                .iter()
+                .enumerate()
+                .all(|(col, block)| row == col || block.is_none())
+        })
+    }
+}
+
+impl<'a, Domain: LinearSpace, Range: LinearSpace> Debug for BlockOperator<'a, Domain, Range> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockOperator")
+            .field("blocks", &self.blocks)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, Domain: LinearSpace, Range: LinearSpace> OperatorBase
+    for BlockOperator<'a, Domain, Range>
+{
+    type Domain = ProductSpace<Domain>;
+    type Range = ProductSpace<Range>;
+
+    fn domain(&self) -> &Self::Domain {
+        &self.domain
+    }
+
+    fn range(&self) -> &Self::Range {
+        &self.range
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        if self.nonzero_blocks().all(|block| block.has_apply()) {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_transpose(
+        &self,
+    ) -> Option<&dyn AsApplyTranspose<Domain = Self::Domain, Range = Self::Range>> {
+        if self
+            .nonzero_blocks()
+            .all(|block| block.has_apply_transpose())
+        {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        if self.nonzero_blocks().all(|block| block.has_apply_adjoint()) {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn as_apply_inverse(
+        &self,
+    ) -> Option<&dyn AsApplyInverse<Domain = Self::Domain, Range = Self::Range>> {
+        let square = self.domain.num_components() == self.range.num_components();
+        let invertible = (0..self.domain.num_components()).all(|index| {
+            self.block(index, index)
+                .is_some_and(|block| block.has_apply_inverse())
+        });
+        if square && self.is_block_diagonal() && invertible {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, Domain: LinearSpace, Range: LinearSpace> AsApply for BlockOperator<'a, Domain, Range> {
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        mut y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        self.domain.check_components(x.len())?;
+        self.range.check_components(y.len())?;
+
+        for ((row, space), y) in self
+            .blocks
+            .iter()
+            .zip(self.range.components())
+            .zip(y.iter_mut())
+        {
+            let terms = row
+                .iter()
+                .zip(x.iter())
+                .filter_map(|(block, x)| block.as_ref().map(|block| (block, x)))
+                .map(|(block, x)| {
+                    Ok((block.as_apply().ok_or_else(|| not_implemented("Apply"))?, x))
+                })
+                .collect::<SparseLinAlgResult<Vec<_>>>()?;
+            accumulate(space, terms.len(), Range::reborrow_view_mut(y), |k, z| {
+                terms[k].0.apply(Domain::reborrow_view(terms[k].1), z)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, Domain: LinearSpace, Range: LinearSpace> AsApplyTranspose
+    for BlockOperator<'a, Domain, Range>
+{
+    fn apply_transpose(
+        &self,
+        x: ElementView<Self::Range>,
+        mut y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        self.range.check_components(x.len())?;
+        self.domain.check_components(y.len())?;
+
+        for (col, (space, y)) in self
+            .domain
+            .components()
+            .iter()
+            .zip(y.iter_mut())
+            .enumerate()
+        {
+            let terms = self
+                .blocks
+                .iter()
+                .zip(x.iter())
+                .filter_map(|(row, x)| row[col].as_ref().map(|block| (block, x)))
+                .map(|(block, x)| {
+                    Ok((
+                        block
+                            .as_apply_transpose()
+                            .ok_or_else(|| not_implemented("ApplyTranspose"))?,
+                        x,
+                    ))
+                })
+                .collect::<SparseLinAlgResult<Vec<_>>>()?;
+            accumulate(space, terms.len(), Domain::reborrow_view_mut(y), |k, z| {
+                terms[k]
+                    .0
+                    .apply_transpose(Range::reborrow_view(terms[k].1), z)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, Domain: LinearSpace, Range: LinearSpace> AsApplyAdjoint
+    for BlockOperator<'a, Domain, Range>
+{
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Self::Range>,
+        mut y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        self.range.check_components(x.len())?;
+        self.domain.check_components(y.len())?;
+
+        for (col, (space, y)) in self
+            .domain
+            .components()
+            .iter()
+            .zip(y.iter_mut())
+            .enumerate()
+        {
+            let terms = self
+                .blocks
+                .iter()
+                .zip(x.iter())
+                .filter_map(|(row, x)| row[col].as_ref().map(|block| (block, x)))
+                .map(|(block, x)| {
+                    Ok((
+                        block
+                            .as_apply_adjoint()
+                            .ok_or_else(|| not_implemented("ApplyAdjoint"))?,
+                        x,
+                    ))
+                })
+                .collect::<SparseLinAlgResult<Vec<_>>>()?;
+            accumulate(space, terms.len(), Domain::reborrow_view_mut(y), |k, z| {
+                terms[k]
+                    .0
+                    .apply_adjoint(Range::reborrow_view(terms[k].1), z)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, Domain: LinearSpace, Range: LinearSpace> AsApplyInverse
+    for BlockOperator<'a, Domain, Range>
+{
+    fn apply_inverse(
+        &self,
+        x: ElementView<Self::Range>,
+        mut y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        if !self.is_block_diagonal() {
+            return Err(not_implemented("ApplyInverse"));
+        }
+        self.range.check_components(x.len())?;
+        self.domain.check_components(y.len())?;
+
+        for (index, (x, y)) in x.iter().zip(y.iter_mut()).enumerate() {
+            self.block(index, index)
+                .and_then(|block| block.as_apply_inverse())
+                .ok_or_else(|| not_implemented("ApplyInverse"))?
+                .apply_inverse(Range::reborrow_view(x), Domain::reborrow_view_mut(y))?;
+        }
+        Ok(())
+    }
+}
+
+/// Which triangle of a block triangular matrix is occupied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTriangle {
+    Lower,
+    Upper,
+}
+
+/// The inverse of a block triangular matrix, applied by block substitution.
+///
+/// The matrix is given by preconditioners `M_i` for its diagonal blocks and by its
+/// blocks strictly below (or above) the diagonal. For the lower triangle the operator
+/// computes `z_i = M_i (r_i - sum_{j < i} A_ij z_j)` for `i = 0, 1, ...`, for the upper
+/// triangle it proceeds backwards. For `[A, B^T; B, 0]` the upper variant with the
+/// blocks `A` and the Schur complement `S = -B A^{-1} B^T` on the diagonal is the
+/// classical block triangular preconditioner, with which GMRES converges in two steps
+/// if the inverses are exact.
+///
+/// As in the solvers, `M_i` is inverted if it supports `as_apply_inverse` and applied
+/// directly otherwise, see [apply_preconditioner].
+pub struct BlockTriangularPreconditioner<'a, Space: LinearSpace> {
+    space: ProductSpace<Space>,
+    triangle: BlockTriangle,
+    inverses: Vec<BoxedOperator<'a, Space, Space>>,
+    blocks: Blocks<'a, Space, Space>,
+}
+
+impl<'a, Space: LinearSpace + Clone> BlockTriangularPreconditioner<'a, Space> {
+    /// Create the preconditioner.
+    ///
+    /// `blocks` has the layout of the whole block matrix, but only the blocks strictly
+    /// inside `triangle` may be nonzero. Returns an error if the layout does not match
+    /// the number of inverses, if a block lies outside of the triangle or if a block does
+    /// not fit the spaces of the inverses.
+    pub fn new(
+        triangle: BlockTriangle,
+        inverses: Vec<BoxedOperator<'a, Space, Space>>,
+        blocks: Blocks<'a, Space, Space>,
+    ) -> SparseLinAlgResult<Self> {
+        let space = ProductSpace::new(inverses.iter().map(|op| op.domain().clone()).collect());
+        for (index, inverse) in inverses.iter().enumerate() {
+            check_compatible(
+                inverse.range(),
+                inverse.domain(),
+                &format!("the inverse of diagonal block {index} is not square"),
+            )?;
+        }
+        check_shape(&blocks, inverses.len(), inverses.len())?;
+        for (row, blocks) in blocks.iter().enumerate() {
+            for (col, block) in blocks.iter().enumerate() {
+                if let Some(block) = block {
+                    let inside = match triangle {
+                        BlockTriangle::Lower => col < row,
+                        BlockTriangle::Upper => col > row,
+                    };
+                    if !inside {
+                        return Err(SparseLinAlgError::InvalidStructure(format!(
+                            "block ({row}, {col}) lies outside of the strict {triangle:?} triangle"
+                        )));
+                    }
+                    check_block(
+                        block.as_ref(),
+                        space.component(col),
+                        space.component(row),
+                        row,
+                        col,
+                    )?;
+                }
+            }
+        }
+        Ok(Self {
+            space,
+            triangle,
+            inverses,
+            blocks,
+        })
+    }
+}
+
+impl<'a, Space: LinearSpace> Debug for BlockTriangularPreconditioner<'a, Space> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockTriangularPreconditioner")
+            .field("triangle", &self.triangle)
+            .field("inverses", &self.inverses)
+            .field("blocks", &self.blocks)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, Space: LinearSpace> OperatorBase for BlockTriangularPreconditioner<'a, Space> {
+    type Domain = ProductSpace<Space>;
+    type Range = ProductSpace<Space>;
+
+    fn domain(&self) -> &Self::Domain {
+        &self.space
+    }
+
+    fn range(&self) -> &Self::Range {
+        &self.space
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        let mut inverses = self.inverses.iter();
+        let mut blocks = self.blocks.iter().flatten().flatten();
+        if inverses.all(|op| op.has_apply_inverse() || op.has_apply())
+            && blocks.all(|op| op.has_apply())
+        {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, Space: LinearSpace> AsApply for BlockTriangularPreconditioner<'a, Space> {
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        mut y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        self.space.check_components(x.len())?;
+        self.space.check_components(y.len())?;
+
+        let n = self.inverses.len();
+        let order: Vec<usize> = match self.triangle {
+            BlockTriangle::Lower => (0..n).collect(),
+            BlockTriangle::Upper => (0..n).rev().collect(),
+        };
+
+        // The solved components are needed as inputs of the later ones, so they are
+        // kept in elements and copied into `y` at the end.
+        let mut z: Vec<_> = self
+            .space
+            .components()
+            .iter()
+            .map(|space| space.create_element())
+            .collect();
+        for &row in &order {
+            let space = self.space.component(row);
+
+            let mut rhs = space.create_element();
+            space.fill_inplace(&Space::reborrow_view(&x[row]), &mut rhs.view_mut())?;
+            let mut tmp = space.create_element();
+            for (col, block) in self.blocks[row].iter().enumerate() {
+                if let Some(block) = block {
+                    let block = block.as_apply().ok_or_else(|| not_implemented("Apply"))?;
+                    block.apply(z[col].view(), tmp.view_mut())?;
+                    space.axpy_inplace(-Space::F::one(), &tmp.view(), &mut rhs.view_mut())?;
+                }
+            }
+            apply_preconditioner(&self.inverses[row], rhs.view(), z[row].view_mut())?;
+        }
+
+        for ((space, z), y) in self.space.components().iter().zip(&z).zip(y.iter_mut()) {
+            space.fill_inplace(&z.view(), &mut Space::reborrow_view_mut(y))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::operator::test_spaces::VectorSpace;
+
+    /// The diagonal matrix with the given entries.
+    fn diagonal<'a>(
+        space: &'a VectorSpace,
+        diag: &'a [f64],
+    ) -> BoxedOperator<'a, VectorSpace, VectorSpace> {
+        let apply = move |x: &[f64], y: &mut [f64]| {
+            for ((out, x), d) in y.iter_mut().zip(x).zip(diag) {
+                *out = d * x;
+            }
+            Ok(())
+        };
+        Box::new(FnOperator::new(space, space, apply).with_adjoint(apply))
+    }
+
+    /// The row vector of ones mapping `domain` into a space of dimension one.
+    fn ones<'a>(
+        domain: &'a VectorSpace,
+        range: &'a VectorSpace,
+    ) -> BoxedOperator<'a, VectorSpace, VectorSpace> {
+        Box::new(
+            FnOperator::new(domain, range, |x: &[f64], y: &mut [f64]| {
+                y[0] = x.iter().sum();
+                Ok(())
+            })
+            .with_adjoint(|x: &[f64], y: &mut [f64]| {
+                y.fill(x[0]);
+                Ok(())
+            }),
+        )
+    }
+
+    #[test]
+    fn test_block_operator() -> SparseLinAlgResult<()> {
+        // The saddle point matrix [diag(2, 4), 1; 1^T, 0].
+        let velocity = VectorSpace(2);
+        let pressure = VectorSpace(1);
+        let space = ProductSpace::new(vec![velocity.clone(), pressure.clone()]);
+        let transposed_ones = AdjointOperator::new(ones(&velocity, &pressure));
+        let op = BlockOperator::new(
+            space.clone(),
+            space.clone(),
+            vec![
+                vec![
+                    Some(diagonal(&velocity, &[2.0, 4.0])),
+                    Some(Box::new(transposed_ones)),
+                ],
+                vec![Some(ones(&velocity, &pressure)), None],
+            ],
+        )?;
+        assert!(op.has_apply());
+        assert!(op.has_apply_adjoint());
+        assert!(!op.has_apply_transpose());
+        assert!(op.block(1, 1).is_none());
+        assert_eq!(op.block(1, 0).unwrap().domain().0, 2);
+
+        let x = vec![&[1.0, 2.0][..], &[3.0][..]];
+        let mut y = space.create_element();
+        op.apply(x.clone(), y.view_mut())?;
+        assert_eq!(y.view(), vec![&[5.0, 11.0][..], &[3.0][..]]);
+
+        // The matrix is symmetric.
+        op.apply_adjoint(x, y.view_mut())?;
+        assert_eq!(y.view(), vec![&[5.0, 11.0][..], &[3.0][..]]);
+
+        // A row without nonzero blocks gives zero.
+        let lower = BlockOperator::new(
+            space.clone(),
+            space.clone(),
+            vec![
+                vec![None, None],
+                vec![Some(ones(&velocity, &pressure)), None],
+            ],
+        )?;
+        y.view_mut()[0].fill(1.0);
+        lower.apply(vec![&[1.0, 2.0], &[3.0]], y.view_mut())?;
+        assert_eq!(y.view(), vec![&[0.0, 0.0][..], &[3.0][..]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_operator_errors() {
+        let velocity = VectorSpace(2);
+        let pressure = VectorSpace(1);
+        let space = ProductSpace::new(vec![velocity.clone(), pressure.clone()]);
+
+        // The block in the wrong position.
+        assert!(matches!(
+            BlockOperator::new(
+                space.clone(),
+                space.clone(),
+                vec![
+                    vec![None, Some(ones(&velocity, &pressure))],
+                    vec![None, None],
+                ],
+            ),
+            Err(SparseLinAlgError::IncompatibleSpaces(_))
+        ));
+        assert!(matches!(
+            BlockOperator::new(space.clone(), space.clone(), vec![vec![None, None]]),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 2,
+                actual: 1
+            })
+        ));
+        assert!(matches!(
+            BlockTriangularPreconditioner::new(
+                BlockTriangle::Upper,
+                vec![
+                    diagonal(&velocity, &[1.0, 1.0]),
+                    diagonal(&pressure, &[1.0])
+                ],
+                vec![
+                    vec![None, None],
+                    vec![Some(ones(&velocity, &pressure)), None],
+                ],
+            ),
+            Err(SparseLinAlgError::InvalidStructure(_))
+        ));
+    }
+
+    #[test]
+    fn test_block_preconditioners() -> SparseLinAlgResult<()> {
+        // Exact inverses of diag(2, 4) and of the Schur complement -3/4 of the saddle
+        // point matrix in `test_block_operator`.
+        let velocity = VectorSpace(2);
+        let pressure = VectorSpace(1);
+        let inverse = [0.5, 0.25];
+        let schur_inverse = [-4.0 / 3.0];
+        let r = vec![&[1.0, 1.0][..], &[1.0][..]];
+
+        let jacobi = BlockOperator::block_diagonal(vec![
+            diagonal(&velocity, &inverse),
+            diagonal(&pressure, &schur_inverse),
+        ]);
+        assert!(jacobi.has_apply_adjoint());
+        let mut z = jacobi.range().create_element();
+        jacobi.apply(r.clone(), z.view_mut())?;
+        assert_eq!(z.view(), vec![&[0.5, 0.25][..], &[-4.0 / 3.0][..]]);
+        assert!(!jacobi.has_apply_inverse());
+
+        let identity = BlockOperator::block_diagonal(vec![
+            Box::new(IdentityOperator::new(velocity.clone())) as BoxedOperator<_, _>,
+            Box::new(IdentityOperator::new(pressure.clone())),
+        ]);
+        assert!(identity.has_apply_inverse());
+        identity
+            .as_apply_inverse()
+            .unwrap()
+            .apply_inverse(r.clone(), z.view_mut())?;
+        assert_eq!(z.view(), r);
+
+        let lower = BlockTriangularPreconditioner::new(
+            BlockTriangle::Lower,
+            vec![
+                diagonal(&velocity, &inverse),
+                diagonal(&pressure, &schur_inverse),
+            ],
+            vec![
+                vec![None, None],
+                vec![Some(ones(&velocity, &pressure)), None],
+            ],
+        )?;
+        assert!(lower.has_apply());
+        assert!(!lower.has_apply_adjoint());
+        lower.apply(r.clone(), z.view_mut())?;
+        assert_eq!(z.view()[0], &[0.5, 0.25]);
+        assert!((z.view()[1][0] + 1.0 / 3.0).abs() < 1E-15);
+
+        let upper = BlockTriangularPreconditioner::new(
+            BlockTriangle::Upper,
+            vec![
+                diagonal(&velocity, &inverse),
+                diagonal(&pressure, &schur_inverse),
+            ],
+            vec![
+                vec![
+                    None,
+                    Some(Box::new(AdjointOperator::new(ones(&velocity, &pressure)))),
+                ],
+                vec![None, None],
+            ],
+        )?;
+        upper.apply(r, z.view_mut())?;
+        let expected = [7.0 / 6.0, 7.0 / 12.0, -4.0 / 3.0];
+        let actual = [z.view()[0][0], z.view()[0][1], z.view()[1][0]];
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1E-15);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+//! Matrix-free operators defined by closures.
+
+use std::fmt::Debug;
+
+use crate::*;
+
+/// The type of the adjoint of an [FnOperator] without adjoint.
+pub type AdjointFn<Domain, Range> =
+    for<'a, 'b> fn(ElementView<'a, Range>, ElementViewMut<'b, Domain>) -> SparseLinAlgResult<()>;
+
+/// An operator whose application is given by a closure.
+///
+/// This avoids a struct per operator for Jacobian-vector products, stencils and other
+/// matrix-free operators. The closure receives a view of the input and writes the result
+/// into a view of the output. An optional second closure applies the adjoint and is added
+/// with [FnOperator::with_adjoint]; otherwise `as_apply_adjoint` returns `None`.
+///
+/// ```ignore
+/// let laplace = FnOperator::new(&space, &space, |x, y| stencil(x, y));
+/// cg(&space, &laplace, None, &b, &mut x, &options)?;
+/// ```
+pub struct FnOperator<'s, Domain, Range, Apply, Adjoint = AdjointFn<Domain, Range>>
+where
+    Domain: LinearSpace,
+    Range: LinearSpace,
+{
+    domain: &'s Domain,
+    range: &'s Range,
+    apply: Apply,
+    adjoint: Option<Adjoint>,
+}
+
+impl<'s, Domain, Range, Apply> FnOperator<'s, Domain, Range, Apply>
+where
+    Domain: LinearSpace,
+    Range: LinearSpace,
+    Apply: for<'a, 'b> Fn(
+        ElementView<'a, Domain>,
+        ElementViewMut<'b, Range>,
+    ) -> SparseLinAlgResult<()>,
+{
+    /// Create an operator from `domain` into `range` that applies `apply`.
+    pub fn new(domain: &'s Domain, range: &'s Range, apply: Apply) -> Self {
+        Self {
+            domain,
+            range,
+            apply,
+            adjoint: None,
+        }
+    }
+}
+
+impl<'s, Domain, Range, Apply, Adjoint> FnOperator<'s, Domain, Range, Apply, Adjoint>
+where
+    Domain: LinearSpace,
+    Range: LinearSpace,
+{
+    /// Add a closure that applies the adjoint and maps the range into the domain.
+    pub fn with_adjoint<NewAdjoint>(
+        self,
+        adjoint: NewAdjoint,
+    ) -> FnOperator<'s, Domain, Range, Apply, NewAdjoint>
+    where
+        NewAdjoint: for<'a, 'b> Fn(
+            ElementView<'a, Range>,
+            ElementViewMut<'b, Domain>,
+        ) -> SparseLinAlgResult<()>,
+    {
+        FnOperator {
+            domain: self.domain,
+            range: self.range,
+            apply: self.apply,
+            adjoint: Some(adjoint),
+        }
+    }
+}
+
+impl<'s, Domain, Range, Apply, Adjoint> Debug for FnOperator<'s, Domain, Range, Apply, Adjoint>
+where
+    Domain: LinearSpace,
+    Range: LinearSpace,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnOperator")
+            .field("adjoint", &self.adjoint.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'s, Domain, Range, Apply, Adjoint> OperatorBase
+    for FnOperator<'s, Domain, Range, Apply, Adjoint>
+where
+    Domain: LinearSpace,
+    Range: LinearSpace,
+    Apply: for<'a, 'b> Fn(
+        ElementView<'a, Domain>,
+        ElementViewMut<'b, Range>,
+    ) -> SparseLinAlgResult<()>,
+    Adjoint: for<'a, 'b> Fn(
+        ElementView<'a, Range>,
+        ElementViewMut<'b, Domain>,
+    ) -> SparseLinAlgResult<()>,
+{
+    type Domain = Domain;
+    type Range = Range;
+
+    fn domain(&self) -> &Self::Domain {
+        self.domain
+    }
+
+    fn range(&self) -> &Self::Range {
+        self.range
+    }
+
+    fn as_apply(&self) -> Option<&dyn AsApply<Domain = Self::Domain, Range = Self::Range>> {
+        Some(self)
+    }
+
+    fn as_apply_adjoint(
+        &self,
+    ) -> Option<&dyn AsApplyAdjoint<Domain = Self::Domain, Range = Self::Range>> {
+        self.adjoint.as_ref().map(|_| self as _)
+    }
+}
+
+impl<'s, Domain, Range, Apply, Adjoint> AsApply for FnOperator<'s, Domain, Range, Apply, Adjoint>
+where
+    Domain: LinearSpace,
+    Range: LinearSpace,
+    Apply: for<'a, 'b> Fn(
+        ElementView<'a, Domain>,
+        ElementViewMut<'b, Range>,
+    ) -> SparseLinAlgResult<()>,
+    Adjoint: for<'a, 'b> Fn(
+        ElementView<'a, Range>,
+        ElementViewMut<'b, Domain>,
+    ) -> SparseLinAlgResult<()>,
+{
+    fn apply(
+        &self,
+        x: ElementView<Self::Domain>,
+        y: ElementViewMut<Self::Range>,
+    ) -> SparseLinAlgResult<()> {
+        (self.apply)(x, y)
+    }
+}
+
+impl<'s, Domain, Range, Apply, Adjoint> AsApplyAdjoint
+    for FnOperator<'s, Domain, Range, Apply, Adjoint>
+where
+    Domain: LinearSpace,
+    Range: LinearSpace,
+    Apply: for<'a, 'b> Fn(
+        ElementView<'a, Domain>,
+        ElementViewMut<'b, Range>,
+    ) -> SparseLinAlgResult<()>,
+    Adjoint: for<'a, 'b> Fn(
+        ElementView<'a, Range>,
+        ElementViewMut<'b, Domain>,
+    ) -> SparseLinAlgResult<()>,
+{
+    fn apply_adjoint(
+        &self,
+        x: ElementView<Self::Range>,
+        y: ElementViewMut<Self::Domain>,
+    ) -> SparseLinAlgResult<()> {
+        match &self.adjoint {
+            Some(adjoint) => adjoint(x, y),
+            None => Err(SparseLinAlgError::NotImplemented(
+                "ApplyAdjoint".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::operator::test_spaces::VectorSpace;
+
+    #[test]
+    fn test_fn_operator() -> SparseLinAlgResult<()> {
+        // The difference operator (x_0, x_1, x_2) -> (x_1 - x_0, x_2 - x_1).
+        let domain = VectorSpace(3);
+        let range = VectorSpace(2);
+        let diff = FnOperator::new(&domain, &range, |x: &[f64], y: &mut [f64]| {
+            for (index, out) in y.iter_mut().enumerate() {
+                *out = x[1 + index] - x[index];
+            }
+            Ok(())
+        });
+
+        let op = &diff as &dyn OperatorBase<Domain = VectorSpace, Range = VectorSpace>;
+        assert!(op.has_apply());
+        assert!(!op.has_apply_adjoint());
+        assert!(!op.has_apply_transpose());
+        assert_eq!(op.domain().0, 3);
+        assert_eq!(op.range().0, 2);
+
+        let mut y = [0.0; 2];
+        op.apply(&[1.0, 4.0, 2.0], &mut y)?;
+        assert_eq!(y, [3.0, -2.0]);
+        assert!(matches!(
+            op.apply_adjoint(&[1.0, 1.0], &mut [0.0; 3]),
+            Err(SparseLinAlgError::NotImplemented(_))
+        ));
+
+        let diff = diff.with_adjoint(|x: &[f64], y: &mut [f64]| {
+            y.fill(0.0);
+            for (index, value) in x.iter().enumerate() {
+                y[index] -= value;
+                y[1 + index] += value;
+            }
+            Ok(())
+        });
+        assert!(diff.has_apply_adjoint());
+        let mut y = [0.0; 3];
+        diff.apply_adjoint(&[1.0, 2.0], &mut y)?;
+        assert_eq!(y, [-1.0, -1.0, 2.0]);
+
+        // Closure operators combine with the operator algebra.
+        let sum = SumOperator::new(&diff, &diff)?;
+        let mut y = [0.0; 2];
+        sum.apply(&[1.0, 4.0, 2.0], &mut y)?;
+        assert_eq!(y, [6.0, -4.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fn_operator_errors() {
+        let space = VectorSpace(2);
+        let failing = FnOperator::new(&space, &space, |_: &[f64], _: &mut [f64]| {
+            Err(SparseLinAlgError::OperationFailed("Apply".to_string()))
+        });
+        assert!(matches!(
+            failing.apply(&[0.0; 2], &mut [0.0; 2]),
+            Err(SparseLinAlgError::OperationFailed(_))
+        ));
+    }
+}
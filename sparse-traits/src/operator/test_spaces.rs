@@ -0,0 +1,91 @@
+//! Spaces shared by the tests of the operators.
+
+use crate::*;
+
+/// A space of real vectors of fixed length.
+#[derive(Debug, Clone)]
+pub(crate) struct VectorSpace(pub(crate) usize);
+
+pub(crate) struct Vector(pub(crate) Vec<f64>);
+
+impl Element for Vector {
+    type Space = VectorSpace;
+
+    fn view(&self) -> &[f64] {
+        &self.0
+    }
+
+    fn view_mut(&mut self) -> &mut [f64] {
+        &mut self.0
+    }
+}
+
+impl LinearSpace for VectorSpace {
+    type F = f64;
+    type E<'a> = Vector;
+    type View<'a> = &'a [f64];
+    type ViewMut<'a> = &'a mut [f64];
+
+    fn is_compatible(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn reborrow_view<'b, 'a: 'b>(x: &'b &'a [f64]) -> &'b [f64] {
+        x
+    }
+
+    fn reborrow_view_mut<'b, 'a: 'b>(y: &'b mut &'a mut [f64]) -> &'b mut [f64] {
+        y
+    }
+
+    fn create_element(&self) -> Vector {
+        Vector(vec![0.0; self.0])
+    }
+
+    fn axpy_inplace<'a>(
+        &self,
+        alpha: f64,
+        x: &&'a [f64],
+        y: &mut &'a mut [f64],
+    ) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        for (out, value) in y.iter_mut().zip(x.iter()) {
+            *out += alpha * value;
+        }
+        Ok(())
+    }
+
+    fn fill_inplace<'a>(&self, x: &&'a [f64], y: &mut &'a mut [f64]) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        y.copy_from_slice(x);
+        Ok(())
+    }
+
+    fn scale_inplace<'a>(&self, alpha: f64, y: &mut &'a mut [f64])
+    where
+        Self: 'a,
+    {
+        for value in y.iter_mut() {
+            *value *= alpha;
+        }
+    }
+}
+
+impl InnerProductSpace for VectorSpace {
+    fn inner<'a>(&self, x: &&'a [f64], other: &&'a [f64]) -> SparseLinAlgResult<f64>
+    where
+        Self: 'a,
+    {
+        Ok(x.iter().zip(other.iter()).map(|(x, y)| x * y).sum())
+    }
+}
+
+impl NormedSpace for VectorSpace {
+    fn norm<'a>(&'a self, x: &&'a [f64]) -> f64 {
+        x.iter().map(|x| x * x).sum::<f64>().sqrt()
+    }
+}
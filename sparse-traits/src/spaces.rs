@@ -7,6 +7,7 @@ pub mod indexable_space;
 pub mod inner_product_space;
 pub mod linear_space;
 pub mod normed_space;
+pub mod product_space;
 
 pub use dual_space::*;
 pub use element::*;
@@ -14,3 +15,4 @@ pub use indexable_space::*;
 pub use inner_product_space::*;
 pub use linear_space::*;
 pub use normed_space::*;
+pub use product_space::*;
@@ -1,6 +1,7 @@
 //! Linear spaces and their elements.
 
-use super::Element;
+use super::{Element, ElementView, ElementViewMut};
+use crate::types::SparseLinAlgResult;
 use crate::Scalar;
 
 /// Definition of a linear space
@@ -16,8 +17,69 @@ pub trait LinearSpace {
     where
         Self: 'b;
 
+    /// View type of elements of the space.
+    ///
+    /// The view type is associated with the space and not with the element
+    /// so that it only depends on the lifetime of the borrow. Generic code
+    /// can then borrow an element several times.
+    type View<'b>
+    where
+        Self: 'b;
+
+    /// Mutable view type of elements of the space.
+    type ViewMut<'b>
+    where
+        Self: 'b;
+
+    /// Return true if elements of `other` can be used in place of elements of this space.
+    ///
+    /// By default all instances of a space type are compatible. Spaces whose instances
+    /// differ, for example in their dimension, override this.
+    fn is_compatible(&self, _other: &Self) -> bool {
+        true
+    }
+
+    /// Reborrow a view for a shorter lifetime.
+    ///
+    /// Views need not be `Copy`. Generic code that passes the same view to several
+    /// operations or combines it with views of local elements reborrows it first.
+    fn reborrow_view<'b, 'a: 'b>(x: &'b ElementView<'a, Self>) -> ElementView<'b, Self>
+    where
+        Self: 'a;
+
+    /// Reborrow a mutable view for a shorter lifetime.
+    fn reborrow_view_mut<'b, 'a: 'b>(
+        y: &'b mut ElementViewMut<'a, Self>,
+    ) -> ElementViewMut<'b, Self>
+    where
+        Self: 'a;
+
     /// Create a new vector from the space.
     fn create_element<'b>(&'b self) -> Self::E<'b> {
         std::unimplemented!();
     }
+
+    /// Compute `y -> alpha * x + y`.
+    fn axpy_inplace<'a>(
+        &self,
+        alpha: Self::F,
+        x: &ElementView<'a, Self>,
+        y: &mut ElementViewMut<'a, Self>,
+    ) -> SparseLinAlgResult<()>
+    where
+        Self: 'a;
+
+    /// Copy `x` into `y`.
+    fn fill_inplace<'a>(
+        &self,
+        x: &ElementView<'a, Self>,
+        y: &mut ElementViewMut<'a, Self>,
+    ) -> SparseLinAlgResult<()>
+    where
+        Self: 'a;
+
+    /// Compute `y -> alpha * y`.
+    fn scale_inplace<'a>(&self, alpha: Self::F, y: &mut ElementViewMut<'a, Self>)
+    where
+        Self: 'a;
 }
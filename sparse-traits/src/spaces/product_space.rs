@@ -0,0 +1,250 @@
+//! Cartesian products of linear spaces.
+
+use num::{Float, Zero};
+
+use super::{Element, ElementView, ElementViewMut, InnerProductSpace, LinearSpace, NormedSpace};
+use crate::types::{Scalar, SparseLinAlgError, SparseLinAlgResult};
+
+/// The product `S_0 x S_1 x ... x S_{n-1}` of spaces of the same type.
+///
+/// This is the space of block vectors, for example velocity and pressure in a Stokes
+/// problem. The components may differ in their dimension. An element is a vector of
+/// elements of the components, and views are vectors of views of the components, so
+/// that `x[1]` is the view of the second block. All operations act componentwise, and
+/// the inner product is the sum of the inner products of the components.
+#[derive(Debug, Clone)]
+pub struct ProductSpace<S: LinearSpace> {
+    spaces: Vec<S>,
+}
+
+impl<S: LinearSpace> ProductSpace<S> {
+    /// Create the product of `spaces`.
+    pub fn new(spaces: Vec<S>) -> Self {
+        Self { spaces }
+    }
+
+    /// The number of components.
+    pub fn num_components(&self) -> usize {
+        self.spaces.len()
+    }
+
+    /// The component space with the given index.
+    pub fn component(&self, index: usize) -> &S {
+        &self.spaces[index]
+    }
+
+    /// The component spaces.
+    pub fn components(&self) -> &[S] {
+        &self.spaces
+    }
+
+    /// Return an error unless `actual` is the number of components.
+    pub(crate) fn check_components(&self, actual: usize) -> SparseLinAlgResult<()> {
+        if actual == self.spaces.len() {
+            Ok(())
+        } else {
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: self.spaces.len(),
+                actual,
+            })
+        }
+    }
+}
+
+/// An element of a [ProductSpace].
+pub struct ProductElement<'a, S: LinearSpace + 'a> {
+    space: &'a ProductSpace<S>,
+    parts: Vec<S::E<'a>>,
+}
+
+impl<'a, S: LinearSpace + 'a> ProductElement<'a, S> {
+    /// The elements of the components.
+    pub fn parts(&self) -> &[S::E<'a>] {
+        &self.parts
+    }
+
+    /// The mutable elements of the components.
+    pub fn parts_mut(&mut self) -> &mut [S::E<'a>] {
+        &mut self.parts
+    }
+}
+
+impl<'a, S: LinearSpace + 'a> Element for ProductElement<'a, S> {
+    type Space = ProductSpace<S>;
+
+    fn space(&self) -> &Self::Space {
+        self.space
+    }
+
+    fn view<'b>(&'b self) -> ElementView<'b, Self::Space> {
+        self.parts.iter().map(|part| part.view()).collect()
+    }
+
+    fn view_mut<'b>(&'b mut self) -> ElementViewMut<'b, Self::Space> {
+        self.parts.iter_mut().map(|part| part.view_mut()).collect()
+    }
+}
+
+impl<S: LinearSpace> LinearSpace for ProductSpace<S> {
+    type F = S::F;
+    type E<'b>
+        = ProductElement<'b, S>
+    where
+        Self: 'b;
+    type View<'b>
+        = Vec<ElementView<'b, S>>
+    where
+        Self: 'b;
+    type ViewMut<'b>
+        = Vec<ElementViewMut<'b, S>>
+    where
+        Self: 'b;
+
+    fn is_compatible(&self, other: &Self) -> bool {
+        self.spaces.len() == other.spaces.len()
+            && self
+                .spaces
+                .iter()
+                .zip(other.spaces.iter())
+                .all(|(space, other)| space.is_compatible(other))
+    }
+
+    fn reborrow_view<'b, 'a: 'b>(x: &'b ElementView<'a, Self>) -> ElementView<'b, Self>
+    where
+        Self: 'a,
+    {
+        x.iter().map(S::reborrow_view).collect()
+    }
+
+    fn reborrow_view_mut<'b, 'a: 'b>(
+        y: &'b mut ElementViewMut<'a, Self>,
+    ) -> ElementViewMut<'b, Self>
+    where
+        Self: 'a,
+    {
+        y.iter_mut().map(S::reborrow_view_mut).collect()
+    }
+
+    fn create_element<'b>(&'b self) -> Self::E<'b> {
+        ProductElement {
+            space: self,
+            parts: self
+                .spaces
+                .iter()
+                .map(|space| space.create_element())
+                .collect(),
+        }
+    }
+
+    fn axpy_inplace<'a>(
+        &self,
+        alpha: Self::F,
+        x: &ElementView<'a, Self>,
+        y: &mut ElementViewMut<'a, Self>,
+    ) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        self.check_components(x.len())?;
+        self.check_components(y.len())?;
+        for ((space, x), y) in self.spaces.iter().zip(x.iter()).zip(y.iter_mut()) {
+            space.axpy_inplace(alpha, x, y)?;
+        }
+        Ok(())
+    }
+
+    fn fill_inplace<'a>(
+        &self,
+        x: &ElementView<'a, Self>,
+        y: &mut ElementViewMut<'a, Self>,
+    ) -> SparseLinAlgResult<()>
+    where
+        Self: 'a,
+    {
+        self.check_components(x.len())?;
+        self.check_components(y.len())?;
+        for ((space, x), y) in self.spaces.iter().zip(x.iter()).zip(y.iter_mut()) {
+            space.fill_inplace(x, y)?;
+        }
+        Ok(())
+    }
+
+    fn scale_inplace<'a>(&self, alpha: Self::F, y: &mut ElementViewMut<'a, Self>)
+    where
+        Self: 'a,
+    {
+        for (space, y) in self.spaces.iter().zip(y.iter_mut()) {
+            space.scale_inplace(alpha, y);
+        }
+    }
+}
+
+impl<S: InnerProductSpace> InnerProductSpace for ProductSpace<S> {
+    fn inner<'a>(
+        &self,
+        x: &ElementView<'a, Self>,
+        other: &ElementView<'a, Self>,
+    ) -> SparseLinAlgResult<Self::F>
+    where
+        Self: 'a,
+    {
+        self.check_components(x.len())?;
+        self.check_components(other.len())?;
+        let mut result = Self::F::zero();
+        for ((space, x), other) in self.spaces.iter().zip(x.iter()).zip(other.iter()) {
+            result += space.inner(x, other)?;
+        }
+        Ok(result)
+    }
+}
+
+impl<S: NormedSpace> NormedSpace for ProductSpace<S> {
+    fn norm<'a>(&'a self, x: &ElementView<'a, Self>) -> <Self::F as Scalar>::Real {
+        let mut result = <Self::F as Scalar>::Real::zero();
+        for (space, x) in self.spaces.iter().zip(x.iter()) {
+            let norm = space.norm(x);
+            result += norm * norm;
+        }
+        Float::sqrt(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::operator::test_spaces::VectorSpace;
+
+    #[test]
+    fn test_product_space() -> SparseLinAlgResult<()> {
+        let space = ProductSpace::new(vec![VectorSpace(2), VectorSpace(1)]);
+        assert_eq!(space.num_components(), 2);
+        assert_eq!(space.component(1).0, 1);
+        assert!(space.is_compatible(&space.clone()));
+        assert!(!space.is_compatible(&ProductSpace::new(vec![VectorSpace(2)])));
+        assert!(!space.is_compatible(&ProductSpace::new(vec![VectorSpace(2), VectorSpace(2)])));
+
+        let mut x = space.create_element();
+        x.view_mut()[0].copy_from_slice(&[1.0, 2.0]);
+        x.view_mut()[1][0] = 2.0;
+        assert_eq!(x.parts().len(), 2);
+        assert_eq!(space.inner(&x.view(), &x.view())?, 9.0);
+        assert_eq!(space.norm(&x.view()), 3.0);
+
+        let mut y = space.create_element();
+        space.fill_inplace(&x.view(), &mut y.view_mut())?;
+        space.axpy_inplace(2.0, &x.view(), &mut y.view_mut())?;
+        space.scale_inplace(0.5, &mut y.view_mut());
+        assert_eq!(y.view(), vec![&[1.5, 3.0][..], &[3.0][..]]);
+
+        let short = vec![&[1.0, 2.0][..]];
+        assert!(matches!(
+            space.axpy_inplace(1.0, &short, &mut y.view_mut()),
+            Err(SparseLinAlgError::SingleDimensionError {
+                expected: 2,
+                actual: 1
+            })
+        ));
+        Ok(())
+    }
+}
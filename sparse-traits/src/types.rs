@@ -20,10 +20,26 @@ pub enum SparseLinAlgError {
         expected: IndexType,
         actual: IndexType,
     },
+    #[error("Index {index:} out of bounds. Dimension is {bound:}")]
+    IndexOutOfBounds { index: IndexType, bound: IndexType },
+    #[error("Invalid sparse matrix structure: {0}")]
+    InvalidStructure(String),
     #[error("Index Layout error: {0}")]
     IndexLayoutError(String),
     #[error("MPI Rank does not exist. {0}")]
     MpiRankError(i32),
+    #[error("Matrix Market parse error on line {line:}: {message:}")]
+    MatrixMarketError { line: usize, message: String },
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Incompatible spaces: {0}")]
+    IncompatibleSpaces(String),
+    #[error("Breakdown of {method:} in iteration {iteration:}: {message:}")]
+    SolverBreakdown {
+        method: String,
+        iteration: usize,
+        message: String,
+    },
 }
 
 pub type SparseLinAlgResult<T> = std::result::Result<T, SparseLinAlgError>;